@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+// resource limits imposed by the cgroup we run in, none outside containers; the suites
+// size their thread pools and buffers by it and the harness records it with the results
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CgroupLimits {
+    pub version: Option<u8>,
+    // quota expressed in cpus, e.g. 1.5 for "150000 100000"
+    pub cpu_quota: Option<f64>,
+    pub memory_limit_bytes: Option<u64>,
+}
+
+impl CgroupLimits {
+    pub fn detect() -> Self {
+        // cgroups are linux only, elsewhere (windows job objects, macos) the host count stands
+        if !cfg!(target_os = "linux") {
+            CgroupLimits::default()
+        } else if Path::new(CGROUP_ROOT).join("cgroup.controllers").exists() {
+            Self::detect_v2()
+        } else if Path::new(CGROUP_ROOT).join("cpu").exists() || Path::new(CGROUP_ROOT).join("memory").exists() {
+            Self::detect_v1()
+        } else {
+            CgroupLimits::default()
+        }
+    }
+
+    // cpus we should plan for: the host count, capped by any quota
+    pub fn effective_cpus(&self) -> usize {
+        let host = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        match self.cpu_quota {
+            Some(quota) => host.min(quota.ceil() as usize).max(1),
+            None => host,
+        }
+    }
+
+    fn detect_v2() -> Self {
+        // limits can sit on any ancestor, the tightest one wins
+        let mut cpu_quota: Option<f64> = None;
+        let mut memory_limit_bytes: Option<u64> = None;
+        for dir in v2_hierarchy() {
+            if let Some(quota) = read_v2_cpu_max(&dir.join("cpu.max")) {
+                cpu_quota = Some(cpu_quota.map_or(quota, |q| q.min(quota)));
+            }
+            if let Some(limit) = read_limit(&dir.join("memory.max")) {
+                memory_limit_bytes = Some(memory_limit_bytes.map_or(limit, |l| l.min(limit)));
+            }
+        }
+        CgroupLimits {
+            version: Some(2),
+            cpu_quota,
+            memory_limit_bytes,
+        }
+    }
+
+    fn detect_v1() -> Self {
+        let root = Path::new(CGROUP_ROOT);
+        let quota = read_number(&root.join("cpu/cpu.cfs_quota_us"));
+        let period = read_number(&root.join("cpu/cpu.cfs_period_us"));
+        let cpu_quota = match (quota, period) {
+            (Some(q), Some(p)) if q > 0.0 && p > 0.0 => Some(q / p),
+            _ => None,
+        };
+        CgroupLimits {
+            version: Some(1),
+            cpu_quota,
+            memory_limit_bytes: read_limit(&root.join("memory/memory.limit_in_bytes")),
+        }
+    }
+}
+
+// cpus we can actually use, the host count capped by a container cpu quota
+pub fn available_cpus() -> usize {
+    CgroupLimits::detect().effective_cpus()
+}
+
+// our own cgroup directory and all of its ancestors up to the mount root
+fn v2_hierarchy() -> Vec<PathBuf> {
+    let own = fs::read_to_string("/proc/self/cgroup")
+        .ok()
+        .and_then(|text| {
+            text.lines()
+                .find_map(|line| line.strip_prefix("0::").map(|p| p.trim().to_string()))
+        })
+        .unwrap_or_default();
+
+    let mut dirs = vec![PathBuf::from(CGROUP_ROOT)];
+    let mut current = PathBuf::from(CGROUP_ROOT);
+    for component in own.split('/').filter(|c| !c.is_empty()) {
+        current.push(component);
+        dirs.push(current.clone());
+    }
+    dirs
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// "max" or absurdly large values mean no limit
+fn read_limit(path: &Path) -> Option<u64> {
+    let value: u64 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    (value < 1 << 60).then_some(value)
+}
+
+fn read_v2_cpu_max(path: &Path) -> Option<f64> {
+    let text = fs::read_to_string(path).ok()?;
+    let mut parts = text.split_whitespace();
+    let quota: f64 = parts.next()?.parse().ok()?;
+    let period: f64 = parts.next()?.parse().ok()?;
+    (period > 0.0).then_some(quota / period)
+}
@@ -0,0 +1,111 @@
+use std::env;
+
+use crate::rng::RngKind;
+
+// a base size at a possibly fractional scale, never below one
+pub fn scaled(base: usize, scale: f64) -> usize {
+    ((base as f64 * scale).round() as usize).max(1)
+}
+
+// what a suite binary prints: the lone total by default, or --output tsv / --format json
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Format {
+    Total,
+    Tsv,
+    Json,
+}
+
+// a suite binary's command line: the scale first, then any of --output/--format,
+// --repeat, --warmup (RUST_BENCH_WARMUP when it isn't given), --strict and --rng
+// (RUST_BENCH_RNG when it isn't given)
+#[derive(Clone, Debug)]
+pub struct ScaleConfig {
+    pub scale: f64,
+    pub format: Format,
+    pub repeat: usize,
+    pub warmup: usize,
+    // stop at the first failed sub-test instead of running the rest
+    pub strict: bool,
+    // the generator every kernel's inputs come from instead of its native one
+    pub rng: RngKind,
+}
+
+impl Default for ScaleConfig {
+    fn default() -> ScaleConfig {
+        ScaleConfig {
+            scale: 1.0,
+            format: Format::Total,
+            repeat: 1,
+            warmup: 0,
+            strict: false,
+            rng: RngKind::Native,
+        }
+    }
+}
+
+impl ScaleConfig {
+    // `args` as env::args() returns them, program name included
+    pub fn from_args(args: &[String]) -> Result<ScaleConfig, String> {
+        let mut config = ScaleConfig::default();
+        if let Some(scale) = args.get(1) {
+            config.scale = scale.parse::<f64>().map_err(|_| format!("Invalid scale factor: {}", scale))?;
+            if !config.scale.is_finite() || config.scale <= 0.0 {
+                return Err("Scale factor must be a positive number".into());
+            }
+        }
+        config.format = output_format(args)?;
+        config.repeat = count_arg(args, "--repeat")?.unwrap_or(1);
+        if config.repeat == 0 {
+            return Err("--repeat needs at least one run".into());
+        }
+        config.warmup = match count_arg(args, "--warmup")? {
+            Some(warmup) => warmup,
+            None => match env::var("RUST_BENCH_WARMUP") {
+                Ok(value) => value.parse().unwrap_or_else(|_| {
+                    eprintln!("Ignoring RUST_BENCH_WARMUP={}, it isn't a whole number", value);
+                    0
+                }),
+                Err(_) => 0,
+            },
+        };
+        config.strict = args.iter().any(|a| a == "--strict");
+        config.rng = match args.iter().position(|a| a == "--rng") {
+            Some(i) => RngKind::parse(args.get(i + 1).map(String::as_str).unwrap_or(""))?,
+            None => match env::var("RUST_BENCH_RNG") {
+                Ok(value) => RngKind::parse(&value).unwrap_or_else(|err| {
+                    eprintln!("Ignoring RUST_BENCH_RNG: {}", err);
+                    RngKind::Native
+                }),
+                Err(_) => RngKind::Native,
+            },
+        };
+        Ok(config)
+    }
+
+    pub fn scaled(&self, base: usize) -> usize {
+        scaled(base, self.scale)
+    }
+}
+
+// --output and --format are the same option
+fn output_format(args: &[String]) -> Result<Format, String> {
+    let Some(i) = args.iter().position(|a| a == "--output" || a == "--format") else {
+        return Ok(Format::Total);
+    };
+    match args.get(i + 1).map(String::as_str) {
+        Some("tsv") => Ok(Format::Tsv),
+        Some("json") => Ok(Format::Json),
+        other => Err(format!("Unknown output format {:?}, use tsv or json", other.unwrap_or(""))),
+    }
+}
+
+// the count after `flag`, none when the flag isn't given
+fn count_arg(args: &[String], flag: &str) -> Result<Option<usize>, String> {
+    let Some(i) = args.iter().position(|a| a == flag) else {
+        return Ok(None);
+    };
+    match args.get(i + 1).and_then(|n| n.parse::<usize>().ok()) {
+        Some(count) => Ok(Some(count)),
+        None => Err(format!("{} needs a whole number", flag)),
+    }
+}
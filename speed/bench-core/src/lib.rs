@@ -0,0 +1,127 @@
+// what the rust suite binaries share: timing, the kernels' generators, the command line
+// and the reports; a new suite implements Benchmark and gets all of it
+mod cgroup;
+mod config;
+mod report;
+mod rng;
+mod timer;
+
+pub use cgroup::{available_cpus, CgroupLimits};
+pub use config::{scaled, Format, ScaleConfig};
+pub use report::{metadata_hash, total, Stats, SKIPPED};
+pub use rng::{select_rng, selected_rng, ChaCha20, InputRng, Lcg, Pcg64, Rng, RngKind, XorShift64};
+pub use timer::Timer;
+
+// one entry per timed sub-test in run order, main sums them and the rust harness
+// (speed/harness) runs them by name
+#[derive(Clone, Copy)]
+pub struct SubTest {
+    pub name: &'static str,
+    // the parameters the test runs with at a given scale, for dry runs and reports
+    pub params: fn(f64) -> String,
+    pub run: fn(f64) -> Result<f64, String>,
+}
+
+// a tiny-size correctness check behind --self-test, so a change to a kernel can't
+// silently change what it computes
+#[derive(Clone, Copy)]
+pub struct SelfCheck {
+    pub name: &'static str,
+    pub check: fn() -> Result<(), String>,
+}
+
+// a full-size correctness check behind --verify: reruns a sub-test's kernel at the run's
+// scale and compares what it computed with known-good values, which the tiny self check
+// inputs can't stand in for
+#[derive(Clone, Copy)]
+pub struct Verification {
+    pub name: &'static str,
+    pub check: fn(f64) -> Result<(), String>,
+}
+
+// one sub-test's samples in ms, one per --repeat run, or why it failed
+pub type Outcome = Result<Vec<f64>, String>;
+
+pub trait Benchmark: Sized {
+    // the suite name, as the scripts and the harness know it
+    const NAME: &'static str;
+    const SUBTESTS: &'static [SubTest];
+    const SELF_CHECKS: &'static [SelfCheck];
+    // most suites have nothing to verify beyond their self checks
+    const VERIFICATIONS: &'static [Verification] = &[];
+
+    // runs every sub-test config.warmup times untimed, then config.repeat times measured;
+    // a sub-test's outcome is the first error when any of its runs failed, and with
+    // config.strict the ones after a failure are skipped
+    fn measure(config: &ScaleConfig) -> Vec<Outcome> {
+        select_rng(config.rng);
+        let mut failed = false;
+        Self::SUBTESTS
+            .iter()
+            .map(|test| {
+                if failed && config.strict {
+                    return Err(report::SKIPPED.to_string());
+                }
+                for _ in 0..config.warmup {
+                    let _ = (test.run)(config.scale);
+                }
+                let outcome: Outcome = (0..config.repeat).map(|_| (test.run)(config.scale)).collect();
+                failed |= outcome.is_err();
+                outcome
+            })
+            .collect()
+    }
+
+    // prints the outcomes the way config.format asks for, on stdout
+    fn report(config: &ScaleConfig, outcomes: &[Outcome]) {
+        match config.format {
+            Format::Total => {
+                if config.repeat > 1 {
+                    report::print_stats::<Self>(outcomes);
+                }
+                println!("{:.3}", total(outcomes));
+            }
+            Format::Tsv => report::print_tsv::<Self>(outcomes, config.scale),
+            Format::Json => report::print_json::<Self>(outcomes, config),
+        }
+    }
+
+    // lists the failed and skipped sub-tests on stderr and returns how many there were;
+    // the mains exit non-zero when there are any, so a missing input can't pass for a
+    // fast run with a smaller total
+    fn failures(outcomes: &[Outcome]) -> usize {
+        report::print_failures::<Self>(outcomes)
+    }
+
+    // runs every self check, reporting on stderr; false when any of them failed
+    fn self_test() -> bool {
+        let mut passed = true;
+        for check in Self::SELF_CHECKS {
+            match (check.check)() {
+                Ok(()) => eprintln!("ok    {}", check.name),
+                Err(err) => {
+                    eprintln!("FAIL  {}: {}", check.name, err);
+                    passed = false;
+                }
+            }
+        }
+        passed
+    }
+
+    // runs every verification at config.scale on config.rng's inputs, reporting on stderr;
+    // false when any of them failed
+    fn verify(config: &ScaleConfig) -> bool {
+        select_rng(config.rng);
+        let mut passed = true;
+        for verification in Self::VERIFICATIONS {
+            match (verification.check)(config.scale) {
+                Ok(()) => eprintln!("ok    {}", verification.name),
+                Err(err) => {
+                    eprintln!("FAIL  {}: {}", verification.name, err);
+                    passed = false;
+                }
+            }
+        }
+        passed
+    }
+}
@@ -0,0 +1,163 @@
+use std::env;
+
+use crate::rng::{selected_rng, RngKind};
+use crate::{Benchmark, Outcome, ScaleConfig};
+
+// min, median, mean, sample standard deviation and nearest-rank p95 of one sub-test's
+// --repeat samples
+pub struct Stats {
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    pub p95: f64,
+}
+
+impl Stats {
+    // `samples` must not be empty
+    pub fn of(samples: &[f64]) -> Stats {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            sorted.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        let median = if n % 2 == 1 { sorted[n / 2] } else { (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0 };
+        let p95 = sorted[(n as f64 * 0.95).ceil() as usize - 1];
+        Stats { min: sorted[0], median, mean, stddev: variance.sqrt(), p95 }
+    }
+}
+
+// the error of the sub-tests --strict didn't run
+pub const SKIPPED: &str = "skipped, an earlier sub-test failed and --strict is set";
+
+// the sum of every sub-test's median, failed ones count as zero
+pub fn total(outcomes: &[Outcome]) -> f64 {
+    outcomes
+        .iter()
+        .filter_map(|o| o.as_ref().ok())
+        .fold(0.0, |total, samples| total + Stats::of(samples).median)
+}
+
+// the per sub-test summary on stderr, so the total on stdout stays the only thing scripts read
+pub(crate) fn print_stats<B: Benchmark>(outcomes: &[Outcome]) {
+    eprintln!("{:<28} {:>10} {:>10} {:>10} {:>10} {:>10}", "test", "min", "median", "mean", "stddev", "p95");
+    for (test, outcome) in B::SUBTESTS.iter().zip(outcomes) {
+        match outcome {
+            Ok(samples) => {
+                let s = Stats::of(samples);
+                eprintln!(
+                    "{:<28} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3}",
+                    test.name, s.min, s.median, s.mean, s.stddev, s.p95
+                );
+            }
+            Err(err) => eprintln!("{:<28} failed: {}", test.name, err),
+        }
+    }
+}
+
+pub(crate) fn print_failures<B: Benchmark>(outcomes: &[Outcome]) -> usize {
+    let failed: Vec<(&str, &String)> = B::SUBTESTS
+        .iter()
+        .zip(outcomes)
+        .filter_map(|(test, outcome)| outcome.as_ref().err().map(|err| (test.name, err)))
+        .collect();
+    if !failed.is_empty() {
+        eprintln!("{} of {} {} sub-tests have no timing, the total leaves them out:", failed.len(), outcomes.len(), B::NAME);
+        for (name, err) in &failed {
+            eprintln!("  {}: {}", name, err);
+        }
+    }
+    failed.len()
+}
+
+// fnv-1a over the suite, platform, every sub-test's parameters at this scale and any
+// non-native generator, so runs with the same hash measured the same thing (std's hasher isn't stable across releases)
+pub fn metadata_hash<B: Benchmark>(scale: f64) -> u64 {
+    let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut text = format!("{}\t{}\t{}\t{}", B::NAME, env::consts::OS, env::consts::ARCH, cpus);
+    for test in B::SUBTESTS {
+        text.push_str(&format!("\t{}={}", test.name, (test.params)(scale)));
+    }
+    // other inputs than the other languages', so their runs can't pass for comparable
+    if selected_rng() != RngKind::Native {
+        text.push_str(&format!("\trng={}", selected_rng().name()));
+    }
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+// --output tsv: one tab separated line of every sub-test's median ms in SUBTESTS order
+// (empty when it failed), then the total and the metadata hash
+pub(crate) fn print_tsv<B: Benchmark>(outcomes: &[Outcome], scale: f64) {
+    let mut fields: Vec<String> = outcomes
+        .iter()
+        .map(|o| o.as_ref().map(|samples| format!("{:.3}", Stats::of(samples).median)).unwrap_or_default())
+        .collect();
+    fields.push(format!("{:.3}", total(outcomes)));
+    fields.push(format!("{:016x}", metadata_hash::<B>(scale)));
+    println!("{}", fields.join("\t"));
+}
+
+// a json string literal, written by hand so the suites need no serializer
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// --format json: one line with every sub-test's name, duration (the median, null with an
+// error when it failed), iterations, parameters, summary and samples, plus the warmup
+// runs, the total and the metadata hash
+pub(crate) fn print_json<B: Benchmark>(outcomes: &[Outcome], config: &ScaleConfig) {
+    let subtests: Vec<String> = B::SUBTESTS
+        .iter()
+        .zip(outcomes)
+        .map(|(test, outcome)| {
+            let duration = match outcome {
+                Ok(samples) => {
+                    let s = Stats::of(samples);
+                    let samples: Vec<String> = samples.iter().map(|ms| format!("{:.3}", ms)).collect();
+                    format!(
+                        "\"duration_ms\":{:.3},\"min_ms\":{:.3},\"mean_ms\":{:.3},\"stddev_ms\":{:.3},\"p95_ms\":{:.3},\"samples_ms\":[{}]",
+                        s.median,
+                        s.min,
+                        s.mean,
+                        s.stddev,
+                        s.p95,
+                        samples.join(",")
+                    )
+                }
+                Err(err) => format!("\"duration_ms\":null,\"error\":{}", json_string(err)),
+            };
+            format!(
+                "{{\"name\":{},{},\"iterations\":{},\"params\":{}}}",
+                json_string(test.name),
+                duration,
+                config.repeat,
+                json_string(&(test.params)(config.scale))
+            )
+        })
+        .collect();
+    println!(
+        "{{\"suite\":{},\"scale\":{},\"warmup\":{},\"rng\":{},\"total_ms\":{:.3},\"metadata_hash\":\"{:016x}\",\"subtests\":[{}]}}",
+        json_string(B::NAME),
+        config.scale,
+        config.warmup,
+        json_string(config.rng.name()),
+        total(outcomes),
+        metadata_hash::<B>(config.scale),
+        subtests.join(",")
+    );
+}
@@ -0,0 +1,296 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// the generators the kernels fill their inputs with; the other languages' versions use
+// the same sequences, so changing one changes what every suite measures
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    // 15 bits, 0..=32767, the range rand() returns
+    fn next_bits(&mut self) -> u32 {
+        (self.next_u64() >> 49) as u32
+    }
+
+    // uniform in [0, 1), from the top 53 bits
+    fn next_unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // uniform in 0..n, by multiplying rather than taking a remainder so small ranges
+    // aren't biased; n must be positive
+    fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+// the c library style lcg the mathematical and concurrency kernels use; its helpers
+// keep rand()'s 15 bits so the inputs match the other languages bit for bit
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Lcg {
+        Lcg { state: seed }
+    }
+}
+
+impl Rng for Lcg {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_mul(1103515245).wrapping_add(12345);
+        self.state
+    }
+
+    fn next_bits(&mut self) -> u32 {
+        ((self.next_u64() >> 16) & 0x7fff) as u32
+    }
+
+    // uniform in [0, 1], both ends included
+    fn next_unit(&mut self) -> f64 {
+        self.next_bits() as f64 / 32767.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_bits() as u64 % n
+    }
+}
+
+// xorshift64, what the memory kernels draw sizes and indices from
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    // zero is xorshift's one fixed point and would yield only zeros, e.g. for the first
+    // tile's seed in matrix_tile_work, so it starts from the splitmix constant instead;
+    // every other seed keeps its stream
+    pub fn new(seed: u64) -> XorShift64 {
+        let state = if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed };
+        XorShift64 { state }
+    }
+}
+
+impl Rng for XorShift64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+// spreads one seed over the wider states below, so nearby seeds don't start nearby
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+// pcg64 (xsl rr 128/64): a 128-bit lcg whose high half is folded and rotated into each
+// output, passing the statistical tests the plain lcgs fail
+pub struct Pcg64 {
+    state: u128,
+    increment: u128,
+}
+
+const PCG_MULTIPLIER: u128 = 0x2360_ed05_1fc6_5da4_4385_df64_9fcc_f645;
+
+impl Pcg64 {
+    pub fn new(seed: u64) -> Pcg64 {
+        let mut mix = SplitMix64 { state: seed };
+        let mut wide = || (mix.next() as u128) << 64 | mix.next() as u128;
+        let state = wide();
+        // the stream, which has to be odd
+        let increment = wide() | 1;
+        let mut pcg = Pcg64 { state: state.wrapping_add(increment), increment };
+        pcg.next_u64();
+        pcg
+    }
+}
+
+impl Rng for Pcg64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_mul(PCG_MULTIPLIER).wrapping_add(self.increment);
+        let rotation = (self.state >> 122) as u32;
+        ((self.state >> 64) as u64 ^ self.state as u64).rotate_right(rotation)
+    }
+}
+
+// the chacha20 block function as a generator: a key from the seed, a 64-bit block
+// counter and a zero nonce, each block giving eight outputs
+pub struct ChaCha20 {
+    state: [u32; 16],
+    block: [u64; 8],
+    used: usize,
+}
+
+impl ChaCha20 {
+    pub fn new(seed: u64) -> ChaCha20 {
+        let mut mix = SplitMix64 { state: seed };
+        // "expand 32-byte k"
+        let mut state = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        for word in state[4..12].chunks_exact_mut(2) {
+            let key = mix.next();
+            word[0] = key as u32;
+            word[1] = (key >> 32) as u32;
+        }
+        ChaCha20 { state, block: [0; 8], used: 8 }
+    }
+
+    fn next_block(&mut self) {
+        let mut x = self.state;
+        // ten double rounds: the columns, then the diagonals
+        for _ in 0..10 {
+            quarter_round(&mut x, 0, 4, 8, 12);
+            quarter_round(&mut x, 1, 5, 9, 13);
+            quarter_round(&mut x, 2, 6, 10, 14);
+            quarter_round(&mut x, 3, 7, 11, 15);
+            quarter_round(&mut x, 0, 5, 10, 15);
+            quarter_round(&mut x, 1, 6, 11, 12);
+            quarter_round(&mut x, 2, 7, 8, 13);
+            quarter_round(&mut x, 3, 4, 9, 14);
+        }
+        for (i, output) in self.block.iter_mut().enumerate() {
+            let low = x[2 * i].wrapping_add(self.state[2 * i]);
+            let high = x[2 * i + 1].wrapping_add(self.state[2 * i + 1]);
+            *output = low as u64 | (high as u64) << 32;
+        }
+        let counter = (self.state[12] as u64 | (self.state[13] as u64) << 32).wrapping_add(1);
+        self.state[12] = counter as u32;
+        self.state[13] = (counter >> 32) as u32;
+        self.used = 0;
+    }
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+impl Rng for ChaCha20 {
+    fn next_u64(&mut self) -> u64 {
+        if self.used == self.block.len() {
+            self.next_block();
+        }
+        self.used += 1;
+        self.block[self.used - 1]
+    }
+}
+
+// which generator the kernels' inputs come from, --rng / RUST_BENCH_RNG; `native` is
+// whichever one the kernel's other-language versions use
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RngKind {
+    #[default]
+    Native,
+    Lcg,
+    XorShift,
+    Pcg64,
+    ChaCha20,
+}
+
+impl RngKind {
+    pub const ALL: [RngKind; 5] = [RngKind::Native, RngKind::Lcg, RngKind::XorShift, RngKind::Pcg64, RngKind::ChaCha20];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RngKind::Native => "native",
+            RngKind::Lcg => "lcg",
+            RngKind::XorShift => "xorshift",
+            RngKind::Pcg64 => "pcg64",
+            RngKind::ChaCha20 => "chacha20",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<RngKind, String> {
+        RngKind::ALL.into_iter().find(|kind| kind.name() == name).ok_or_else(|| {
+            let names: Vec<&str> = RngKind::ALL.iter().map(|kind| kind.name()).collect();
+            format!("Unknown generator {:?}, use one of {}", name, names.join(", "))
+        })
+    }
+}
+
+// the sub-tests only get the scale, so the choice is process wide
+static SELECTED: AtomicU8 = AtomicU8::new(0);
+
+pub fn select_rng(kind: RngKind) {
+    SELECTED.store(kind as u8, Ordering::Relaxed);
+}
+
+pub fn selected_rng() -> RngKind {
+    RngKind::ALL[SELECTED.load(Ordering::Relaxed) as usize]
+}
+
+// the generator a kernel fills its inputs with: its native one unless another was
+// selected, seeded the same way either way so every run sees the same inputs
+pub enum InputRng {
+    Lcg(Lcg),
+    XorShift(XorShift64),
+    Pcg64(Pcg64),
+    ChaCha20(ChaCha20),
+}
+
+impl InputRng {
+    pub fn new(native: RngKind, seed: u64) -> InputRng {
+        let kind = match selected_rng() {
+            RngKind::Native => native,
+            kind => kind,
+        };
+        match kind {
+            RngKind::Native | RngKind::Lcg => InputRng::Lcg(Lcg::new(seed)),
+            RngKind::XorShift => InputRng::XorShift(XorShift64::new(seed)),
+            RngKind::Pcg64 => InputRng::Pcg64(Pcg64::new(seed)),
+            RngKind::ChaCha20 => InputRng::ChaCha20(ChaCha20::new(seed)),
+        }
+    }
+
+    // for the kernels whose native generator is the lcg
+    pub fn lcg(seed: u64) -> InputRng {
+        InputRng::new(RngKind::Lcg, seed)
+    }
+
+    // for the kernels whose native generator is xorshift64
+    pub fn xorshift(seed: u64) -> InputRng {
+        InputRng::new(RngKind::XorShift, seed)
+    }
+
+    fn inner(&mut self) -> &mut dyn Rng {
+        match self {
+            InputRng::Lcg(rng) => rng,
+            InputRng::XorShift(rng) => rng,
+            InputRng::Pcg64(rng) => rng,
+            InputRng::ChaCha20(rng) => rng,
+        }
+    }
+}
+
+impl Rng for InputRng {
+    fn next_u64(&mut self) -> u64 {
+        self.inner().next_u64()
+    }
+
+    fn next_bits(&mut self) -> u32 {
+        self.inner().next_bits()
+    }
+
+    fn next_unit(&mut self) -> f64 {
+        self.inner().next_unit()
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.inner().below(n)
+    }
+}
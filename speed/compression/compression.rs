@@ -0,0 +1,349 @@
+use std::env;
+use std::hint::black_box;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+
+use bench_core::{scaled, Benchmark, InputRng, Rng, ScaleConfig, SelfCheck, SubTest, Timer};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+// debug flag - set via environment variable
+fn debug_enabled() -> bool {
+    env::var("RUST_BENCH_DEBUG").is_ok()
+}
+
+// debug print helper
+macro_rules! debug_print {
+    ($($arg:tt)*) => {
+        if debug_enabled() {
+            eprintln!("[DEBUG] {}", format!($($arg)*));
+        }
+    };
+}
+
+// bytes of every input kind at scale 1
+const INPUT_SIZE: usize = 4 << 20;
+
+const WORDS: &[&str] = &[
+    "the", "of", "and", "to", "in", "is", "that", "for", "it", "as", "was", "with", "be", "by", "on", "not",
+    "he", "this", "are", "or", "his", "from", "at", "which", "but", "have", "an", "had", "they", "you",
+    "were", "their", "one", "all", "we", "can", "her", "has", "there", "been", "if", "more", "when", "will",
+    "would", "who", "so", "no", "benchmark", "compression", "throughput", "latency", "memory", "thread",
+    "buffer", "window", "dictionary", "entropy", "stream", "block", "frame", "checksum", "allocator",
+];
+
+// one codec at the level the sub-tests use; decompress gets the original length, which
+// the bulk apis want up front
+pub struct Codec {
+    name: &'static str,
+    level: &'static str,
+    compress: fn(&[u8]) -> io::Result<Vec<u8>>,
+    decompress: fn(&[u8], usize) -> io::Result<Vec<u8>>,
+}
+
+const GZIP: Codec = Codec {
+    name: "gzip",
+    level: "6",
+    compress: |data| {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::new(6));
+        encoder.write_all(data)?;
+        encoder.finish()
+    },
+    decompress: |data, len| {
+        let mut out = Vec::with_capacity(len);
+        GzDecoder::new(data).read_to_end(&mut out)?;
+        Ok(out)
+    },
+};
+
+const ZSTD: Codec = Codec {
+    name: "zstd",
+    level: "3",
+    compress: |data| zstd::bulk::compress(data, 3),
+    decompress: |data, len| zstd::bulk::decompress(data, len),
+};
+
+// the frame format the lz4 tool writes; lz4_flex only has the default fast level
+const LZ4: Codec = Codec {
+    name: "lz4",
+    level: "frame",
+    compress: |data| {
+        let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+        encoder.write_all(data)?;
+        encoder.finish().map_err(io::Error::other)
+    },
+    decompress: |data, len| {
+        let mut out = Vec::with_capacity(len);
+        lz4_flex::frame::FrameDecoder::new(data).read_to_end(&mut out)?;
+        Ok(out)
+    },
+};
+
+// quality 11 is the library default but far too slow to compare, 5 is what web servers use
+const BROTLI: Codec = Codec {
+    name: "brotli",
+    level: "5",
+    compress: |data| {
+        let mut writer = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+        writer.write_all(data)?;
+        writer.flush()?;
+        Ok(writer.into_inner())
+    },
+    decompress: |data, len| {
+        let mut out = Vec::with_capacity(len);
+        brotli::Decompressor::new(data, 4096).read_to_end(&mut out)?;
+        Ok(out)
+    },
+};
+
+pub const CODECS: &[&Codec] = &[&GZIP, &ZSTD, &LZ4, &BROTLI];
+
+// what gets compressed: prose-like text, telemetry-like binary records, and data that's
+// already been through deflate, which no codec should shrink much further
+pub struct Input {
+    kind: &'static str,
+    data: Vec<u8>,
+}
+
+fn text_input(size: usize, seed: u64) -> Vec<u8> {
+    let mut rng = InputRng::lcg(seed);
+    let mut text = Vec::with_capacity(size + 16);
+    let mut words_in_line = 0;
+    while text.len() < size {
+        text.extend_from_slice(WORDS[rng.below(WORDS.len() as u64) as usize].as_bytes());
+        words_in_line += 1;
+        if words_in_line == 12 {
+            text.extend_from_slice(b".\n");
+            words_in_line = 0;
+        } else {
+            text.push(b' ');
+        }
+    }
+    text.truncate(size);
+    text
+}
+
+// fixed-size records: a running sequence number, one of 16 sensor ids and a random walk
+fn binary_input(size: usize) -> Vec<u8> {
+    let mut rng = InputRng::xorshift(42);
+    let mut data = Vec::with_capacity(size + 16);
+    let mut reading = 20.0f32;
+    let mut sequence = 0u32;
+    while data.len() < size {
+        reading += (rng.next_u64() % 201) as f32 / 100.0 - 1.0;
+        data.extend_from_slice(&sequence.to_le_bytes());
+        data.extend_from_slice(&((rng.next_u64() % 16) as u16).to_le_bytes());
+        data.extend_from_slice(&reading.to_le_bytes());
+        sequence += 1;
+    }
+    data.truncate(size);
+    data
+}
+
+// gzip members of different text until there's enough
+fn compressed_input(size: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(size);
+    let mut seed = 1;
+    while data.len() < size {
+        data.extend_from_slice(&(GZIP.compress)(&text_input(1 << 20, seed)).expect("gzip into memory can't fail"));
+        seed += 1;
+    }
+    data.truncate(size);
+    data
+}
+
+// the inputs at `size` bytes each; generating them takes longer than some of the codecs,
+// so the last size stays cached for the sub-tests that follow
+fn inputs(size: usize) -> Arc<Vec<Input>> {
+    static CACHE: Mutex<Option<(usize, Arc<Vec<Input>>)>> = Mutex::new(None);
+    let mut cache = CACHE.lock().unwrap();
+    if let Some((cached, inputs)) = cache.as_ref() {
+        if *cached == size {
+            return inputs.clone();
+        }
+    }
+    let inputs = Arc::new(vec![
+        Input { kind: "text", data: text_input(size, 42) },
+        Input { kind: "binary", data: binary_input(size) },
+        Input { kind: "compressed", data: compressed_input(size) },
+    ]);
+    *cache = Some((size, inputs.clone()));
+    inputs
+}
+
+// compresses every input once, timing only the codec
+fn compress_test(codec: &Codec, size: usize) -> Result<f64, String> {
+    let mut total_ms = 0.0;
+    for input in inputs(size).iter() {
+        let timer = Timer::start();
+        let compressed = (codec.compress)(black_box(&input.data)).map_err(|e| format!("{} {}: {}", codec.name, input.kind, e))?;
+        let ms = timer.elapsed_ms();
+        debug_print!("{} compress {}: {} -> {} bytes in {:.3}ms", codec.name, input.kind, input.data.len(), compressed.len(), ms);
+        black_box(compressed);
+        total_ms += ms;
+    }
+    Ok(total_ms)
+}
+
+// decompresses every input once, after compressing it untimed
+fn decompress_test(codec: &Codec, size: usize) -> Result<f64, String> {
+    let mut total_ms = 0.0;
+    for input in inputs(size).iter() {
+        let compressed = (codec.compress)(&input.data).map_err(|e| format!("{} {}: {}", codec.name, input.kind, e))?;
+        let timer = Timer::start();
+        let restored = (codec.decompress)(black_box(&compressed), input.data.len())
+            .map_err(|e| format!("{} {}: {}", codec.name, input.kind, e))?;
+        let ms = timer.elapsed_ms();
+        if restored.len() != input.data.len() {
+            return Err(format!("{} {}: {} bytes came back, expected {}", codec.name, input.kind, restored.len(), input.data.len()));
+        }
+        debug_print!("{} decompress {}: {} bytes in {:.3}ms", codec.name, input.kind, restored.len(), ms);
+        total_ms += ms;
+    }
+    Ok(total_ms)
+}
+
+// original size over compressed size for every codec and input, printed by main since a
+// sub-test only reports its time
+fn print_ratios(size: usize) {
+    let inputs = inputs(size);
+    eprint!("{:<8}", "ratio");
+    for input in inputs.iter() {
+        eprint!(" {:>11}", input.kind);
+    }
+    eprintln!();
+    for codec in CODECS {
+        eprint!("{:<8}", codec.name);
+        for input in inputs.iter() {
+            match (codec.compress)(&input.data) {
+                Ok(compressed) => eprint!(" {:>11.3}", input.data.len() as f64 / compressed.len() as f64),
+                Err(_) => eprint!(" {:>11}", "failed"),
+            }
+        }
+        eprintln!();
+    }
+}
+
+fn params(codec: &Codec, scale: f64) -> String {
+    format!("level={} inputs=text,binary,compressed size={}", codec.level, scaled(INPUT_SIZE, scale))
+}
+
+pub const SUBTESTS: &[SubTest] = &[
+    SubTest {
+        name: "gzip_compress",
+        params: |scale| params(&GZIP, scale),
+        run: |scale| compress_test(&GZIP, scaled(INPUT_SIZE, scale)),
+    },
+    SubTest {
+        name: "gzip_decompress",
+        params: |scale| params(&GZIP, scale),
+        run: |scale| decompress_test(&GZIP, scaled(INPUT_SIZE, scale)),
+    },
+    SubTest {
+        name: "zstd_compress",
+        params: |scale| params(&ZSTD, scale),
+        run: |scale| compress_test(&ZSTD, scaled(INPUT_SIZE, scale)),
+    },
+    SubTest {
+        name: "zstd_decompress",
+        params: |scale| params(&ZSTD, scale),
+        run: |scale| decompress_test(&ZSTD, scaled(INPUT_SIZE, scale)),
+    },
+    SubTest {
+        name: "lz4_compress",
+        params: |scale| params(&LZ4, scale),
+        run: |scale| compress_test(&LZ4, scaled(INPUT_SIZE, scale)),
+    },
+    SubTest {
+        name: "lz4_decompress",
+        params: |scale| params(&LZ4, scale),
+        run: |scale| decompress_test(&LZ4, scaled(INPUT_SIZE, scale)),
+    },
+    SubTest {
+        name: "brotli_compress",
+        params: |scale| params(&BROTLI, scale),
+        run: |scale| compress_test(&BROTLI, scaled(INPUT_SIZE, scale)),
+    },
+    SubTest {
+        name: "brotli_decompress",
+        params: |scale| params(&BROTLI, scale),
+        run: |scale| decompress_test(&BROTLI, scaled(INPUT_SIZE, scale)),
+    },
+];
+
+// every input survives a round trip unchanged, text shrinks and deflated data doesn't
+fn round_trip(codec: &Codec) -> Result<(), String> {
+    for input in inputs(64 << 10).iter() {
+        let compressed = (codec.compress)(&input.data).map_err(|e| format!("{}: {}", input.kind, e))?;
+        let restored = (codec.decompress)(&compressed, input.data.len()).map_err(|e| format!("{}: {}", input.kind, e))?;
+        if restored != input.data {
+            return Err(format!("the {} input changed in a round trip", input.kind));
+        }
+        let ratio = input.data.len() as f64 / compressed.len() as f64;
+        match input.kind {
+            "text" if ratio < 2.0 => return Err(format!("text only compressed {:.2}x", ratio)),
+            "compressed" if ratio > 1.1 => return Err(format!("deflated input compressed {:.2}x", ratio)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// the decoders must refuse a damaged stream rather than return garbage
+fn rejects_damage(codec: &Codec) -> Result<(), String> {
+    let data = text_input(4096, 7);
+    let mut compressed = (codec.compress)(&data).map_err(|e| e.to_string())?;
+    let middle = compressed.len() / 2;
+    compressed.truncate(middle);
+    match (codec.decompress)(&compressed, data.len()) {
+        Ok(restored) => Err(format!("a stream cut in half decoded to {} of {} bytes", restored.len(), data.len())),
+        Err(_) => Ok(()),
+    }
+}
+
+pub const SELF_CHECKS: &[SelfCheck] = &[
+    SelfCheck { name: "gzip_compress", check: || round_trip(&GZIP) },
+    SelfCheck { name: "gzip_decompress", check: || rejects_damage(&GZIP) },
+    SelfCheck { name: "zstd_compress", check: || round_trip(&ZSTD) },
+    SelfCheck { name: "zstd_decompress", check: || rejects_damage(&ZSTD) },
+    SelfCheck { name: "lz4_compress", check: || round_trip(&LZ4) },
+    SelfCheck { name: "lz4_decompress", check: || rejects_damage(&LZ4) },
+    SelfCheck { name: "brotli_compress", check: || round_trip(&BROTLI) },
+    SelfCheck { name: "brotli_decompress", check: || rejects_damage(&BROTLI) },
+];
+
+pub struct Compression;
+
+impl Benchmark for Compression {
+    const NAME: &'static str = "compression";
+    const SUBTESTS: &'static [SubTest] = SUBTESTS;
+    const SELF_CHECKS: &'static [SelfCheck] = SELF_CHECKS;
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--self-test") {
+        std::process::exit(if Compression::self_test() { 0 } else { 1 });
+    }
+    // unoptimized timings would end up in the results as if they were representative
+    if cfg!(debug_assertions) && !args.iter().any(|a| a == "--allow-debug") {
+        eprintln!("This binary was built without optimizations, rebuild it in release mode or pass --allow-debug after the scale");
+        std::process::exit(1);
+    }
+    let config = ScaleConfig::from_args(&args).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+
+    let outcomes = Compression::measure(&config);
+    for (test, outcome) in SUBTESTS.iter().zip(&outcomes) {
+        if let Err(err) = outcome {
+            debug_print!("{} test failed: {}", test.name, err);
+        }
+    }
+    print_ratios(config.scaled(INPUT_SIZE));
+    Compression::report(&config, &outcomes);
+    if Compression::failures(&outcomes) > 0 {
+        std::process::exit(1);
+    }
+}
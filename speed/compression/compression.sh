@@ -0,0 +1,96 @@
+#!/bin/bash
+
+SCALE_FACTOR=1 # this controls how many bytes go through each codec
+
+# detect if we're running on windows/mingw64
+if [[ "$OSTYPE" == "msys" ]] || [[ "$OSTYPE" == "win32" ]] || [[ "$MINGW_CHOST" ]]; then
+    IS_WINDOWS=true
+    EXE_EXT=".exe"
+    echo "Detected Windows/MINGW64 environment"
+else
+    IS_WINDOWS=false
+    EXE_EXT=""
+    echo "Detected Unix-like environment"
+fi
+
+# only rust so far, the other languages need third party libraries for zstd, lz4 and brotli
+echo "Checking if all required tools are installed"
+TOOLS=("rustc" "cargo" "hyperfine")
+all_tools_found=true
+for tool in "${TOOLS[@]}"; do
+  if ! command -v $tool &> /dev/null; then
+    echo "Error: Command not found -> $tool. Please install it first."
+    all_tools_found=false
+  fi
+done
+
+if [ "$all_tools_found" = false ]; then
+    echo "Stopping script because some tools are missing."
+    exit 1
+fi
+echo "All tools found. Let's continue."
+echo ""
+
+echo "Compiling Rust code with Cargo..."
+# create a cargo.toml file to handle dependencies
+CARGO_TOML="Cargo.toml"
+echo "Creating Cargo.toml for Rust dependencies..."
+echo '[package]' > $CARGO_TOML
+echo 'name = "compression_bench"' >> $CARGO_TOML
+echo 'version = "0.1.0"' >> $CARGO_TOML
+echo 'edition = "2021"' >> $CARGO_TOML
+echo '' >> $CARGO_TOML
+echo '[[bin]]' >> $CARGO_TOML
+echo 'name = "compression_bench"' >> $CARGO_TOML
+echo 'path = "compression.rs"' >> $CARGO_TOML
+echo '' >> $CARGO_TOML
+echo '[dependencies]' >> $CARGO_TOML
+echo 'bench-core = { path = "../bench-core" }' >> $CARGO_TOML
+echo 'flate2 = "1"' >> $CARGO_TOML
+echo 'zstd = "0.13"' >> $CARGO_TOML
+echo 'lz4_flex = "0.11"' >> $CARGO_TOML
+echo 'brotli = "7"' >> $CARGO_TOML
+
+cargo build --release --quiet
+if [ $? -ne 0 ]; then echo "Rust compilation failed. Stopping."; exit 1; fi
+cp "target/release/compression_bench${EXE_EXT}" "compression_rust${EXE_EXT}"
+
+echo "Compilation was successful!"
+echo ""
+
+echo "Running the compression performance test!"
+echo "Codecs: gzip (level 6), zstd (level 3), lz4 (frame), brotli (quality 5)"
+echo "Inputs: text, binary and already compressed, 4 MiB each"
+echo "Scale factor ${SCALE_FACTOR} selected"
+echo ""
+
+RUST_CMD="./compression_rust${EXE_EXT} ${SCALE_FACTOR}"
+echo "  Rust: $RUST_CMD"
+echo ""
+
+hyperfine -N --warmup 3 --runs 8 --ignore-failure \
+  --command-name "Rust Compression Benchmark" "$RUST_CMD"
+
+echo ""
+echo "Per sub-test timings (ratios on stderr):"
+./compression_rust${EXE_EXT} ${SCALE_FACTOR} --output tsv
+
+echo ""
+echo "Cleaning up compiled files..."
+
+# windows-specific cleanup to handle file locking issues
+if [ "$IS_WINDOWS" = true ]; then
+    sleep 1  # give windows time to release file handles
+    cmd //c "del compression_rust.exe *.pdb 2>nul"
+    # fallback to rm if cmd fails
+    rm -f compression_rust.exe *.pdb 2>/dev/null
+else
+    rm -f compression_rust
+fi
+
+# cleanup generated files
+echo "Cleaning up generated files..."
+rm -f Cargo.toml Cargo.lock
+rm -rf target
+
+echo "All done! Thanks for running the compression benchmark!"
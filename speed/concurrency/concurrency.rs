@@ -1,23 +1,164 @@
+// #[thread_local] statics are nightly-only, enabled with `--features nightly`
+#![cfg_attr(feature = "nightly", feature(thread_local))]
+
 use std::env;
-use std::sync::{Arc, Mutex, mpsc};
-use std::sync::atomic::{AtomicI32, AtomicI64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock, mpsc};
+use std::sync::atomic::{AtomicI32, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::cell::{Cell, UnsafeCell};
 use std::thread;
 use std::time::{Duration, Instant};
 use std::fs::{self, File};
 use std::io::{Write, Read};
-use std::path::Path;
+use std::path::PathBuf;
+use std::hint::black_box;
+use std::future::Future;
+use std::pin::Pin;
+use bench_core::{available_cpus, scaled, Benchmark, InputRng, Rng, ScaleConfig, SelfCheck, SubTest, Timer};
 
-// parallel http requests test using reqwest
-async fn parallel_http_test(num_requests: usize) -> f64 {
-    let start = Instant::now();
-    
+use futures::stream::{FuturesUnordered, StreamExt};
+
+// rust-only extended sub-tests - set via environment variable
+fn extended_enabled() -> bool {
+    env::var("RUST_BENCH_EXTENDED").is_ok()
+}
+
+// percentile of an already sorted latency sample, in microseconds
+fn percentile_us(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[idx].as_secs_f64() * 1_000_000.0
+}
+
+// what /slow waits before answering unless the request asks for another delay, the same
+// as the mock server the suite script starts for the other languages (server/server.py)
+const SLOW_DELAY: Duration = Duration::from_secs(2);
+
+// the delay a request asks for with `?delay_ms=N`, if any
+fn requested_delay(request: &hyper::Request<hyper::Body>) -> Option<Duration> {
+    let query = request.uri().query()?;
+    let ms = query.split('&').find_map(|pair| pair.strip_prefix("delay_ms="))?;
+    ms.parse().ok().map(Duration::from_millis)
+}
+
+// answers a websocket handshake on /ws and echoes every data frame of the connection
+// back until the client closes it
+fn websocket_upgrade(mut request: hyper::Request<hyper::Body>) -> hyper::Response<hyper::Body> {
+    use futures::SinkExt;
+    use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+
+    let accept = match request.headers().get("sec-websocket-key") {
+        Some(key) => derive_accept_key(key.as_bytes()),
+        None => {
+            let mut response = hyper::Response::new(hyper::Body::from("websocket handshake expected"));
+            *response.status_mut() = hyper::StatusCode::BAD_REQUEST;
+            return response;
+        }
+    };
+    tokio::spawn(async move {
+        if let Ok(upgraded) = hyper::upgrade::on(&mut request).await {
+            let mut socket = tokio_tungstenite::WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+            while let Some(Ok(message)) = socket.next().await {
+                if (message.is_binary() || message.is_text()) && socket.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    let mut response = hyper::Response::new(hyper::Body::empty());
+    *response.status_mut() = hyper::StatusCode::SWITCHING_PROTOCOLS;
+    let headers = response.headers_mut();
+    headers.insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("upgrade"));
+    headers.insert(hyper::header::UPGRADE, hyper::header::HeaderValue::from_static("websocket"));
+    if let Ok(accept) = accept.parse() {
+        headers.insert(hyper::header::SEC_WEBSOCKET_ACCEPT, accept);
+    }
+    response
+}
+
+// the mock server's endpoints: /fast answers at once, /slow after SLOW_DELAY and /large
+// with a 10 KB json body; `?delay_ms=N` sets the artificial latency of any of them. /ws is
+// a websocket echo, which server/server.py doesn't have
+async fn mock_route(request: hyper::Request<hyper::Body>) -> Result<hyper::Response<hyper::Body>, std::convert::Infallible> {
+    let (default_delay, body) = match request.uri().path() {
+        "/ws" => return Ok(websocket_upgrade(request)),
+        "/fast" => (Duration::ZERO, hyper::Body::from("Fast response")),
+        "/slow" => (SLOW_DELAY, hyper::Body::from("Slow response")),
+        "/large" => (Duration::ZERO, hyper::Body::from(format!(r#"{{"data": "{}", "status": "ok"}}"#, "x".repeat(10_000)))),
+        _ => {
+            let mut response = hyper::Response::new(hyper::Body::empty());
+            *response.status_mut() = hyper::StatusCode::NOT_FOUND;
+            return Ok(response);
+        }
+    };
+    let delay = requested_delay(&request).unwrap_or(default_delay);
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+    Ok(hyper::Response::new(body))
+}
+
+// binds an ephemeral loopback port for the mock endpoints, the returned future serves them
+// once spawned; has to be called inside a runtime
+fn serve_mock() -> std::io::Result<(std::net::SocketAddr, impl Future<Output = hyper::Result<()>>)> {
+    use hyper::service::{make_service_fn, service_fn};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    listener.set_nonblocking(true)?;
+    let addr = listener.local_addr()?;
+    let server = hyper::Server::from_tcp(listener)
+        .map_err(std::io::Error::other)?
+        .serve(make_service_fn(|_| async { Ok::<_, std::convert::Infallible>(service_fn(mock_route)) }));
+    Ok((addr, server))
+}
+
+// the mock server, embedded so the http test doesn't depend on anything listening on a
+// fixed port; it runs on a runtime of its own, so serving doesn't take worker threads from
+// the clients being timed, and stops when dropped (outside of any async context)
+struct MockServer {
+    addr: std::net::SocketAddr,
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl MockServer {
+    // binds an ephemeral loopback port and starts serving before returning
+    fn start() -> std::io::Result<MockServer> {
+        let runtime = tokio::runtime::Runtime::new()?;
+        let _context = runtime.enter();
+        let (addr, server) = serve_mock()?;
+        runtime.spawn(server);
+        Ok(MockServer { addr, _runtime: runtime })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+}
+
+// parallel http requests test using reqwest; a request left unanswered fails the run, so
+// connection errors can't pass for a fast timing
+async fn parallel_http_test(url: String, num_requests: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+    let successful = parallel_http_requests(&url, num_requests).await;
+    let ms = timer.elapsed_ms();
+    if successful != num_requests {
+        return Err(format!("{} of {} requests to {} went unanswered", num_requests - successful, num_requests, url));
+    }
+    Ok(ms)
+}
+
+// requests that got an answer
+async fn parallel_http_requests(url: &str, num_requests: usize) -> usize {
     let client = reqwest::Client::new();
     let mut handles = Vec::new();
     
     for _ in 0..num_requests {
         let client = client.clone();
+        let url = url.to_string();
         let handle = tokio::spawn(async move {
-            match client.get("http://127.0.0.1:8000/fast").send().await {
+            match client.get(url).send().await {
                 Ok(response) => {
                     let _ = response.text().await;
                     true
@@ -36,17 +177,112 @@ async fn parallel_http_test(num_requests: usize) -> f64 {
             }
         }
     }
-    
-    let duration = start.elapsed();
-    std::hint::black_box(successful);
-    duration.as_secs_f64() * 1000.0
+    successful
+}
+
+// requests each http client sub-test keeps in flight
+const HTTP_IN_FLIGHT: usize = 16;
+
+// how the http client sub-tests talk to the mock server
+#[derive(Clone, Copy)]
+enum HttpClientMode {
+    KeepAlive,
+    Fresh,
+    Multiplexed,
+}
+
+impl HttpClientMode {
+    fn name(self) -> &'static str {
+        match self {
+            HttpClientMode::KeepAlive => "http1_keepalive",
+            HttpClientMode::Fresh => "http1_fresh",
+            HttpClientMode::Multiplexed => "http2_multiplexed",
+        }
+    }
+
+    fn version(self) -> reqwest::Version {
+        match self {
+            HttpClientMode::KeepAlive | HttpClientMode::Fresh => reqwest::Version::HTTP_11,
+            HttpClientMode::Multiplexed => reqwest::Version::HTTP_2,
+        }
+    }
+
+    fn client(self) -> reqwest::Result<reqwest::Client> {
+        let builder = reqwest::Client::builder();
+        match self {
+            // the pool keeps a connection per request in flight and reuses them
+            HttpClientMode::KeepAlive => builder.http1_only(),
+            // no idle connection is kept, so every request opens one of its own
+            HttpClientMode::Fresh => builder.http1_only().pool_max_idle_per_host(0),
+            // cleartext http/2 without an upgrade, every request a stream of one connection
+            HttpClientMode::Multiplexed => builder.http2_prior_knowledge(),
+        }
+        .build()
+    }
+}
+
+// sorted latencies of `requests` gets, `in_flight` at a time; a failed request or one
+// answered over another protocol version than the mode's fails the run
+async fn http_client_latencies(mode: HttpClientMode, url: &str, requests: usize, in_flight: usize) -> Result<Vec<Duration>, String> {
+    let client = mode.client().map_err(|e| e.to_string())?;
+    let results: Vec<Result<Duration, String>> = futures::stream::iter(0..requests)
+        .map(|_| {
+            let client = client.clone();
+            let url = url.to_string();
+            async move {
+                let start = Instant::now();
+                let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+                if response.version() != mode.version() {
+                    return Err(format!("answered over {:?}, expected {:?}", response.version(), mode.version()));
+                }
+                response.bytes().await.map_err(|e| e.to_string())?;
+                Ok(start.elapsed())
+            }
+        })
+        .buffer_unordered(in_flight)
+        .collect()
+        .await;
+    let mut latencies = results.into_iter().collect::<Result<Vec<_>, _>>()?;
+    latencies.sort();
+    Ok(latencies)
+}
+
+// http client test: requests per second and latency percentiles of one way of talking to
+// the embedded mock server, whose startup isn't timed
+fn http_client_test(mode: HttpClientMode, requests: usize) -> Result<f64, String> {
+    let server = MockServer::start().map_err(|e| e.to_string())?;
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime.block_on(async {
+        let timer = Timer::start();
+        let latencies = http_client_latencies(mode, &server.url("/fast"), requests, HTTP_IN_FLIGHT).await?;
+        let ms = timer.elapsed_ms();
+        eprintln!(
+            "{} [{} requests, {} in flight]: {:.0} requests/s, p50 {:.1} us, p90 {:.1} us, p99 {:.1} us",
+            mode.name(),
+            requests,
+            HTTP_IN_FLIGHT,
+            requests as f64 / (ms / 1000.0),
+            percentile_us(&latencies, 0.50),
+            percentile_us(&latencies, 0.90),
+            percentile_us(&latencies, 0.99),
+        );
+        Ok(ms)
+    })
 }
 
 // producer-consumer queue test using channels
 fn producer_consumer_test(num_pairs: usize, items_per_thread: usize) -> f64 {
-    let start = Instant::now();
-    
-    let (tx, rx) = mpsc::channel();
+    let timer = Timer::start();
+    let processed = producer_consumer_run(num_pairs, items_per_thread);
+    let ms = timer.elapsed_ms();
+    std::hint::black_box(processed);
+    ms
+}
+
+// items the consumers processed; crossbeam's channel is multi-consumer, so unlike std's
+// receiver it is cloned into every consumer instead of shared behind a lock
+fn producer_consumer_run(num_pairs: usize, items_per_thread: usize) -> i32 {
+    let (tx, rx) = crossbeam::channel::unbounded();
     let processed = Arc::new(AtomicI32::new(0));
     
     let mut handles = Vec::new();
@@ -63,7 +299,7 @@ fn producer_consumer_test(num_pairs: usize, items_per_thread: usize) -> f64 {
         handles.push(handle);
     }
     
-    // drop the main sender
+    // drop the main sender, the consumers stop once the producers are done
     drop(tx);
     
     // create consumer threads
@@ -71,12 +307,10 @@ fn producer_consumer_test(num_pairs: usize, items_per_thread: usize) -> f64 {
         let rx = rx.clone();
         let processed = processed.clone();
         let handle = thread::spawn(move || {
-            for _ in 0..items_per_thread {
-                if let Ok(item) = rx.recv() {
-                    // simulate processing
-                    let _dummy = item * item;
-                    processed.fetch_add(1, Ordering::Relaxed);
-                }
+            for item in rx.iter() {
+                // simulate processing
+                let _dummy = item * item;
+                processed.fetch_add(1, Ordering::Relaxed);
             }
         });
         handles.push(handle);
@@ -86,10 +320,573 @@ fn producer_consumer_test(num_pairs: usize, items_per_thread: usize) -> f64 {
     for handle in handles {
         handle.join().unwrap();
     }
-    
-    let duration = start.elapsed();
-    std::hint::black_box(processed.load(Ordering::Relaxed));
-    duration.as_secs_f64() * 1000.0
+    processed.load(Ordering::Relaxed)
+}
+
+// the two ends of a multi-producer multi-consumer queue, so one run works for every queue
+trait MpmcSender: Clone + Send + 'static {
+    fn push(&self, value: u64);
+}
+
+trait MpmcReceiver: Clone + Send + 'static {
+    // None once every sender is gone and the queue is drained
+    fn pop(&self) -> Option<u64>;
+}
+
+impl MpmcSender for crossbeam::channel::Sender<u64> {
+    fn push(&self, value: u64) {
+        self.send(value).unwrap();
+    }
+}
+
+impl MpmcReceiver for crossbeam::channel::Receiver<u64> {
+    fn pop(&self) -> Option<u64> {
+        self.recv().ok()
+    }
+}
+
+impl MpmcSender for flume::Sender<u64> {
+    fn push(&self, value: u64) {
+        self.send(value).unwrap();
+    }
+}
+
+impl MpmcReceiver for flume::Receiver<u64> {
+    fn pop(&self) -> Option<u64> {
+        self.recv().ok()
+    }
+}
+
+// queues compared by the mpmc tests
+#[derive(Clone, Copy)]
+enum MpmcQueue {
+    CrossbeamBounded,
+    CrossbeamUnbounded,
+    FlumeBounded,
+    FlumeUnbounded,
+}
+
+impl MpmcQueue {
+    // slots of the bounded queues
+    const CAPACITY: usize = 1024;
+    const ALL: [MpmcQueue; 4] = [
+        MpmcQueue::CrossbeamBounded,
+        MpmcQueue::CrossbeamUnbounded,
+        MpmcQueue::FlumeBounded,
+        MpmcQueue::FlumeUnbounded,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MpmcQueue::CrossbeamBounded => "crossbeam bounded",
+            MpmcQueue::CrossbeamUnbounded => "crossbeam unbounded",
+            MpmcQueue::FlumeBounded => "flume bounded",
+            MpmcQueue::FlumeUnbounded => "flume unbounded",
+        }
+    }
+
+    // moves 0..messages from `producers` threads to `consumers` threads, returning the
+    // messages received, their wrapping sum and the elapsed time
+    fn run(self, producers: usize, consumers: usize, messages: usize) -> (usize, u64, Duration) {
+        match self {
+            MpmcQueue::CrossbeamBounded => mpmc_run(crossbeam::channel::bounded(Self::CAPACITY), producers, consumers, messages),
+            MpmcQueue::CrossbeamUnbounded => mpmc_run(crossbeam::channel::unbounded(), producers, consumers, messages),
+            MpmcQueue::FlumeBounded => mpmc_run(flume::bounded(Self::CAPACITY), producers, consumers, messages),
+            MpmcQueue::FlumeUnbounded => mpmc_run(flume::unbounded(), producers, consumers, messages),
+        }
+    }
+}
+
+// producer p sends p, p + producers, ... below `messages`; every consumer drains the one
+// shared queue until the producers are done
+fn mpmc_run<S: MpmcSender, R: MpmcReceiver>((tx, rx): (S, R), producers: usize, consumers: usize, messages: usize) -> (usize, u64, Duration) {
+    let start = Instant::now();
+    let senders: Vec<_> = (0..producers)
+        .map(|p| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for value in (p..messages).step_by(producers) {
+                    tx.push(value as u64);
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+    let receivers: Vec<_> = (0..consumers)
+        .map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || {
+                let (mut received, mut sum) = (0usize, 0u64);
+                while let Some(value) = rx.pop() {
+                    received += 1;
+                    sum = sum.wrapping_add(value);
+                }
+                (received, sum)
+            })
+        })
+        .collect();
+    drop(rx);
+    for sender in senders {
+        sender.join().unwrap();
+    }
+    let (received, sum) = receivers
+        .into_iter()
+        .map(|receiver| receiver.join().unwrap())
+        .fold((0, 0u64), |(n, total), (received, sum)| (n + received, total.wrapping_add(sum)));
+    (received, sum, start.elapsed())
+}
+
+// counters in the table the lock contention tests share
+const LOCK_SLOTS: usize = 64;
+// threads hammering the table when RUST_BENCH_THREADS doesn't set them
+const LOCK_THREADS: usize = 4;
+
+// a table of counters several threads read and bump at once, behind one primitive
+trait SharedCounters: Send + Sync + 'static {
+    // sum of every counter, which only the locks read as one consistent snapshot
+    fn read(&self) -> u64;
+    fn bump(&self, slot: usize);
+}
+
+impl SharedCounters for Mutex<[u64; LOCK_SLOTS]> {
+    fn read(&self) -> u64 {
+        self.lock().unwrap().iter().sum()
+    }
+
+    fn bump(&self, slot: usize) {
+        self.lock().unwrap()[slot] += 1;
+    }
+}
+
+impl SharedCounters for RwLock<[u64; LOCK_SLOTS]> {
+    fn read(&self) -> u64 {
+        self.read().unwrap().iter().sum()
+    }
+
+    fn bump(&self, slot: usize) {
+        self.write().unwrap()[slot] += 1;
+    }
+}
+
+impl SharedCounters for parking_lot::Mutex<[u64; LOCK_SLOTS]> {
+    fn read(&self) -> u64 {
+        self.lock().iter().sum()
+    }
+
+    fn bump(&self, slot: usize) {
+        self.lock()[slot] += 1;
+    }
+}
+
+impl SharedCounters for parking_lot::RwLock<[u64; LOCK_SLOTS]> {
+    fn read(&self) -> u64 {
+        self.read().iter().sum()
+    }
+
+    fn bump(&self, slot: usize) {
+        self.write()[slot] += 1;
+    }
+}
+
+impl SharedCounters for [AtomicU64; LOCK_SLOTS] {
+    fn read(&self) -> u64 {
+        self.iter().map(|counter| counter.load(Ordering::Relaxed)).sum()
+    }
+
+    fn bump(&self, slot: usize) {
+        self[slot].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// primitives compared by the lock contention tests
+#[derive(Clone, Copy)]
+enum LockPrimitive {
+    StdMutex,
+    StdRwLock,
+    ParkingLotMutex,
+    ParkingLotRwLock,
+    Atomics,
+}
+
+impl LockPrimitive {
+    const ALL: [LockPrimitive; 5] = [
+        LockPrimitive::StdMutex,
+        LockPrimitive::StdRwLock,
+        LockPrimitive::ParkingLotMutex,
+        LockPrimitive::ParkingLotRwLock,
+        LockPrimitive::Atomics,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            LockPrimitive::StdMutex => "std mutex",
+            LockPrimitive::StdRwLock => "std rwlock",
+            LockPrimitive::ParkingLotMutex => "parking_lot mutex",
+            LockPrimitive::ParkingLotRwLock => "parking_lot rwlock",
+            LockPrimitive::Atomics => "atomics",
+        }
+    }
+
+    // `ops` reads and bumps split across `threads`, write_percent of them bumps; returns
+    // the bumps made, the table's sum afterwards and the elapsed time
+    fn run(self, threads: usize, ops: usize, write_percent: u32) -> (u64, u64, Duration) {
+        let table = [0u64; LOCK_SLOTS];
+        match self {
+            LockPrimitive::StdMutex => contend(Mutex::new(table), threads, ops, write_percent),
+            LockPrimitive::StdRwLock => contend(RwLock::new(table), threads, ops, write_percent),
+            LockPrimitive::ParkingLotMutex => contend(parking_lot::Mutex::new(table), threads, ops, write_percent),
+            LockPrimitive::ParkingLotRwLock => contend(parking_lot::RwLock::new(table), threads, ops, write_percent),
+            LockPrimitive::Atomics => contend(std::array::from_fn::<_, LOCK_SLOTS, _>(|_| AtomicU64::new(0)), threads, ops, write_percent),
+        }
+    }
+}
+
+// a read sums the whole table, which keeps a reader inside its critical section long
+// enough for read locks to pay off; a write bumps one random counter
+fn contend<C: SharedCounters>(counters: C, threads: usize, ops: usize, write_percent: u32) -> (u64, u64, Duration) {
+    let counters = Arc::new(counters);
+    let start = Instant::now();
+    let handles: Vec<_> = (0..threads)
+        .map(|t| {
+            let counters = counters.clone();
+            thread::spawn(move || {
+                let mut rng = XorShift32(0x9E37_79B9 ^ (t as u32 + 1));
+                let mut writes = 0;
+                for _ in 0..ops / threads {
+                    let r = rng.next();
+                    if r % 100 < write_percent {
+                        counters.bump((r >> 8) as usize % LOCK_SLOTS);
+                        writes += 1;
+                    } else {
+                        black_box(counters.read());
+                    }
+                }
+                writes
+            })
+        })
+        .collect();
+    let writes = handles.into_iter().map(|handle| handle.join().unwrap()).sum();
+    let elapsed = start.elapsed();
+    (writes, counters.read(), elapsed)
+}
+
+// lock contention test: ns per operation of every primitive in LockPrimitive at one mix
+// of reads and writes
+fn lock_contention_test(threads: usize, ops: usize, write_percent: u32) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for primitive in LockPrimitive::ALL {
+        let (writes, total, elapsed) = primitive.run(threads, ops, write_percent);
+        if writes != total {
+            return Err(format!("{} counted {} of {} writes", primitive.label(), total, writes));
+        }
+        eprintln!(
+            "lock_contention [{}, {}% writes]: {:.1} ns/op, {:.2} M ops/s",
+            primitive.label(),
+            write_percent,
+            elapsed.as_secs_f64() * 1e9 / ops as f64,
+            ops as f64 / elapsed.as_secs_f64() / 1e6,
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// threads on the many side of the mpmc topologies
+const MPMC_THREADS: usize = 4;
+
+// mpmc test: ns per message through every queue in MpmcQueue for one topology of
+// producer and consumer threads
+fn mpmc_test(producers: usize, consumers: usize, messages: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let expected = (0..messages as u64).fold(0u64, |acc, v| acc.wrapping_add(v));
+    for queue in MpmcQueue::ALL {
+        let (received, sum, elapsed) = queue.run(producers, consumers, messages);
+        if received != messages || sum != expected {
+            return Err(format!("{} delivered {} of {} messages, sum {} instead of {}", queue.label(), received, messages, sum, expected));
+        }
+        eprintln!(
+            "mpmc [{}, {}:{}]: {:.1} ns/message, {:.2} M messages/s",
+            queue.label(),
+            producers,
+            consumers,
+            elapsed.as_secs_f64() * 1e9 / messages as f64,
+            messages as f64 / elapsed.as_secs_f64() / 1e6,
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// tasks on each side of the tokio::sync tests
+const SYNC_TASKS: usize = 8;
+
+// tokio::sync primitives compared by the async coordination tests
+#[derive(Clone, Copy)]
+enum SyncPrimitive {
+    // one bounded channel per consumer, the producers deal their messages out round robin
+    Mpsc,
+    // every consumer receives every message
+    Broadcast,
+    // a counter the producers bump, the consumers only ever see its latest value
+    Watch,
+    // a pending count the producers raise and the consumers take from, woken by notify_one
+    Notify,
+}
+
+impl SyncPrimitive {
+    // slots of the bounded channels
+    const CAPACITY: usize = 1024;
+
+    fn label(self) -> &'static str {
+        match self {
+            SyncPrimitive::Mpsc => "mpsc",
+            SyncPrimitive::Broadcast => "broadcast",
+            SyncPrimitive::Watch => "watch",
+            SyncPrimitive::Notify => "notify",
+        }
+    }
+
+    // what the side count of a run is, for the stderr line
+    fn side_label(self) -> Option<&'static str> {
+        match self {
+            SyncPrimitive::Mpsc => None,
+            SyncPrimitive::Broadcast => Some("lagged"),
+            SyncPrimitive::Watch => Some("changes seen"),
+            SyncPrimitive::Notify => Some("waits"),
+        }
+    }
+
+    // producer p handles p, p + producers, ... below `messages`, on a multi-threaded runtime
+    // with `workers` threads; returns the deliveries (a broadcast message counts once per
+    // consumer, a watch update once), a side count per side_label and the elapsed time
+    fn run(self, workers: usize, producers: usize, consumers: usize, messages: usize) -> Result<(u64, u64, Duration), String> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(workers)
+            .build()
+            .map_err(|e| e.to_string())?;
+        runtime.block_on(async {
+            let start = Instant::now();
+            let (received, side) = match self {
+                SyncPrimitive::Mpsc => sync_mpsc(producers, consumers, messages).await?,
+                SyncPrimitive::Broadcast => sync_broadcast(producers, consumers, messages).await?,
+                SyncPrimitive::Watch => sync_watch(producers, consumers, messages).await?,
+                SyncPrimitive::Notify => sync_notify(producers, consumers, messages).await?,
+            };
+            Ok((received, side, start.elapsed()))
+        })
+    }
+}
+
+async fn join_tasks<T>(tasks: Vec<tokio::task::JoinHandle<T>>) -> Result<Vec<T>, String> {
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        results.push(task.await.map_err(|e| e.to_string())?);
+    }
+    Ok(results)
+}
+
+async fn sync_mpsc(producers: usize, consumers: usize, messages: usize) -> Result<(u64, u64), String> {
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..consumers).map(|_| tokio::sync::mpsc::channel::<u64>(SyncPrimitive::CAPACITY)).unzip();
+    let consuming: Vec<_> = receivers
+        .into_iter()
+        .map(|mut rx| {
+            tokio::spawn(async move {
+                let (mut received, mut sum) = (0u64, 0u64);
+                while let Some(value) = rx.recv().await {
+                    received += 1;
+                    sum = sum.wrapping_add(value);
+                }
+                (received, sum)
+            })
+        })
+        .collect();
+    let producing: Vec<_> = (0..producers)
+        .map(|p| {
+            let senders = senders.clone();
+            tokio::spawn(async move {
+                for value in (p..messages).step_by(producers) {
+                    senders[value % senders.len()].send(value as u64).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(senders);
+    join_tasks(producing).await?;
+    let (received, sum) = join_tasks(consuming)
+        .await?
+        .into_iter()
+        .fold((0, 0u64), |(n, total), (received, sum)| (n + received, total.wrapping_add(sum)));
+    let expected = (0..messages as u64).fold(0u64, |acc, v| acc.wrapping_add(v));
+    if received != messages as u64 || sum != expected {
+        return Err(format!("mpsc delivered {} of {} messages, sum {} instead of {}", received, messages, sum, expected));
+    }
+    Ok((received, 0))
+}
+
+async fn sync_broadcast(producers: usize, consumers: usize, messages: usize) -> Result<(u64, u64), String> {
+    let (tx, _) = tokio::sync::broadcast::channel::<u64>(SyncPrimitive::CAPACITY);
+    // subscribed before anything is sent, so every consumer is owed every message
+    let consuming: Vec<_> = (0..consumers)
+        .map(|_| {
+            let mut rx = tx.subscribe();
+            tokio::spawn(async move {
+                let (mut received, mut lagged) = (0u64, 0u64);
+                loop {
+                    match rx.recv().await {
+                        Ok(value) => {
+                            black_box(value);
+                            received += 1;
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => lagged += skipped,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+                (received, lagged)
+            })
+        })
+        .collect();
+    let producing: Vec<_> = (0..producers)
+        .map(|p| {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                for value in (p..messages).step_by(producers) {
+                    tx.send(value as u64).unwrap();
+                    // the send never waits, let the consumers keep up
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+    join_tasks(producing).await?;
+    let (received, lagged) = join_tasks(consuming)
+        .await?
+        .into_iter()
+        .fold((0, 0), |(n, l), (received, lagged)| (n + received, l + lagged));
+    let owed = (messages * consumers) as u64;
+    if received + lagged != owed {
+        return Err(format!("broadcast delivered {} and skipped {} of {} messages", received, lagged, owed));
+    }
+    Ok((received, lagged))
+}
+
+async fn sync_watch(producers: usize, consumers: usize, messages: usize) -> Result<(u64, u64), String> {
+    let (tx, rx) = tokio::sync::watch::channel(0u64);
+    let tx = Arc::new(tx);
+    let consuming: Vec<_> = (0..consumers)
+        .map(|_| {
+            let mut rx = rx.clone();
+            tokio::spawn(async move {
+                let mut seen = 0u64;
+                loop {
+                    let value = *rx.borrow_and_update();
+                    if value == messages as u64 || rx.changed().await.is_err() {
+                        return (value, seen);
+                    }
+                    seen += 1;
+                }
+            })
+        })
+        .collect();
+    drop(rx);
+    let producing: Vec<_> = (0..producers)
+        .map(|p| {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                for _ in (p..messages).step_by(producers) {
+                    tx.send_modify(|value| *value += 1);
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect();
+    join_tasks(producing).await?;
+    drop(tx);
+    let mut seen = 0;
+    for (last, changes) in join_tasks(consuming).await? {
+        if last != messages as u64 {
+            return Err(format!("a watch consumer stopped at {} of {} updates", last, messages));
+        }
+        seen += changes;
+    }
+    // the updates coalesce, the consumers only wake for some of them
+    Ok((messages as u64, seen))
+}
+
+async fn sync_notify(producers: usize, consumers: usize, messages: usize) -> Result<(u64, u64), String> {
+    let notify = Arc::new(tokio::sync::Notify::new());
+    let pending = Arc::new(AtomicUsize::new(0));
+    let taken = Arc::new(AtomicUsize::new(0));
+    let consuming: Vec<_> = (0..consumers)
+        .map(|_| {
+            let (notify, pending, taken) = (notify.clone(), pending.clone(), taken.clone());
+            tokio::spawn(async move {
+                let (mut received, mut waits) = (0u64, 0u64);
+                loop {
+                    // registered before looking, so a notify_waiters in between isn't lost
+                    let notified = notify.notified();
+                    if pending.fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1)).is_ok() {
+                        received += 1;
+                        if taken.fetch_add(1, Ordering::AcqRel) + 1 == messages {
+                            notify.notify_waiters();
+                        }
+                        continue;
+                    }
+                    if taken.load(Ordering::Acquire) == messages {
+                        return (received, waits);
+                    }
+                    notified.await;
+                    waits += 1;
+                }
+            })
+        })
+        .collect();
+    let producing: Vec<_> = (0..producers)
+        .map(|p| {
+            let (notify, pending) = (notify.clone(), pending.clone());
+            tokio::spawn(async move {
+                for _ in (p..messages).step_by(producers) {
+                    pending.fetch_add(1, Ordering::Release);
+                    notify.notify_one();
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect();
+    join_tasks(producing).await?;
+    let (received, waits) = join_tasks(consuming)
+        .await?
+        .into_iter()
+        .fold((0, 0), |(n, w), (received, waits)| (n + received, w + waits));
+    if received != messages as u64 {
+        return Err(format!("notify handed out {} of {} permits", received, messages));
+    }
+    Ok((received, waits))
+}
+
+// async coordination test: ns per delivery through one tokio::sync primitive with
+// SYNC_TASKS producer and consumer tasks
+fn tokio_sync_test(primitive: SyncPrimitive, workers: usize, messages: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let (received, side, elapsed) = primitive.run(workers, SYNC_TASKS, SYNC_TASKS, messages)?;
+    let mut line = format!(
+        "tokio_sync [{}, {}:{} tasks, workers={}]: {:.1} ns/delivery, {:.2} M deliveries/s",
+        primitive.label(),
+        SYNC_TASKS,
+        SYNC_TASKS,
+        workers,
+        elapsed.as_secs_f64() * 1e9 / received as f64,
+        received as f64 / elapsed.as_secs_f64() / 1e6,
+    );
+    if let Some(side_label) = primitive.side_label() {
+        line.push_str(&format!(", {} {}", side, side_label));
+    }
+    eprintln!("{}", line);
+
+    Ok(timer.elapsed_ms())
 }
 
 // fibonacci computation
@@ -110,8 +907,15 @@ fn fibonacci(n: u64) -> u64 {
 
 // parallel mathematical work test
 fn parallel_math_test(num_threads: usize, work_per_thread: usize) -> f64 {
-    let start = Instant::now();
-    
+    let timer = Timer::start();
+    let total_sum = parallel_math_run(num_threads, work_per_thread);
+    let ms = timer.elapsed_ms();
+    std::hint::black_box(total_sum);
+    ms
+}
+
+// sum of every thread's results
+fn parallel_math_run(num_threads: usize, work_per_thread: usize) -> i64 {
     let total_sum = Arc::new(AtomicI64::new(0));
     let mut handles = Vec::new();
     
@@ -137,16 +941,20 @@ fn parallel_math_test(num_threads: usize, work_per_thread: usize) -> f64 {
     for handle in handles {
         handle.join().unwrap();
     }
-    
-    let duration = start.elapsed();
-    std::hint::black_box(total_sum.load(Ordering::Relaxed));
-    duration.as_secs_f64() * 1000.0
+    total_sum.load(Ordering::Relaxed)
 }
 
 // async file processing test using tokio
 async fn async_file_test(num_files: usize) -> f64 {
-    let start = Instant::now();
-    
+    let timer = Timer::start();
+    let processed = async_file_run(num_files).await;
+    let ms = timer.elapsed_ms();
+    std::hint::black_box(processed);
+    ms
+}
+
+// files written, read back and found non-empty
+async fn async_file_run(num_files: usize) -> i32 {
     let temp_dir = tempfile::tempdir().unwrap();
     let processed = Arc::new(AtomicI32::new(0));
     
@@ -194,16 +1002,62 @@ async fn async_file_test(num_files: usize) -> f64 {
     for handle in handles {
         let _ = handle.await;
     }
-    
-    let duration = start.elapsed();
-    std::hint::black_box(processed.load(Ordering::Relaxed));
-    duration.as_secs_f64() * 1000.0
+    processed.load(Ordering::Relaxed)
 }
 
 // thread pool performance test using rayon
 fn thread_pool_test(pool_size: usize, total_tasks: usize) -> f64 {
-    let start = Instant::now();
-    
+    let timer = Timer::start();
+    let completed = thread_pool_run(pool_size, total_tasks);
+    let ms = timer.elapsed_ms();
+    std::hint::black_box(completed);
+    ms
+}
+
+// tasks the pool completed
+fn thread_pool_run(pool_size: usize, total_tasks: usize) -> i32 {
+    thread_pool_run_load(pool_size, total_tasks, PoolLoad::Mixed)
+}
+
+// what each thread pool task does
+#[derive(Clone, Copy)]
+enum PoolLoad {
+    // the cross-language test's loop plus a 100us sleep, which mostly times the sleep
+    Mixed,
+    // the same loop, without the sleep
+    Cpu,
+    // blocks for POOL_IO_WAIT like a synchronous read would, with no computation
+    Io,
+}
+
+// how long a simulated i/o task blocks, long enough that sleep overshoot stays small
+const POOL_IO_WAIT: Duration = Duration::from_millis(1);
+
+fn pool_task(load: PoolLoad) {
+    match load {
+        PoolLoad::Mixed => {
+            // simulate varied workload
+            let mut work = 0i64;
+            for j in 0..10000 {
+                work += (j * j) as i64;
+            }
+            thread::sleep(Duration::from_micros(100));
+            std::hint::black_box(work);
+        }
+        PoolLoad::Cpu => {
+            // black_box keeps the compiler from folding the sum into a constant, which it
+            // does with the mixed loop
+            let mut work = 0i64;
+            for j in 0..10000 {
+                work += black_box(j) * j;
+            }
+            black_box(work);
+        }
+        PoolLoad::Io => thread::sleep(POOL_IO_WAIT),
+    }
+}
+
+fn thread_pool_run_load(pool_size: usize, total_tasks: usize, load: PoolLoad) -> i32 {
     let pool = rayon::ThreadPoolBuilder::new()
         .num_threads(pool_size)
         .build()
@@ -215,46 +1069,3418 @@ fn thread_pool_test(pool_size: usize, total_tasks: usize) -> f64 {
         for _ in 0..total_tasks {
             let completed = completed.clone();
             s.spawn(move |_| {
-                // simulate varied workload
-                let mut work = 0i64;
-                for j in 0..10000 {
-                    work += (j * j) as i64;
-                }
-                
-                thread::sleep(Duration::from_micros(100));
+                pool_task(load);
                 completed.fetch_add(1, Ordering::Relaxed);
-                
-                std::hint::black_box(work);
             });
         }
     });
-    
-    let duration = start.elapsed();
-    std::hint::black_box(completed.load(Ordering::Relaxed));
-    duration.as_secs_f64() * 1000.0
+    completed.load(Ordering::Relaxed)
 }
 
-#[tokio::main]
-async fn main() {
-    let args: Vec<String> = env::args().collect();
-    let mut scale_factor = 1;
-    
-    if args.len() > 1 {
-        match args[1].parse::<usize>() {
-            Ok(factor) if factor > 0 => scale_factor = factor,
-            _ => {
-                eprintln!("Invalid scale factor. Using default 1.");
-            }
+// thread pool throughput test: tasks per second of a pool kept saturated with one kind of
+// task; for simulated i/o the ceiling is pool_size tasks per POOL_IO_WAIT
+fn thread_pool_throughput_test(load: PoolLoad, pool_size: usize, total_tasks: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+    let completed = thread_pool_run_load(pool_size, total_tasks, load);
+    let ms = timer.elapsed_ms();
+    if completed as usize != total_tasks {
+        return Err(format!("{} of {} tasks completed", completed, total_tasks));
+    }
+
+    let per_second = total_tasks as f64 / (ms / 1000.0);
+    match load {
+        PoolLoad::Io => eprintln!(
+            "thread_pool [io, {} threads]: {:.0} tasks/s, {:.0}% of the {:.0} a perfect pool manages",
+            pool_size,
+            per_second,
+            100.0 * per_second / (pool_size as f64 / POOL_IO_WAIT.as_secs_f64()),
+            pool_size as f64 / POOL_IO_WAIT.as_secs_f64(),
+        ),
+        _ => eprintln!("thread_pool [cpu, {} threads]: {:.0} tasks/s", pool_size, per_second),
+    }
+    Ok(ms)
+}
+
+// where the heavy jobs of the blocking-in-async test are placed
+#[derive(Clone, Copy)]
+enum HeavyPlacement {
+    None,
+    Inline,
+    SpawnBlocking,
+    DedicatedRuntime,
+}
+
+impl HeavyPlacement {
+    const ALL: [HeavyPlacement; 4] = [
+        HeavyPlacement::None,
+        HeavyPlacement::Inline,
+        HeavyPlacement::SpawnBlocking,
+        HeavyPlacement::DedicatedRuntime,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            HeavyPlacement::None => "no heavy work",
+            HeavyPlacement::Inline => "inline on async workers",
+            HeavyPlacement::SpawnBlocking => "spawn_blocking",
+            HeavyPlacement::DedicatedRuntime => "dedicated runtime",
         }
     }
+}
+
+// busy loop that never yields, standing in for parsing/hashing hot loops
+fn cpu_burn(duration: Duration) {
+    let until = Instant::now() + duration;
+    let mut acc = 0u64;
+    while Instant::now() < until {
+        acc = acc.wrapping_add(fibonacci(black_box(90)));
+    }
+    black_box(acc);
+}
+
+// cpu-bound busy loop followed by a blocking sleep, the classic async misuse
+fn heavy_blocking_job(cpu: Duration, blocked: Duration) {
+    cpu_burn(cpu);
+    thread::sleep(blocked);
+}
 
-    let mut total_time = 0.0;
+// sleeps each light task takes in the blocking-in-async test
+const LIGHT_ROUNDS: usize = 20;
 
-    total_time += parallel_http_test(50 * scale_factor).await;
-    total_time += producer_consumer_test(4, 1000 * scale_factor);
-    total_time += parallel_math_test(4, 100 * scale_factor);
-    total_time += async_file_test(20 * scale_factor).await;
-    total_time += thread_pool_test(8, 500 * scale_factor);
+// light tasks that only sleep 1ms at a time, recording how late each wakeup is
+fn light_task_latencies(placement: HeavyPlacement, light_tasks: usize, heavy_tasks: usize) -> Vec<Duration> {
+    const TICK: Duration = Duration::from_millis(1);
 
-    println!("{:.3}", total_time);
-}
\ No newline at end of file
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .unwrap();
+    let dedicated = match placement {
+        HeavyPlacement::DedicatedRuntime => Some(
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(2)
+                .build()
+                .unwrap(),
+        ),
+        _ => None,
+    };
+
+    runtime.block_on(async {
+        let mut heavy = Vec::new();
+        for _ in 0..heavy_tasks {
+            let job = || heavy_blocking_job(Duration::from_millis(20), Duration::from_millis(20));
+            match placement {
+                HeavyPlacement::None => {}
+                HeavyPlacement::Inline => heavy.push(tokio::spawn(async move { job() })),
+                HeavyPlacement::SpawnBlocking => heavy.push(tokio::task::spawn_blocking(job)),
+                HeavyPlacement::DedicatedRuntime => {
+                    heavy.push(dedicated.as_ref().unwrap().spawn(async move { job() }))
+                }
+            }
+        }
+
+        let mut light = Vec::new();
+        for _ in 0..light_tasks {
+            light.push(tokio::spawn(async {
+                let mut delays = Vec::with_capacity(LIGHT_ROUNDS);
+                for _ in 0..LIGHT_ROUNDS {
+                    let tick_start = Instant::now();
+                    tokio::time::sleep(TICK).await;
+                    delays.push(tick_start.elapsed().saturating_sub(TICK));
+                }
+                delays
+            }));
+        }
+
+        let mut delays = Vec::with_capacity(light_tasks * LIGHT_ROUNDS);
+        for handle in light {
+            if let Ok(task_delays) = handle.await {
+                delays.extend(task_delays);
+            }
+        }
+        for handle in heavy {
+            let _ = handle.await;
+        }
+        delays
+    })
+}
+
+// blocking-in-async hazard test: light task latency with heavy work placed
+// inline, on the blocking pool, or on a dedicated runtime; fails when a light task
+// didn't finish its sleeps
+fn blocking_in_async_test(light_tasks: usize, heavy_tasks: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for placement in HeavyPlacement::ALL {
+        let mut delays = light_task_latencies(placement, light_tasks, heavy_tasks);
+        if delays.len() != light_tasks * LIGHT_ROUNDS {
+            return Err(format!(
+                "{} of {} light task wakeups came back with {}",
+                delays.len(),
+                light_tasks * LIGHT_ROUNDS,
+                placement.label()
+            ));
+        }
+        delays.sort();
+        eprintln!(
+            "blocking_in_async [{}]: wakeup delay p50 {:.1}us p99 {:.1}us max {:.1}us",
+            placement.label(),
+            percentile_us(&delays, 0.50),
+            percentile_us(&delays, 0.99),
+            percentile_us(&delays, 1.0),
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// executors compared by the scheduler fairness test
+#[derive(Clone, Copy)]
+enum FairnessExecutor {
+    Rayon,
+    Tokio,
+    RawThreads,
+}
+
+impl FairnessExecutor {
+    const ALL: [FairnessExecutor; 3] = [FairnessExecutor::Rayon, FairnessExecutor::Tokio, FairnessExecutor::RawThreads];
+
+    fn label(self) -> &'static str {
+        match self {
+            FairnessExecutor::Rayon => "rayon",
+            FairnessExecutor::Tokio => "tokio",
+            FairnessExecutor::RawThreads => "raw threads",
+        }
+    }
+}
+
+// the latency-sensitive side of the fairness mix
+fn short_task_work() {
+    let mut work = 0i64;
+    for k in 0..1000 {
+        work += (k * k) as i64;
+    }
+    black_box(work);
+}
+
+// submit long cpu hogs first, then short tasks, and record each short task's
+// submit-to-finish latency on the chosen executor
+fn short_task_latencies(executor: FairnessExecutor, workers: usize, long_tasks: usize, short_tasks: usize) -> Vec<Duration> {
+    const LONG_TASK: Duration = Duration::from_millis(50);
+
+    let latencies = Arc::new(Mutex::new(Vec::with_capacity(short_tasks)));
+
+    match executor {
+        FairnessExecutor::Rayon => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(workers)
+                .build()
+                .unwrap();
+            pool.scope(|s| {
+                for _ in 0..long_tasks {
+                    s.spawn(|_| cpu_burn(LONG_TASK));
+                }
+                for _ in 0..short_tasks {
+                    let latencies = latencies.clone();
+                    let submitted = Instant::now();
+                    s.spawn(move |_| {
+                        short_task_work();
+                        latencies.lock().unwrap().push(submitted.elapsed());
+                    });
+                }
+            });
+        }
+        FairnessExecutor::Tokio => {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(workers)
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                let mut handles = Vec::with_capacity(long_tasks + short_tasks);
+                for _ in 0..long_tasks {
+                    handles.push(tokio::spawn(async { cpu_burn(LONG_TASK) }));
+                }
+                for _ in 0..short_tasks {
+                    let latencies = latencies.clone();
+                    let submitted = Instant::now();
+                    handles.push(tokio::spawn(async move {
+                        short_task_work();
+                        latencies.lock().unwrap().push(submitted.elapsed());
+                    }));
+                }
+                for handle in handles {
+                    let _ = handle.await;
+                }
+            });
+        }
+        FairnessExecutor::RawThreads => {
+            // fixed workers pulling boxed jobs from one fifo queue
+            let (tx, rx) = mpsc::channel::<Box<dyn FnOnce() + Send>>();
+            let rx = Arc::new(Mutex::new(rx));
+            let mut handles = Vec::with_capacity(workers);
+            for _ in 0..workers {
+                let rx = rx.clone();
+                handles.push(thread::spawn(move || loop {
+                    let job = rx.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                }));
+            }
+            for _ in 0..long_tasks {
+                tx.send(Box::new(|| cpu_burn(LONG_TASK))).unwrap();
+            }
+            for _ in 0..short_tasks {
+                let latencies = latencies.clone();
+                let submitted = Instant::now();
+                tx.send(Box::new(move || {
+                    short_task_work();
+                    latencies.lock().unwrap().push(submitted.elapsed());
+                }))
+                .unwrap();
+            }
+            drop(tx);
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        }
+    }
+
+    let mut latencies = Arc::try_unwrap(latencies).unwrap().into_inner().unwrap();
+    latencies.sort();
+    latencies
+}
+
+// workers and long cpu hogs of the scheduler fairness test
+const FAIRNESS_WORKERS: usize = 4;
+const FAIRNESS_LONG_TASKS: usize = 8;
+
+// scheduler fairness test: short task latency alone versus queued behind long
+// cpu-bound tasks, on rayon, tokio and a plain fifo thread pool
+fn scheduler_fairness_test(workers: usize, long_tasks: usize, short_tasks: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for executor in FairnessExecutor::ALL {
+        let alone = short_task_latencies(executor, workers, 0, short_tasks);
+        let mixed = short_task_latencies(executor, workers, long_tasks, short_tasks);
+        if alone.len() != short_tasks || mixed.len() != short_tasks {
+            return Err(format!("{} ran {} and {} of {} short tasks", executor.label(), alone.len(), mixed.len(), short_tasks));
+        }
+        let alone_p99 = percentile_us(&alone, 0.99);
+        let mixed_p99 = percentile_us(&mixed, 0.99);
+        eprintln!(
+            "scheduler_fairness [{}]: short task p50 {:.1}us -> {:.1}us, p99 {:.1}us -> {:.1}us ({:.1}x)",
+            executor.label(),
+            percentile_us(&alone, 0.50),
+            percentile_us(&mixed, 0.50),
+            alone_p99,
+            mixed_p99,
+            mixed_p99 / alone_p99.max(f64::EPSILON),
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// bounded single-producer/single-consumer ring buffer of u64 messages
+struct SpscRing {
+    buffer: Box<[UnsafeCell<u64>]>,
+    mask: usize,
+    head: crossbeam::utils::CachePadded<AtomicUsize>, // next slot to read, advanced by the consumer
+    tail: crossbeam::utils::CachePadded<AtomicUsize>, // next slot to write, advanced by the producer
+}
+
+// slots are only written by the single producer before publishing them
+// through tail, and only read by the single consumer after observing it
+unsafe impl Sync for SpscRing {}
+
+struct RingProducer(Arc<SpscRing>);
+struct RingConsumer(Arc<SpscRing>);
+
+fn spsc_ring(capacity: usize) -> (RingProducer, RingConsumer) {
+    let capacity = capacity.next_power_of_two();
+    let ring = Arc::new(SpscRing {
+        buffer: (0..capacity).map(|_| UnsafeCell::new(0)).collect(),
+        mask: capacity - 1,
+        head: crossbeam::utils::CachePadded::new(AtomicUsize::new(0)),
+        tail: crossbeam::utils::CachePadded::new(AtomicUsize::new(0)),
+    });
+    (RingProducer(ring.clone()), RingConsumer(ring))
+}
+
+impl RingProducer {
+    // copies as many items as fit and publishes them with one release store
+    fn push_batch(&mut self, items: &[u64]) -> usize {
+        let ring = &self.0;
+        let tail = ring.tail.load(Ordering::Relaxed);
+        let head = ring.head.load(Ordering::Acquire);
+        let free = ring.buffer.len() - (tail - head);
+        let n = free.min(items.len());
+        for (i, &item) in items[..n].iter().enumerate() {
+            unsafe { *ring.buffer[(tail + i) & ring.mask].get() = item };
+        }
+        ring.tail.store(tail + n, Ordering::Release);
+        n
+    }
+}
+
+impl RingConsumer {
+    // takes up to out.len() items and frees their slots with one release store
+    fn pop_batch(&mut self, out: &mut [u64]) -> usize {
+        let ring = &self.0;
+        let head = ring.head.load(Ordering::Relaxed);
+        let tail = ring.tail.load(Ordering::Acquire);
+        let n = (tail - head).min(out.len());
+        for (i, slot) in out[..n].iter_mut().enumerate() {
+            *slot = unsafe { *ring.buffer[(head + i) & ring.mask].get() };
+        }
+        ring.head.store(head + n, Ordering::Release);
+        n
+    }
+}
+
+// queues compared by the spsc vs mpmc test
+#[derive(Clone, Copy)]
+enum QueueKind {
+    SpscRing,
+    CrossbeamBounded,
+    StdSyncChannel,
+}
+
+impl QueueKind {
+    const ALL: [QueueKind; 3] = [QueueKind::SpscRing, QueueKind::CrossbeamBounded, QueueKind::StdSyncChannel];
+
+    fn label(self) -> &'static str {
+        match self {
+            QueueKind::SpscRing => "spsc ring",
+            QueueKind::CrossbeamBounded => "crossbeam bounded",
+            QueueKind::StdSyncChannel => "std sync_channel",
+        }
+    }
+}
+
+// moves `messages` sequential u64s from one producer to one consumer in
+// batches, returning the consumer's checksum and the elapsed time
+fn transfer_messages(kind: QueueKind, messages: usize, batch: usize) -> (u64, Duration) {
+    const CAPACITY: usize = 1024;
+
+    let start = Instant::now();
+    let checksum = match kind {
+        QueueKind::SpscRing => {
+            let (mut producer, mut consumer) = spsc_ring(CAPACITY);
+            let sender = thread::spawn(move || {
+                let mut batch_buf = Vec::with_capacity(batch);
+                let mut next = 0u64;
+                while (next as usize) < messages {
+                    batch_buf.clear();
+                    let end = (next as usize + batch).min(messages) as u64;
+                    batch_buf.extend(next..end);
+                    let mut sent = 0;
+                    while sent < batch_buf.len() {
+                        let pushed = producer.push_batch(&batch_buf[sent..]);
+                        if pushed == 0 {
+                            thread::yield_now();
+                        }
+                        sent += pushed;
+                    }
+                    next = end;
+                }
+            });
+            let mut out = vec![0u64; batch];
+            let mut received = 0;
+            let mut sum = 0u64;
+            while received < messages {
+                let n = consumer.pop_batch(&mut out);
+                if n == 0 {
+                    thread::yield_now();
+                }
+                sum = out[..n].iter().fold(sum, |acc, &v| acc.wrapping_add(v));
+                received += n;
+            }
+            sender.join().unwrap();
+            sum
+        }
+        QueueKind::CrossbeamBounded => {
+            let (tx, rx) = crossbeam::channel::bounded::<Vec<u64>>(CAPACITY / batch.max(1));
+            let sender = thread::spawn(move || {
+                let mut next = 0u64;
+                while (next as usize) < messages {
+                    let end = (next as usize + batch).min(messages) as u64;
+                    tx.send((next..end).collect()).unwrap();
+                    next = end;
+                }
+            });
+            let sum = rx.iter().flatten().fold(0u64, |acc, v| acc.wrapping_add(v));
+            sender.join().unwrap();
+            sum
+        }
+        QueueKind::StdSyncChannel => {
+            let (tx, rx) = mpsc::sync_channel::<Vec<u64>>(CAPACITY / batch.max(1));
+            let sender = thread::spawn(move || {
+                let mut next = 0u64;
+                while (next as usize) < messages {
+                    let end = (next as usize + batch).min(messages) as u64;
+                    tx.send((next..end).collect()).unwrap();
+                    next = end;
+                }
+            });
+            let sum = rx.iter().flatten().fold(0u64, |acc, v| acc.wrapping_add(v));
+            sender.join().unwrap();
+            sum
+        }
+    };
+    (checksum, start.elapsed())
+}
+
+// messages per batch in the spsc vs mpmc test
+const SPSC_BATCHES: [usize; 3] = [1, 16, 256];
+
+// spsc vs mpmc test: ns per message through a purpose-built ring buffer and
+// general channels at several batch sizes
+fn spsc_vs_mpmc_test(messages: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let expected = (0..messages as u64).fold(0u64, |acc, v| acc.wrapping_add(v));
+    for batch in SPSC_BATCHES {
+        for kind in QueueKind::ALL {
+            let (checksum, elapsed) = transfer_messages(kind, messages, batch);
+            if checksum != expected {
+                return Err(format!("{} at batch {} summed {} instead of {}", kind.label(), batch, checksum, expected));
+            }
+            eprintln!(
+                "spsc_vs_mpmc [{}, batch {}]: {:.1} ns/msg",
+                kind.label(),
+                batch,
+                elapsed.as_secs_f64() * 1e9 / messages as f64,
+            );
+        }
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// spin on an atomic until it reaches `target`, yielding only after a long
+// spin so single-core hosts still make progress
+fn spin_until(flag: &AtomicUsize, target: usize) {
+    let mut spins = 0u32;
+    while flag.load(Ordering::Acquire) != target {
+        if spins < 10_000 {
+            std::hint::spin_loop();
+            spins += 1;
+        } else {
+            thread::yield_now();
+        }
+    }
+}
+
+// round trips between two threads over a pair of channels
+fn ping_pong_channels(round_trips: usize) -> Duration {
+    let (ping_tx, ping_rx) = mpsc::channel::<usize>();
+    let (pong_tx, pong_rx) = mpsc::channel::<usize>();
+    let ponger = thread::spawn(move || {
+        for value in ping_rx {
+            if pong_tx.send(value).is_err() {
+                break;
+            }
+        }
+    });
+
+    let start = Instant::now();
+    for i in 0..round_trips {
+        ping_tx.send(i).unwrap();
+        black_box(pong_rx.recv().unwrap());
+    }
+    let elapsed = start.elapsed();
+
+    drop(ping_tx);
+    ponger.join().unwrap();
+    elapsed
+}
+
+// round trips handing a turn flag back and forth under a mutex and condvar
+fn ping_pong_condvar(round_trips: usize) -> Duration {
+    let shared = Arc::new((Mutex::new(0usize), Condvar::new()));
+    let ponger = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            let (lock, cvar) = &*shared;
+            for i in 0..round_trips {
+                let mut turn = cvar.wait_while(lock.lock().unwrap(), |turn| *turn != 2 * i + 1).unwrap();
+                *turn += 1;
+                cvar.notify_one();
+            }
+        })
+    };
+
+    let (lock, cvar) = &*shared;
+    let start = Instant::now();
+    for i in 0..round_trips {
+        let mut turn = lock.lock().unwrap();
+        *turn += 1;
+        cvar.notify_one();
+        let turn = cvar.wait_while(turn, |turn| *turn != 2 * i + 2).unwrap();
+        black_box(*turn);
+    }
+    let elapsed = start.elapsed();
+
+    ponger.join().unwrap();
+    elapsed
+}
+
+// round trips by busy-waiting on a shared atomic counter
+fn ping_pong_atomics(round_trips: usize) -> Duration {
+    let turn = Arc::new(AtomicUsize::new(0));
+    let ponger = {
+        let turn = turn.clone();
+        thread::spawn(move || {
+            for i in 0..round_trips {
+                spin_until(&turn, 2 * i + 1);
+                turn.store(2 * i + 2, Ordering::Release);
+            }
+        })
+    };
+
+    let start = Instant::now();
+    for i in 0..round_trips {
+        turn.store(2 * i + 1, Ordering::Release);
+        spin_until(&turn, 2 * i + 2);
+    }
+    let elapsed = start.elapsed();
+
+    ponger.join().unwrap();
+    elapsed
+}
+
+// one wakeup mechanism, timing the given number of two-thread round trips
+type PingPong = fn(usize) -> Duration;
+
+const PING_PONGS: [(&str, PingPong); 3] = [
+    ("channel pair", ping_pong_channels),
+    ("mutex + condvar", ping_pong_condvar),
+    ("spinning atomics", ping_pong_atomics),
+];
+
+// ping-pong test: two-thread round-trip time per wakeup mechanism, a direct
+// measure of context switch and wakeup cost
+fn ping_pong_test(round_trips: usize) -> f64 {
+    let timer = Timer::start();
+
+    for (label, mechanism) in PING_PONGS {
+        let elapsed = mechanism(round_trips);
+        eprintln!(
+            "ping_pong [{}]: {:.0} ns/round trip",
+            label,
+            elapsed.as_secs_f64() * 1e9 / round_trips as f64,
+        );
+    }
+
+    timer.elapsed_ms()
+}
+
+// wakeup latency of `sleepers` threads parked with thread::park and woken one
+// after another with unpark, over several rounds
+fn park_unpark_latencies(sleepers: usize, rounds: usize) -> Vec<Duration> {
+    let base = Instant::now();
+    let stamp = Arc::new(AtomicU64::new(0));
+    let generation = Arc::new(AtomicUsize::new(0));
+    let parked = Arc::new(AtomicUsize::new(0));
+    let latencies = Arc::new(Mutex::new(Vec::with_capacity(sleepers * rounds)));
+
+    let mut handles = Vec::with_capacity(sleepers);
+    for _ in 0..sleepers {
+        let (stamp, generation, parked, latencies) =
+            (stamp.clone(), generation.clone(), parked.clone(), latencies.clone());
+        handles.push(thread::spawn(move || {
+            for round in 0..rounds {
+                parked.fetch_add(1, Ordering::AcqRel);
+                while generation.load(Ordering::Acquire) <= round {
+                    thread::park();
+                }
+                let woke = base.elapsed();
+                let sent = Duration::from_nanos(stamp.load(Ordering::Acquire));
+                latencies.lock().unwrap().push(woke.saturating_sub(sent));
+            }
+        }));
+    }
+
+    for round in 0..rounds {
+        while parked.load(Ordering::Acquire) < sleepers * (round + 1) {
+            thread::yield_now();
+        }
+        // give the last sleeper time to actually reach park()
+        thread::sleep(Duration::from_micros(200));
+        stamp.store(base.elapsed().as_nanos() as u64, Ordering::Release);
+        generation.store(round + 1, Ordering::Release);
+        for handle in &handles {
+            handle.thread().unpark();
+        }
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut latencies = Arc::try_unwrap(latencies).unwrap().into_inner().unwrap();
+    latencies.sort();
+    latencies
+}
+
+// wakeup latency of `sleepers` threads waiting on one parking_lot condvar and
+// released together with notify_all
+fn parking_lot_notify_latencies(sleepers: usize, rounds: usize) -> Vec<Duration> {
+    let base = Instant::now();
+    // (generation, sleepers waiting in the current round)
+    let shared = Arc::new((parking_lot::Mutex::new((0usize, 0usize)), parking_lot::Condvar::new()));
+    let stamp = Arc::new(AtomicU64::new(0));
+    let latencies = Arc::new(Mutex::new(Vec::with_capacity(sleepers * rounds)));
+
+    let mut handles = Vec::with_capacity(sleepers);
+    for _ in 0..sleepers {
+        let (shared, stamp, latencies) = (shared.clone(), stamp.clone(), latencies.clone());
+        handles.push(thread::spawn(move || {
+            let (lock, cvar) = &*shared;
+            for round in 0..rounds {
+                let mut state = lock.lock();
+                state.1 += 1;
+                while state.0 <= round {
+                    cvar.wait(&mut state);
+                }
+                drop(state);
+                let woke = base.elapsed();
+                let sent = Duration::from_nanos(stamp.load(Ordering::Acquire));
+                latencies.lock().unwrap().push(woke.saturating_sub(sent));
+            }
+        }));
+    }
+
+    let (lock, cvar) = &*shared;
+    for _ in 0..rounds {
+        // sleepers register under the lock and release it inside wait, so a
+        // full count seen while holding the lock means everyone is asleep
+        let mut state = lock.lock();
+        while state.1 < sleepers {
+            drop(state);
+            thread::yield_now();
+            state = lock.lock();
+        }
+        state.0 += 1;
+        state.1 = 0;
+        stamp.store(base.elapsed().as_nanos() as u64, Ordering::Release);
+        cvar.notify_all();
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut latencies = Arc::try_unwrap(latencies).unwrap().into_inner().unwrap();
+    latencies.sort();
+    latencies
+}
+
+// sleeping thread counts the park/unpark test steps through
+const PARK_SLEEPERS: [usize; 3] = [1, 4, 16];
+
+// park/unpark test: wakeup latency distribution for std park/unpark and
+// contended parking_lot condvar waits as the number of sleepers grows; fails when
+// a sleeper missed a round
+fn park_unpark_test(rounds: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for sleepers in PARK_SLEEPERS {
+        let park = park_unpark_latencies(sleepers, rounds);
+        let notify = parking_lot_notify_latencies(sleepers, rounds);
+        for (label, latencies) in [("park/unpark", park), ("parking_lot notify_all", notify)] {
+            if latencies.len() != sleepers * rounds {
+                return Err(format!("{} woke {} of {} sleepers over {} rounds", label, latencies.len(), sleepers * rounds, rounds));
+            }
+            eprintln!(
+                "park_unpark [{}, {} sleepers]: wakeup p50 {:.1}us p99 {:.1}us max {:.1}us",
+                label,
+                sleepers,
+                percentile_us(&latencies, 0.50),
+                percentile_us(&latencies, 0.99),
+                percentile_us(&latencies, 1.0),
+            );
+        }
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// mutexes compared inside async tasks
+#[derive(Clone, Copy)]
+enum AsyncLockKind {
+    Tokio,
+    Std,
+    ParkingLot,
+}
+
+impl AsyncLockKind {
+    const ALL: [AsyncLockKind; 3] = [AsyncLockKind::Tokio, AsyncLockKind::Std, AsyncLockKind::ParkingLot];
+
+    fn label(self) -> &'static str {
+        match self {
+            AsyncLockKind::Tokio => "tokio::sync::Mutex",
+            AsyncLockKind::Std => "std::sync::Mutex",
+            AsyncLockKind::ParkingLot => "parking_lot::Mutex",
+        }
+    }
+}
+
+// one counter per mutex flavour so each run starts from the same state
+struct AsyncLockTargets {
+    tokio: tokio::sync::Mutex<u64>,
+    std: Mutex<u64>,
+    parking_lot: parking_lot::Mutex<u64>,
+}
+
+// the short critical section every lock guards
+fn critical_section(value: &mut u64) {
+    *value = value.wrapping_mul(31).wrapping_add(fibonacci(black_box(20)));
+}
+
+// what the guarded counter holds after `ops` critical sections, in any order
+fn critical_sections_result(ops: usize) -> u64 {
+    let mut value = 0;
+    for _ in 0..ops {
+        critical_section(&mut value);
+    }
+    value
+}
+
+// `tasks` async tasks share `total_ops` lock acquisitions, returning the wall time,
+// every acquire-to-release latency and the counter the lock guarded
+fn async_lock_run(kind: AsyncLockKind, tasks: usize, total_ops: usize) -> (Duration, Vec<Duration>, u64) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .build()
+        .unwrap();
+    let targets = Arc::new(AsyncLockTargets {
+        tokio: tokio::sync::Mutex::new(0),
+        std: Mutex::new(0),
+        parking_lot: parking_lot::Mutex::new(0),
+    });
+    let ops_per_task = total_ops / tasks;
+
+    runtime.block_on(async {
+        let start = Instant::now();
+        let mut handles = Vec::with_capacity(tasks);
+        for _ in 0..tasks {
+            let targets = targets.clone();
+            handles.push(tokio::spawn(async move {
+                let mut latencies = Vec::with_capacity(ops_per_task);
+                for i in 0..ops_per_task {
+                    let op_start = Instant::now();
+                    match kind {
+                        AsyncLockKind::Tokio => critical_section(&mut *targets.tokio.lock().await),
+                        AsyncLockKind::Std => critical_section(&mut targets.std.lock().unwrap()),
+                        AsyncLockKind::ParkingLot => critical_section(&mut targets.parking_lot.lock()),
+                    }
+                    latencies.push(op_start.elapsed());
+                    // let other tasks on this worker interleave, like real handlers would
+                    if i % 16 == 15 {
+                        tokio::task::yield_now().await;
+                    }
+                }
+                latencies
+            }));
+        }
+
+        let mut latencies = Vec::with_capacity(ops_per_task * tasks);
+        for handle in handles {
+            latencies.extend(handle.await.unwrap());
+        }
+        let elapsed = start.elapsed();
+        latencies.sort();
+        let value = match kind {
+            AsyncLockKind::Tokio => *targets.tokio.lock().await,
+            AsyncLockKind::Std => *targets.std.lock().unwrap(),
+            AsyncLockKind::ParkingLot => *targets.parking_lot.lock(),
+        };
+        (elapsed, latencies, value)
+    })
+}
+
+// task counts the async mutex test shares its lock acquisitions between
+const ASYNC_LOCK_TASKS: [usize; 3] = [1, 8, 64];
+
+// async mutex test: throughput and latency of tokio, std and parking_lot
+// mutexes guarding short critical sections inside async tasks; fails when the
+// guarded counter shows a lost critical section
+fn async_mutex_test(total_ops: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for tasks in ASYNC_LOCK_TASKS {
+        // the ops are split evenly, the remainder isn't run
+        let expected = critical_sections_result(total_ops / tasks * tasks);
+        for kind in AsyncLockKind::ALL {
+            let (elapsed, latencies, value) = async_lock_run(kind, tasks, total_ops);
+            if value != expected {
+                return Err(format!("{} with {} tasks lost critical sections", kind.label(), tasks));
+            }
+            eprintln!(
+                "async_mutex [{}, {} tasks]: {:.2} Mops/s, p50 {:.2}us p99 {:.2}us",
+                kind.label(),
+                tasks,
+                latencies.len() as f64 / elapsed.as_secs_f64() / 1e6,
+                percentile_us(&latencies, 0.50),
+                percentile_us(&latencies, 0.99),
+            );
+        }
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// the unit of work for the combinator test, resolves on first poll
+async fn trivial_future(x: u64) -> u64 {
+    black_box(x).wrapping_mul(2)
+}
+
+// ways of awaiting many futures compared by the combinator test
+#[derive(Clone, Copy)]
+enum CombinatorStyle {
+    Sequential,
+    Join4,
+    Select2,
+    JoinAll,
+    Unordered,
+    Boxed,
+    Unboxed,
+}
+
+impl CombinatorStyle {
+    const ALL: [CombinatorStyle; 7] = [
+        CombinatorStyle::Sequential,
+        CombinatorStyle::Join4,
+        CombinatorStyle::Select2,
+        CombinatorStyle::JoinAll,
+        CombinatorStyle::Unordered,
+        CombinatorStyle::Boxed,
+        CombinatorStyle::Unboxed,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            CombinatorStyle::Sequential => "sequential await",
+            CombinatorStyle::Join4 => "join! x4",
+            CombinatorStyle::Select2 => "select! x2",
+            CombinatorStyle::JoinAll => "join_all",
+            CombinatorStyle::Unordered => "FuturesUnordered",
+            CombinatorStyle::Boxed => "boxed dyn Future",
+            CombinatorStyle::Unboxed => "unboxed Vec<impl Future>",
+        }
+    }
+
+    // the checksum drive_combinator has to come back with: join! x4 awaits whole groups
+    // of four, past n when it isn't a multiple, and the biased select! x2 only ever
+    // takes the first of each pair
+    fn checksum(self, n: u64) -> u64 {
+        let inputs: Box<dyn Iterator<Item = u64>> = match self {
+            CombinatorStyle::Join4 => Box::new(0..n.next_multiple_of(4)),
+            CombinatorStyle::Select2 => Box::new((0..n).step_by(2)),
+            _ => Box::new(0..n),
+        };
+        inputs.fold(0u64, |acc, i| acc.wrapping_add(i.wrapping_mul(2)))
+    }
+}
+
+// awaits `n` trivial futures through one combinator style, returning the checksum
+async fn drive_combinator(style: CombinatorStyle, n: u64) -> u64 {
+    let mut sum = 0u64;
+    match style {
+        CombinatorStyle::Sequential => {
+            for i in 0..n {
+                sum = sum.wrapping_add(trivial_future(i).await);
+            }
+        }
+        CombinatorStyle::Join4 => {
+            for i in (0..n).step_by(4) {
+                let (a, b, c, d) = tokio::join!(
+                    trivial_future(i),
+                    trivial_future(i + 1),
+                    trivial_future(i + 2),
+                    trivial_future(i + 3),
+                );
+                sum = sum.wrapping_add(a).wrapping_add(b).wrapping_add(c).wrapping_add(d);
+            }
+        }
+        CombinatorStyle::Select2 => {
+            for i in (0..n).step_by(2) {
+                let value = tokio::select! {
+                    biased;
+                    a = trivial_future(i) => a,
+                    b = trivial_future(i + 1) => b,
+                };
+                sum = sum.wrapping_add(value);
+            }
+        }
+        CombinatorStyle::JoinAll => {
+            let results = futures::future::join_all((0..n).map(trivial_future)).await;
+            sum = results.into_iter().fold(sum, u64::wrapping_add);
+        }
+        CombinatorStyle::Unordered => {
+            let mut pending: FuturesUnordered<_> = (0..n).map(trivial_future).collect();
+            while let Some(value) = pending.next().await {
+                sum = sum.wrapping_add(value);
+            }
+        }
+        CombinatorStyle::Boxed => {
+            let boxed: Vec<Pin<Box<dyn Future<Output = u64>>>> =
+                (0..n).map(|i| Box::pin(trivial_future(i)) as Pin<Box<dyn Future<Output = u64>>>).collect();
+            for future in boxed {
+                sum = sum.wrapping_add(future.await);
+            }
+        }
+        CombinatorStyle::Unboxed => {
+            let unboxed: Vec<_> = (0..n).map(trivial_future).collect();
+            for future in unboxed {
+                sum = sum.wrapping_add(future.await);
+            }
+        }
+    }
+    sum
+}
+
+// futures combinator test: per-future overhead of join!, select!, join_all,
+// FuturesUnordered and boxed vs unboxed futures on a current-thread runtime
+fn futures_combinator_test(futures_per_style: u64) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().map_err(|e| e.to_string())?;
+    for style in CombinatorStyle::ALL {
+        let style_start = Instant::now();
+        let checksum = runtime.block_on(drive_combinator(style, futures_per_style));
+        let elapsed = style_start.elapsed();
+        if checksum != style.checksum(futures_per_style) {
+            return Err(format!("{} summed {} instead of {}", style.label(), checksum, style.checksum(futures_per_style)));
+        }
+        eprintln!(
+            "futures_combinator [{}]: {:.1} ns/future",
+            style.label(),
+            elapsed.as_secs_f64() * 1e9 / futures_per_style as f64,
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// worker counts the multi-threaded spawn test sweeps
+const SPAWN_WORKERS: [usize; 4] = [1, 2, 4, 8];
+
+// runtimes compared by the spawn tests
+#[derive(Clone, Copy)]
+enum SpawnRuntime {
+    CurrentThread,
+    MultiThread(usize),
+}
+
+impl SpawnRuntime {
+    fn label(self) -> String {
+        match self {
+            SpawnRuntime::CurrentThread => "current_thread".to_string(),
+            SpawnRuntime::MultiThread(workers) => format!("multi_thread, {} workers", workers),
+        }
+    }
+
+    fn build(self) -> std::io::Result<tokio::runtime::Runtime> {
+        match self {
+            SpawnRuntime::CurrentThread => tokio::runtime::Builder::new_current_thread().build(),
+            SpawnRuntime::MultiThread(workers) => tokio::runtime::Builder::new_multi_thread().worker_threads(workers).build(),
+        }
+    }
+}
+
+// spawns `tasks` trivial_futures and awaits every handle, from inside a task so the spawns
+// take the scheduler's usual path, not the one for spawns from outside the runtime; returns
+// the tasks' wrapping sum and the elapsed time, runtime construction not included
+fn spawn_trivial_tasks(runtime: SpawnRuntime, tasks: usize) -> std::io::Result<(u64, Duration)> {
+    let runtime = runtime.build()?;
+    let spawner = runtime.spawn(async move {
+        let start = Instant::now();
+        let handles: Vec<_> = (0..tasks as u64).map(|i| tokio::spawn(trivial_future(i))).collect();
+        let mut sum = 0u64;
+        for handle in handles {
+            sum = sum.wrapping_add(handle.await.unwrap());
+        }
+        (sum, start.elapsed())
+    });
+    runtime.block_on(spawner).map_err(std::io::Error::other)
+}
+
+// spawn test: ns per spawned and awaited task on each runtime, the scheduler's overhead
+// with no work in the tasks to hide it
+fn spawn_test(runtimes: &[SpawnRuntime], tasks: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let expected = (0..tasks as u64).fold(0u64, |acc, i| acc.wrapping_add(i.wrapping_mul(2)));
+    for &runtime in runtimes {
+        let (sum, elapsed) = spawn_trivial_tasks(runtime, tasks).map_err(|e| e.to_string())?;
+        if sum != expected {
+            return Err(format!("tasks on {} summed to {}, expected {}", runtime.label(), sum, expected));
+        }
+        eprintln!(
+            "task_spawn [{}]: {:.1} ns/task, {:.2} M tasks/s",
+            runtime.label(),
+            elapsed.as_secs_f64() * 1e9 / tasks as f64,
+            tasks as f64 / elapsed.as_secs_f64() / 1e6,
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// concurrency limits the stream pipeline test runs at
+const PIPELINE_LIMITS: [usize; 3] = [8, 64, 512];
+
+// map -> buffer_unordered -> fold over `items` simulated work items with at most `limit`
+// in flight, returning the folded checksum and every item's latency through the pipeline
+async fn stream_pipeline_run(items: usize, limit: usize) -> (u64, Vec<Duration>) {
+    futures::stream::iter(0..items as u64)
+        // parse stage, stamps each item as it enters the pipeline
+        .map(|id| (Instant::now(), fibonacci(black_box(id % 90))))
+        // simulated downstream call with 1ms of latency
+        .map(|(entered, value)| async move {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            (entered, value.wrapping_mul(3))
+        })
+        .buffer_unordered(limit)
+        .fold((0u64, Vec::with_capacity(items)), |(sum, mut latencies), (entered, value)| async move {
+            latencies.push(entered.elapsed());
+            (sum.wrapping_add(value), latencies)
+        })
+        .await
+}
+
+// what stream_pipeline_run has to fold `items` into, whatever order they finish in
+fn stream_pipeline_checksum(items: usize) -> u64 {
+    (0..items as u64).fold(0u64, |acc, id| acc.wrapping_add(fibonacci(id % 90).wrapping_mul(3)))
+}
+
+// stream pipeline test: map -> buffer_unordered -> fold over simulated work
+// items at several concurrency limits, reporting items/s and item latency
+fn stream_pipeline_test(items: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let expected = stream_pipeline_checksum(items);
+    for limit in PIPELINE_LIMITS {
+        let run_start = Instant::now();
+        let (checksum, mut latencies) = runtime.block_on(stream_pipeline_run(items, limit));
+        let elapsed = run_start.elapsed();
+        if checksum != expected || latencies.len() != items {
+            return Err(format!("buffer_unordered {} passed {} of {} items, checksum {} instead of {}", limit, latencies.len(), items, checksum, expected));
+        }
+        latencies.sort();
+        eprintln!(
+            "stream_pipeline [buffer_unordered {}]: {:.0} items/s, latency p50 {:.1}us p99 {:.1}us",
+            limit,
+            items as f64 / elapsed.as_secs_f64(),
+            percentile_us(&latencies, 0.50),
+            percentile_us(&latencies, 0.99),
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// max_blocking_threads limits the blocking pool test runs under
+const BLOCKING_LIMITS: [usize; 3] = [4, 16, 64];
+
+// `jobs` mixed short and long spawn_blocking jobs on a runtime capped at `max_blocking`
+// blocking threads, returning each job's queueing delay and the elapsed time
+fn blocking_pool_run(max_blocking: usize, jobs: usize) -> std::io::Result<(Vec<Duration>, Duration)> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .max_blocking_threads(max_blocking)
+        .build()?;
+
+    let run_start = Instant::now();
+    let delays = runtime.block_on(async {
+        let mut handles = Vec::with_capacity(jobs);
+        for i in 0..jobs {
+            let submitted = Instant::now();
+            handles.push(tokio::task::spawn_blocking(move || {
+                let queued = submitted.elapsed();
+                // every tenth job is a slow blocking call, the rest are quick
+                let blocked = if i % 10 == 9 { Duration::from_millis(20) } else { Duration::from_millis(1) };
+                thread::sleep(blocked);
+                queued
+            }));
+        }
+        let mut delays = Vec::with_capacity(jobs);
+        for handle in handles {
+            delays.push(handle.await.map_err(std::io::Error::other)?);
+        }
+        Ok::<_, std::io::Error>(delays)
+    })?;
+    Ok((delays, run_start.elapsed()))
+}
+
+// blocking pool test: mixed short and long spawn_blocking jobs under several
+// max_blocking_threads limits, reporting jobs/s and queueing delay
+fn blocking_pool_test(jobs: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for max_blocking in BLOCKING_LIMITS {
+        let (mut delays, elapsed) = blocking_pool_run(max_blocking, jobs).map_err(|e| e.to_string())?;
+        delays.sort();
+        eprintln!(
+            "blocking_pool [max_blocking_threads {}]: {:.0} jobs/s, queueing delay p50 {:.1}us p99 {:.1}us",
+            max_blocking,
+            jobs as f64 / elapsed.as_secs_f64(),
+            percentile_us(&delays, 0.50),
+            percentile_us(&delays, 0.99),
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// 32x32 tile multiply, the cpu half of the mixed workload
+fn matrix_tile_work(seed: u64) -> f64 {
+    const N: usize = 32;
+    let mut a = [[0.0f64; N]; N];
+    let mut b = [[0.0f64; N]; N];
+    let mut c = [[0.0f64; N]; N];
+
+    let mut rng = InputRng::lcg(seed);
+    for i in 0..N {
+        for j in 0..N {
+            a[i][j] = rng.next_unit();
+            b[i][j] = rng.next_unit();
+        }
+    }
+    for i in 0..N {
+        for k in 0..N {
+            for j in 0..N {
+                c[i][j] += a[i][k] * b[k][j];
+            }
+        }
+    }
+    (0..N).map(|i| c[i][i]).sum()
+}
+
+// echoes everything back on every accepted connection until the runtime stops
+async fn echo_server(listener: tokio::net::TcpListener) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    while let Ok((mut socket, _)) = listener.accept().await {
+        // echoes of messages bigger than the buffer go back in pieces, which nagle would hold
+        let _ = socket.set_nodelay(true);
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 4096];
+            while let Ok(n) = socket.read(&mut buf).await {
+                if n == 0 || socket.write_all(&buf[..n]).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+// client counts every echo sub-test sweeps; the server runs in the same process on
+// loopback, so they time the network stack rather than a network
+const ECHO_CONNECTIONS: [usize; 3] = [1, 4, 16];
+
+// a udp round trip that takes longer than this counts as lost
+const UDP_TIMEOUT: Duration = Duration::from_millis(200);
+
+// what the clients of one echo run saw: the latency of every answered round trip, how
+// many came back with the bytes that were sent, and the wall time of the whole run
+struct EchoRun {
+    latencies: Vec<Duration>,
+    intact: usize,
+    elapsed: Duration,
+}
+
+// a message whose first bytes are the round trip's sequence number, so a stale reply
+// never passes for the current one
+fn echo_message(client: usize, seq: usize, size: usize) -> Vec<u8> {
+    let mut message = vec![client as u8; size];
+    let seq = (seq as u64).to_le_bytes();
+    let len = seq.len().min(size);
+    message[..len].copy_from_slice(&seq[..len]);
+    message
+}
+
+// `connections` clients on their own tcp connections, each sending its share of the
+// round trips one at a time and waiting for the full echo before the next
+async fn tcp_echo_clients(addr: std::net::SocketAddr, size: usize, connections: usize, round_trips: usize) -> std::io::Result<EchoRun> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let per_client = (round_trips / connections).max(1);
+    let start = Instant::now();
+    let clients: Vec<_> = (0..connections)
+        .map(|client| {
+            tokio::spawn(async move {
+                let mut stream = tokio::net::TcpStream::connect(addr).await?;
+                stream.set_nodelay(true)?;
+                let mut echo = vec![0u8; size];
+                let mut latencies = Vec::with_capacity(per_client);
+                let mut intact = 0;
+                for seq in 0..per_client {
+                    let message = echo_message(client, seq, size);
+                    let sent = Instant::now();
+                    stream.write_all(&message).await?;
+                    stream.read_exact(&mut echo).await?;
+                    latencies.push(sent.elapsed());
+                    intact += (echo == message) as usize;
+                }
+                Ok::<_, std::io::Error>((latencies, intact))
+            })
+        })
+        .collect();
+    collect_echo_clients(clients, start).await
+}
+
+// answers every datagram with itself until the runtime stops
+async fn udp_echo_server(socket: tokio::net::UdpSocket) {
+    let mut buf = vec![0u8; 65536];
+    while let Ok((n, peer)) = socket.recv_from(&mut buf).await {
+        let _ = socket.send_to(&buf[..n], peer).await;
+    }
+}
+
+// like tcp_echo_clients with a socket per client; a datagram that isn't answered within
+// UDP_TIMEOUT is lost and left out of the latencies, late replies to it are skipped
+async fn udp_echo_clients(addr: std::net::SocketAddr, size: usize, connections: usize, round_trips: usize) -> std::io::Result<EchoRun> {
+    let per_client = (round_trips / connections).max(1);
+    let start = Instant::now();
+    let clients: Vec<_> = (0..connections)
+        .map(|client| {
+            tokio::spawn(async move {
+                let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await?;
+                socket.connect(addr).await?;
+                let mut echo = vec![0u8; size + 1];
+                let mut latencies = Vec::with_capacity(per_client);
+                let mut intact = 0;
+                let tag = size.min(8);
+                for seq in 0..per_client {
+                    let message = echo_message(client, seq, size);
+                    let sent = Instant::now();
+                    socket.send(&message).await?;
+                    while let Ok(received) = tokio::time::timeout(UDP_TIMEOUT, socket.recv(&mut echo)).await {
+                        let n = received?;
+                        if n >= tag && echo[..tag] == message[..tag] {
+                            latencies.push(sent.elapsed());
+                            intact += (echo[..n] == message[..]) as usize;
+                            break;
+                        }
+                    }
+                }
+                Ok::<_, std::io::Error>((latencies, intact))
+            })
+        })
+        .collect();
+    collect_echo_clients(clients, start).await
+}
+
+// like tcp_echo_clients over websocket connections to the mock server's /ws, one binary
+// frame per message
+async fn websocket_echo_clients(addr: std::net::SocketAddr, size: usize, connections: usize, round_trips: usize) -> std::io::Result<EchoRun> {
+    use futures::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let per_client = (round_trips / connections).max(1);
+    let start = Instant::now();
+    let clients: Vec<_> = (0..connections)
+        .map(|client| {
+            tokio::spawn(async move {
+                let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", addr))
+                    .await
+                    .map_err(std::io::Error::other)?;
+                let mut latencies = Vec::with_capacity(per_client);
+                let mut intact = 0;
+                for seq in 0..per_client {
+                    let message = echo_message(client, seq, size);
+                    let sent = Instant::now();
+                    socket.send(Message::binary(message.clone())).await.map_err(std::io::Error::other)?;
+                    let echo = loop {
+                        match socket.next().await {
+                            Some(Ok(Message::Binary(echo))) => break echo,
+                            Some(Ok(_)) => continue,
+                            Some(Err(err)) => return Err(std::io::Error::other(err)),
+                            None => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+                        }
+                    };
+                    latencies.push(sent.elapsed());
+                    intact += (echo == message) as usize;
+                }
+                let _ = socket.close(None).await;
+                Ok::<_, std::io::Error>((latencies, intact))
+            })
+        })
+        .collect();
+    collect_echo_clients(clients, start).await
+}
+
+// one echo client task, yielding its round trip latencies and how many came back intact
+type EchoClient = tokio::task::JoinHandle<std::io::Result<(Vec<Duration>, usize)>>;
+
+async fn collect_echo_clients(
+    clients: Vec<EchoClient>,
+    start: Instant,
+) -> std::io::Result<EchoRun> {
+    let mut run = EchoRun { latencies: Vec::new(), intact: 0, elapsed: Duration::ZERO };
+    for client in clients {
+        let (latencies, intact) = client.await.map_err(std::io::Error::other)??;
+        run.latencies.extend(latencies);
+        run.intact += intact;
+    }
+    run.elapsed = start.elapsed();
+    run.latencies.sort();
+    Ok(run)
+}
+
+#[derive(Clone, Copy)]
+enum EchoProtocol {
+    Tcp,
+    Udp,
+    WebSocket,
+}
+
+impl EchoProtocol {
+    fn name(self) -> &'static str {
+        match self {
+            EchoProtocol::Tcp => "tcp_echo",
+            EchoProtocol::Udp => "udp_echo",
+            EchoProtocol::WebSocket => "websocket_echo",
+        }
+    }
+
+    // binds a server on an ephemeral loopback port, spawns it and returns its address
+    async fn serve(self) -> std::io::Result<std::net::SocketAddr> {
+        match self {
+            EchoProtocol::Tcp => {
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+                let addr = listener.local_addr()?;
+                tokio::spawn(echo_server(listener));
+                Ok(addr)
+            }
+            EchoProtocol::Udp => {
+                let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await?;
+                let addr = socket.local_addr()?;
+                tokio::spawn(udp_echo_server(socket));
+                Ok(addr)
+            }
+            EchoProtocol::WebSocket => {
+                let (addr, server) = serve_mock()?;
+                tokio::spawn(server);
+                Ok(addr)
+            }
+        }
+    }
+
+    async fn clients(self, addr: std::net::SocketAddr, size: usize, connections: usize, round_trips: usize) -> std::io::Result<EchoRun> {
+        match self {
+            EchoProtocol::Tcp => tcp_echo_clients(addr, size, connections, round_trips).await,
+            EchoProtocol::Udp => udp_echo_clients(addr, size, connections, round_trips).await,
+            EchoProtocol::WebSocket => websocket_echo_clients(addr, size, connections, round_trips).await,
+        }
+    }
+}
+
+// echo test: round trips per second, bytes per second both ways and latency percentiles
+// for each client count in ECHO_CONNECTIONS; the round trips are split across the clients
+fn echo_test(protocol: EchoProtocol, size: usize, round_trips: usize) -> Result<f64, String> {
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime.block_on(async {
+        let addr = protocol.serve().await.map_err(|e| e.to_string())?;
+        let timer = Timer::start();
+        for connections in ECHO_CONNECTIONS {
+            let run = protocol.clients(addr, size, connections, round_trips).await.map_err(|e| e.to_string())?;
+            let answered = run.latencies.len();
+            if run.intact != answered {
+                return Err(format!("{} of {} echoes came back changed", answered - run.intact, answered));
+            }
+            let seconds = run.elapsed.as_secs_f64();
+            let sent = connections * (round_trips / connections).max(1);
+            let lost = if sent > answered { format!(", {} lost", sent - answered) } else { String::new() };
+            eprintln!(
+                "{} [{} B, {} connections]: {:.0} round trips/s, {:.1} MiB/s, p50 {:.1} us, p99 {:.1} us{}",
+                protocol.name(),
+                size,
+                connections,
+                answered as f64 / seconds,
+                (2 * size * answered) as f64 / seconds / (1024.0 * 1024.0),
+                percentile_us(&run.latencies, 0.50),
+                percentile_us(&run.latencies, 0.99),
+                lost,
+            );
+        }
+        Ok(timer.elapsed_ms())
+    })
+}
+
+// one io task: write+read a small file and a 1KB tcp echo per operation
+async fn mixed_io_task(dir: PathBuf, addr: std::net::SocketAddr, task_id: usize, ops: usize) -> std::io::Result<Vec<Duration>> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = tokio::net::TcpStream::connect(addr).await?;
+    let payload = vec![task_id as u8; 1024];
+    let mut echo = vec![0u8; 1024];
+    let mut latencies = Vec::with_capacity(ops);
+    for op in 0..ops {
+        let op_start = Instant::now();
+        let path = dir.join(format!("mixed_{}_{}.dat", task_id, op));
+        tokio::fs::write(&path, &payload).await?;
+        let contents = tokio::fs::read(&path).await?;
+        let _ = tokio::fs::remove_file(&path).await;
+        stream.write_all(&contents).await?;
+        stream.read_exact(&mut echo).await?;
+        if echo != payload {
+            return Err(std::io::Error::other(format!("io task {} got its file or echo back changed", task_id)));
+        }
+        latencies.push(op_start.elapsed());
+    }
+    Ok(latencies)
+}
+
+// which workload classes share the runtime in one mixed run
+#[derive(Clone, Copy, PartialEq)]
+enum MixedClasses {
+    CpuOnly,
+    IoOnly,
+    Both,
+}
+
+// runs the selected classes together on one 4-worker runtime, returning the
+// cpu class wall time and the io class per-operation latencies
+fn mixed_workload_run(
+    classes: MixedClasses,
+    cpu_tasks: usize,
+    tiles_per_task: usize,
+    io_tasks: usize,
+    ops_per_task: usize,
+) -> std::io::Result<(Option<Duration>, Vec<Duration>)> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+
+    runtime.block_on(async move {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(echo_server(listener));
+
+        let cpu = (classes != MixedClasses::IoOnly).then(|| {
+            tokio::spawn(async move {
+                let cpu_start = Instant::now();
+                let handles: Vec<_> = (0..cpu_tasks)
+                    .map(|t| {
+                        tokio::spawn(async move {
+                            let mut sum = 0.0;
+                            for tile in 0..tiles_per_task {
+                                sum += matrix_tile_work((t * tiles_per_task + tile) as u64);
+                            }
+                            black_box(sum)
+                        })
+                    })
+                    .collect();
+                for handle in handles {
+                    let _ = handle.await;
+                }
+                cpu_start.elapsed()
+            })
+        });
+        let io: Vec<_> = if classes != MixedClasses::CpuOnly {
+            (0..io_tasks)
+                .map(|t| tokio::spawn(mixed_io_task(dir.clone(), addr, t, ops_per_task)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut latencies = Vec::with_capacity(io_tasks * ops_per_task);
+        for handle in io {
+            latencies.extend(handle.await.map_err(std::io::Error::other)??);
+        }
+        let cpu_elapsed = match cpu {
+            Some(handle) => Some(handle.await.map_err(std::io::Error::other)?),
+            None => None,
+        };
+        latencies.sort();
+        Ok((cpu_elapsed, latencies))
+    })
+}
+
+// the mixed workload test's task counts, the tiles and io operations per task scale
+const MIXED_CPU_TASKS: usize = 8;
+const MIXED_IO_TASKS: usize = 16;
+
+// mixed workload test: cpu tiles and file+network io alone and sharing one
+// runtime, reporting how much each class slows the other down
+fn mixed_workload_test(cpu_tasks: usize, tiles_per_task: usize, io_tasks: usize, ops_per_task: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let run = |classes| mixed_workload_run(classes, cpu_tasks, tiles_per_task, io_tasks, ops_per_task).map_err(|e| e.to_string());
+    let (cpu_alone, _) = run(MixedClasses::CpuOnly)?;
+    let (_, io_alone) = run(MixedClasses::IoOnly)?;
+    let (cpu_mixed, io_mixed) = run(MixedClasses::Both)?;
+
+    let tiles = (cpu_tasks * tiles_per_task) as f64;
+    // the cpu class runs in both of these, so its time is always there
+    let cpu_alone = cpu_alone.unwrap_or_default().as_secs_f64();
+    let cpu_mixed = cpu_mixed.unwrap_or_default().as_secs_f64();
+    eprintln!(
+        "mixed_workload [cpu tiles]: {:.0} tiles/s alone, {:.0} tiles/s mixed ({:.2}x slower)",
+        tiles / cpu_alone,
+        tiles / cpu_mixed,
+        cpu_mixed / cpu_alone,
+    );
+    eprintln!(
+        "mixed_workload [file+tcp io]: op latency p50 {:.1}us -> {:.1}us, p99 {:.1}us -> {:.1}us",
+        percentile_us(&io_alone, 0.50),
+        percentile_us(&io_mixed, 0.50),
+        percentile_us(&io_alone, 0.99),
+        percentile_us(&io_mixed, 0.99),
+    );
+
+    Ok(timer.elapsed_ms())
+}
+
+// long-running task that only stops when its token is cancelled, returning
+// the moment it noticed
+async fn cancellable_worker(token: tokio_util::sync::CancellationToken) -> Instant {
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => return Instant::now(),
+            _ = tokio::time::sleep(Duration::from_millis(1)) => {
+                black_box(fibonacci(black_box(40)));
+            }
+        }
+    }
+}
+
+// cancellation test: per-task cost of tokio::time::timeout wrappers, then
+// cancel a fraction of long-running tasks cooperatively and abort the rest,
+// reporting how long cancellation takes to land; fails when a cancelled task
+// never noticed or an aborted one finished anyway
+fn cancellation_test(tasks: usize, cancel_fraction: f64) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    runtime.block_on(async {
+        // timeout wrapper overhead on tasks that finish immediately
+        let bare_start = Instant::now();
+        let handles: Vec<_> = (0..tasks as u64).map(|i| tokio::spawn(trivial_future(i))).collect();
+        for handle in handles {
+            black_box(handle.await.map_err(|e| e.to_string())?);
+        }
+        let bare = bare_start.elapsed();
+
+        let wrapped_start = Instant::now();
+        let handles: Vec<_> = (0..tasks as u64)
+            .map(|i| tokio::spawn(tokio::time::timeout(Duration::from_secs(60), trivial_future(i))))
+            .collect();
+        for handle in handles {
+            black_box(handle.await.map_err(|e| e.to_string())?.map_err(|e| e.to_string())?);
+        }
+        let wrapped = wrapped_start.elapsed();
+
+        let per_task_ns = |elapsed: Duration| elapsed.as_secs_f64() * 1e9 / tasks as f64;
+        eprintln!(
+            "cancellation [timeout wrapper]: {:.0} ns/task bare, {:.0} ns/task with timeout (+{:.0} ns)",
+            per_task_ns(bare),
+            per_task_ns(wrapped),
+            per_task_ns(wrapped) - per_task_ns(bare),
+        );
+
+        // long-running tasks, every one behind a timeout that never fires
+        let running = tasks / 10;
+        let cancel_every = (1.0 / cancel_fraction.clamp(0.01, 1.0)).round() as usize;
+        let mut cooperative = Vec::new();
+        let mut aborted = Vec::new();
+        for i in 0..running {
+            let token = tokio_util::sync::CancellationToken::new();
+            let handle = tokio::spawn(tokio::time::timeout(Duration::from_secs(60), cancellable_worker(token.clone())));
+            if i % cancel_every == 0 {
+                cooperative.push((token, handle));
+            } else {
+                aborted.push(handle);
+            }
+        }
+        // let every task get past its first poll so cancellation hits mid-flight
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let cancelled_at = Instant::now();
+        for (token, _) in &cooperative {
+            token.cancel();
+        }
+        let cancelled = cooperative.len();
+        let mut cooperative_latencies = Vec::with_capacity(cancelled);
+        for (_, handle) in cooperative {
+            if let Ok(Ok(noticed)) = handle.await {
+                cooperative_latencies.push(noticed.saturating_duration_since(cancelled_at));
+            }
+        }
+        if cooperative_latencies.len() != cancelled {
+            return Err(format!("{} of {} cancelled tasks noticed their token", cooperative_latencies.len(), cancelled));
+        }
+
+        let mut abort_latencies = Vec::with_capacity(aborted.len());
+        for handle in aborted {
+            let abort_start = Instant::now();
+            handle.abort();
+            match handle.await {
+                Err(e) if e.is_cancelled() => abort_latencies.push(abort_start.elapsed()),
+                _ => return Err("an aborted task finished instead of being cancelled".to_string()),
+            }
+        }
+
+        cooperative_latencies.sort();
+        abort_latencies.sort();
+        eprintln!(
+            "cancellation [token, {} tasks]: propagation p50 {:.1}us p99 {:.1}us",
+            cooperative_latencies.len(),
+            percentile_us(&cooperative_latencies, 0.50),
+            percentile_us(&cooperative_latencies, 0.99),
+        );
+        eprintln!(
+            "cancellation [JoinHandle::abort, {} tasks]: abort-to-join p50 {:.1}us p99 {:.1}us",
+            abort_latencies.len(),
+            percentile_us(&abort_latencies, 0.50),
+            percentile_us(&abort_latencies, 0.99),
+        );
+        Ok(())
+    })?;
+
+    Ok(timer.elapsed_ms())
+}
+
+// ways of handing a large read-only dataset to worker threads
+#[derive(Clone, Copy)]
+enum SharingStyle {
+    ScopedBorrow,
+    ArcClone,
+    DeepClone,
+    Rayon,
+}
+
+impl SharingStyle {
+    const ALL: [SharingStyle; 4] = [
+        SharingStyle::ScopedBorrow,
+        SharingStyle::ArcClone,
+        SharingStyle::DeepClone,
+        SharingStyle::Rayon,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            SharingStyle::ScopedBorrow => "thread::scope borrow",
+            SharingStyle::ArcClone => "Arc<Vec> clone",
+            SharingStyle::DeepClone => "Vec clone per thread",
+            SharingStyle::Rayon => "rayon par_chunks",
+        }
+    }
+}
+
+// read-only pass over one worker's share of the dataset
+fn checksum_chunk(chunk: &[u64]) -> u64 {
+    chunk.iter().fold(0u64, |acc, &v| acc.wrapping_add(v.rotate_left(7) ^ 0x9e37_79b9))
+}
+
+// one parallel pass in the given style; with `touch_all` false each worker
+// only reads its first element, isolating spawn and sharing setup cost
+fn sharing_pass(style: SharingStyle, data: &Arc<Vec<u64>>, threads: usize, touch_all: bool) -> u64 {
+    let chunk_len = data.len().div_ceil(threads);
+    let work = move |chunk: &[u64]| if touch_all { checksum_chunk(chunk) } else { chunk[0] };
+
+    match style {
+        SharingStyle::ScopedBorrow => thread::scope(|s| {
+            let handles: Vec<_> = data.chunks(chunk_len).map(|chunk| s.spawn(move || work(chunk))).collect();
+            handles.into_iter().fold(0u64, |acc, h| acc.wrapping_add(h.join().unwrap()))
+        }),
+        SharingStyle::ArcClone => {
+            let handles: Vec<_> = (0..threads)
+                .map(|t| {
+                    let data = Arc::clone(data);
+                    thread::spawn(move || {
+                        let end = ((t + 1) * chunk_len).min(data.len());
+                        work(&data[t * chunk_len..end])
+                    })
+                })
+                .collect();
+            handles.into_iter().fold(0u64, |acc, h| acc.wrapping_add(h.join().unwrap()))
+        }
+        SharingStyle::DeepClone => {
+            let handles: Vec<_> = (0..threads)
+                .map(|t| {
+                    let data: Vec<u64> = data.as_ref().clone();
+                    thread::spawn(move || {
+                        let end = ((t + 1) * chunk_len).min(data.len());
+                        work(&data[t * chunk_len..end])
+                    })
+                })
+                .collect();
+            handles.into_iter().fold(0u64, |acc, h| acc.wrapping_add(h.join().unwrap()))
+        }
+        SharingStyle::Rayon => {
+            use rayon::prelude::*;
+            data.par_chunks(chunk_len).map(work).reduce(|| 0, u64::wrapping_add)
+        }
+    }
+}
+
+// scoped sharing test: setup cost and steady-state throughput of borrowing
+// a dataset with thread::scope versus Arc clones, deep clones and rayon; fails
+// when a style's full pass doesn't sum to the serial checksum
+fn scoped_sharing_test(elements: usize, threads: usize) -> Result<f64, String> {
+    const SETUP_ROUNDS: usize = 50;
+    const PASSES: usize = 5;
+
+    let data: Arc<Vec<u64>> = Arc::new((0..elements as u64).map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15)).collect());
+    let bytes = (elements * std::mem::size_of::<u64>()) as f64;
+    let expected = checksum_chunk(&data);
+
+    let timer = Timer::start();
+
+    for style in SharingStyle::ALL {
+        let setup_start = Instant::now();
+        for _ in 0..SETUP_ROUNDS {
+            black_box(sharing_pass(style, &data, threads, false));
+        }
+        let setup = setup_start.elapsed() / SETUP_ROUNDS as u32;
+
+        let pass_start = Instant::now();
+        for _ in 0..PASSES {
+            let checksum = black_box(sharing_pass(style, &data, threads, true));
+            if checksum != expected {
+                return Err(format!("{} summed to {:#x}, the serial checksum is {:#x}", style.label(), checksum, expected));
+            }
+        }
+        let pass = pass_start.elapsed() / PASSES as u32;
+
+        eprintln!(
+            "scoped_sharing [{}]: setup {:.1}us, steady state {:.2} GB/s",
+            style.label(),
+            setup.as_secs_f64() * 1e6,
+            bytes / pass.as_secs_f64() / 1e9,
+        );
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+thread_local! {
+    // lazily initialized: every access checks the init state first, which is what this
+    // variant measures, so it must not be made const
+    #[allow(clippy::missing_const_for_thread_local)]
+    static TLS_LAZY_COUNTER: Cell<u64> = Cell::new(black_box(0));
+    // const initialized: no lazy-init check on access
+    static TLS_CONST_COUNTER: Cell<u64> = const { Cell::new(0) };
+}
+
+// a Cell rather than a static mut, so bumping it needs no unsafe
+#[cfg(feature = "nightly")]
+#[thread_local]
+static RAW_TLS_COUNTER: Cell<u64> = Cell::new(0);
+
+const TLS_SHARDS: usize = 64;
+
+static SHARDED_COUNTERS: [crossbeam::utils::CachePadded<AtomicU64>; TLS_SHARDS] =
+    [const { crossbeam::utils::CachePadded::new(AtomicU64::new(0)) }; TLS_SHARDS];
+
+// per-thread counter flavours compared by the tls test
+#[derive(Clone, Copy)]
+enum TlsAccess {
+    LazyThreadLocal,
+    ConstThreadLocal,
+    #[cfg(feature = "nightly")]
+    RawThreadLocal,
+    ShardedByThreadId,
+}
+
+impl TlsAccess {
+    const ALL: &[TlsAccess] = &[
+        TlsAccess::LazyThreadLocal,
+        TlsAccess::ConstThreadLocal,
+        #[cfg(feature = "nightly")]
+        TlsAccess::RawThreadLocal,
+        TlsAccess::ShardedByThreadId,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            TlsAccess::LazyThreadLocal => "thread_local! lazy init",
+            TlsAccess::ConstThreadLocal => "thread_local! const init",
+            #[cfg(feature = "nightly")]
+            TlsAccess::RawThreadLocal => "#[thread_local] static",
+            TlsAccess::ShardedByThreadId => "array sharded by thread id",
+        }
+    }
+
+    // bump this thread's counter once
+    fn increment(self) {
+        match self {
+            TlsAccess::LazyThreadLocal => TLS_LAZY_COUNTER.with(|c| c.set(c.get() + 1)),
+            TlsAccess::ConstThreadLocal => TLS_CONST_COUNTER.with(|c| c.set(c.get() + 1)),
+            #[cfg(feature = "nightly")]
+            TlsAccess::RawThreadLocal => RAW_TLS_COUNTER.set(RAW_TLS_COUNTER.get() + 1),
+            TlsAccess::ShardedByThreadId => {
+                use std::hash::{Hash, Hasher};
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                thread::current().id().hash(&mut hasher);
+                let shard = hasher.finish() as usize % TLS_SHARDS;
+                SHARDED_COUNTERS[shard].fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    // the bumps counted so far: this thread's for the thread-locals, every thread's
+    // for the shards
+    fn counted(self) -> u64 {
+        match self {
+            TlsAccess::LazyThreadLocal => TLS_LAZY_COUNTER.with(Cell::get),
+            TlsAccess::ConstThreadLocal => TLS_CONST_COUNTER.with(Cell::get),
+            #[cfg(feature = "nightly")]
+            TlsAccess::RawThreadLocal => RAW_TLS_COUNTER.get(),
+            TlsAccess::ShardedByThreadId => SHARDED_COUNTERS.iter().map(|c| c.load(Ordering::Relaxed)).sum(),
+        }
+    }
+
+    // every thread's bumps with `threads` fresh threads each bumping `ops` times,
+    // and how long that took
+    fn run(self, threads: usize, ops: usize) -> (u64, Duration) {
+        let before = self.counted();
+        let start = Instant::now();
+        let per_thread: u64 = thread::scope(|s| {
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    s.spawn(move || {
+                        for _ in 0..ops {
+                            black_box(self).increment();
+                        }
+                        self.counted()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        let elapsed = start.elapsed();
+        let counted = match self {
+            TlsAccess::ShardedByThreadId => self.counted() - before,
+            _ => per_thread,
+        };
+        (counted, elapsed)
+    }
+}
+
+// the thread counts the tls test runs each flavour with
+const TLS_THREADS: [usize; 3] = [1, 4, 16];
+
+// thread-local storage test: ns per counter increment through each tls
+// flavour with 1, 4 and 16 threads hammering their own counters; fails when a
+// flavour loses a bump
+fn thread_local_test(ops_per_thread: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for threads in TLS_THREADS {
+        for &access in TlsAccess::ALL {
+            let (counted, elapsed) = access.run(threads, ops_per_thread);
+            if counted != (ops_per_thread * threads) as u64 {
+                return Err(format!("{} counted {} of {} bumps", access.label(), counted, ops_per_thread * threads));
+            }
+            eprintln!(
+                "thread_local [{}, {} threads]: {:.2} ns/op",
+                access.label(),
+                threads,
+                elapsed.as_secs_f64() * 1e9 / (ops_per_thread * threads) as f64,
+            );
+        }
+    }
+    #[cfg(not(feature = "nightly"))]
+    eprintln!("thread_local [#[thread_local] static]: skipped, build with --features nightly on a nightly toolchain");
+
+    Ok(timer.elapsed_ms())
+}
+
+// variable-cost unit of work for the scheduler tests
+fn skewed_task_work(cost: u32) -> u64 {
+    let mut acc = cost as u64;
+    for i in 0..cost as u64 * 100 {
+        acc = acc.wrapping_mul(6364136223846793005).wrapping_add(i);
+    }
+    black_box(acc)
+}
+
+// task costs with one heavy task in ten, all injected at a single worker
+fn skewed_task_costs(tasks: usize) -> Vec<u32> {
+    let mut rng = XorShift32(0x2545_f491);
+    (0..tasks)
+        .map(|i| if i % 10 == 0 { 100 + rng.next() % 100 } else { 1 + rng.next() % 10 })
+        .collect()
+}
+
+// tiny xorshift used to draw task costs
+struct XorShift32(u32);
+
+impl XorShift32 {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+}
+
+// small work-stealing scheduler: every task starts in worker 0's deque and
+// idle workers steal; returns elapsed time, successful steals and the wrapping
+// sum of every task's work
+fn work_stealing_run(costs: &[u32], workers: usize) -> (Duration, usize, u64) {
+    use crossbeam::deque::{Steal, Stealer, Worker};
+
+    let locals: Vec<Worker<u32>> = (0..workers).map(|_| Worker::new_fifo()).collect();
+    for &cost in costs {
+        locals[0].push(cost);
+    }
+    let stealers: Vec<Stealer<u32>> = locals.iter().map(|w| w.stealer()).collect();
+    let remaining = AtomicUsize::new(costs.len());
+    let steals = AtomicUsize::new(0);
+
+    let start = Instant::now();
+    let checksum = thread::scope(|s| {
+        let handles: Vec<_> = locals.into_iter().enumerate().map(|(id, local)| {
+            let (stealers, remaining, steals) = (&stealers, &remaining, &steals);
+            s.spawn(move || {
+                let mut acc = 0u64;
+                while remaining.load(Ordering::Acquire) > 0 {
+                    let task = local.pop().or_else(|| {
+                        // try victims in turn, moving half their queue over on success
+                        (1..workers).map(|offset| &stealers[(id + offset) % workers]).find_map(|victim| {
+                            match victim.steal_batch_and_pop(&local) {
+                                Steal::Success(task) => {
+                                    steals.fetch_add(1, Ordering::Relaxed);
+                                    Some(task)
+                                }
+                                _ => None,
+                            }
+                        })
+                    });
+                    match task {
+                        Some(cost) => {
+                            acc = acc.wrapping_add(skewed_task_work(cost));
+                            remaining.fetch_sub(1, Ordering::AcqRel);
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+                acc
+            })
+        }).collect();
+        handles.into_iter().fold(0u64, |acc, h| acc.wrapping_add(h.join().unwrap()))
+    });
+    (start.elapsed(), steals.into_inner(), checksum)
+}
+
+// baseline: the same tasks pulled by every worker from one locked queue; returns
+// elapsed time and the wrapping sum of every task's work
+fn shared_queue_run(costs: &[u32], workers: usize) -> (Duration, u64) {
+    let queue = Mutex::new(costs.iter().copied().collect::<std::collections::VecDeque<u32>>());
+
+    let start = Instant::now();
+    let checksum = thread::scope(|s| {
+        let handles: Vec<_> = (0..workers).map(|_| {
+            let queue = &queue;
+            s.spawn(move || {
+                let mut acc = 0u64;
+                loop {
+                    let task = queue.lock().unwrap().pop_front();
+                    match task {
+                        Some(cost) => acc = acc.wrapping_add(skewed_task_work(cost)),
+                        None => break,
+                    }
+                }
+                acc
+            })
+        }).collect();
+        handles.into_iter().fold(0u64, |acc, h| acc.wrapping_add(h.join().unwrap()))
+    });
+    (start.elapsed(), checksum)
+}
+
+// work distribution test: crossbeam-deque work stealing against a single
+// shared queue on skewed task injection and skewed task costs; fails when either
+// runs a task other than once
+fn work_stealing_test(tasks: usize, workers: usize) -> Result<f64, String> {
+    let start = Instant::now();
+
+    let costs = skewed_task_costs(tasks);
+    let expected = costs.iter().fold(0u64, |acc, &cost| acc.wrapping_add(skewed_task_work(cost)));
+    let (stealing, steals, stolen_sum) = work_stealing_run(&costs, workers);
+    let (shared, shared_sum) = shared_queue_run(&costs, workers);
+    if stolen_sum != expected || shared_sum != expected {
+        return Err(format!(
+            "work stealing summed to {:#x} and the shared queue to {:#x}, the serial sum is {:#x}",
+            stolen_sum, shared_sum, expected
+        ));
+    }
+    eprintln!(
+        "work_stealing [crossbeam-deque, {} workers]: {:.2}ms, {} batch steals ({:.1} per 100 tasks)",
+        workers,
+        stealing.as_secs_f64() * 1000.0,
+        steals,
+        steals as f64 * 100.0 / tasks as f64,
+    );
+    eprintln!(
+        "work_stealing [shared Mutex<VecDeque>, {} workers]: {:.2}ms",
+        workers,
+        shared.as_secs_f64() * 1000.0,
+    );
+
+    let duration = start.elapsed();
+    Ok(duration.as_secs_f64() * 1000.0)
+}
+
+// task costs rising with the index plus some noise, so equal-sized chunks carry very
+// unequal work: the last of four chunks holds 7/16 of it
+fn rising_task_costs(tasks: usize) -> Vec<u32> {
+    let mut rng = XorShift32(0x2545_f491);
+    (0..tasks)
+        .map(|i| 1 + (i * 200 / tasks.max(1)) as u32 + rng.next() % 10)
+        .collect()
+}
+
+// ways of splitting the rising costs across threads compared by the partitioning tests
+#[derive(Clone, Copy)]
+enum Partitioning {
+    WorkStealing,
+    Static,
+}
+
+impl Partitioning {
+    // wrapping sum of skewed_task_work over every cost; `pool` only runs the rayon version
+    fn run(self, pool: &rayon::ThreadPool, costs: &[u32], workers: usize) -> u64 {
+        match self {
+            Partitioning::WorkStealing => {
+                use rayon::prelude::*;
+                pool.install(|| costs.par_iter().map(|&cost| skewed_task_work(cost)).reduce(|| 0, u64::wrapping_add))
+            }
+            // one contiguous chunk per thread, decided up front and never rebalanced
+            Partitioning::Static => thread::scope(|s| {
+                costs
+                    .chunks(costs.len().div_ceil(workers).max(1))
+                    .map(|chunk| s.spawn(move || chunk.iter().fold(0u64, |acc, &cost| acc.wrapping_add(skewed_task_work(cost)))))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .fold(0u64, |acc, handle| acc.wrapping_add(handle.join().unwrap()))
+            }),
+        }
+    }
+}
+
+// partitioning test: the rising costs through rayon's par_iter, which splits adaptively
+// and steals, or through equal chunks on plain threads; the pool is built before timing
+fn partition_test(partitioning: Partitioning, tasks: usize, workers: usize) -> Result<f64, String> {
+    let costs = rising_task_costs(tasks);
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(workers).build().map_err(|e| e.to_string())?;
+
+    let timer = Timer::start();
+    let checksum = partitioning.run(&pool, &costs, workers);
+    let ms = timer.elapsed_ms();
+
+    black_box(checksum);
+    Ok(ms)
+}
+
+// thread counts of a scaling curve: the powers of two below the available cpus, then the
+// cpus themselves
+fn scaling_threads() -> Vec<usize> {
+    let cpus = available_cpus();
+    let mut counts: Vec<usize> = std::iter::successors(Some(1usize), |n| Some(n * 2)).take_while(|&n| n < cpus).collect();
+    counts.push(cpus);
+    counts
+}
+
+fn thread_list(counts: &[usize]) -> String {
+    counts.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",")
+}
+
+// memory orderings compared by the atomics tests, each as the read-modify-write ordering
+// and the matching one for plain loads and failed compare_exchanges
+#[derive(Clone, Copy)]
+enum AtomicOrdering {
+    Relaxed,
+    AcquireRelease,
+    SeqCst,
+}
+
+impl AtomicOrdering {
+    const ALL: [AtomicOrdering; 3] = [AtomicOrdering::Relaxed, AtomicOrdering::AcquireRelease, AtomicOrdering::SeqCst];
+
+    fn label(self) -> &'static str {
+        match self {
+            AtomicOrdering::Relaxed => "relaxed",
+            AtomicOrdering::AcquireRelease => "acquire/release",
+            AtomicOrdering::SeqCst => "seqcst",
+        }
+    }
+
+    fn rmw(self) -> Ordering {
+        match self {
+            AtomicOrdering::Relaxed => Ordering::Relaxed,
+            AtomicOrdering::AcquireRelease => Ordering::AcqRel,
+            AtomicOrdering::SeqCst => Ordering::SeqCst,
+        }
+    }
+
+    fn load(self) -> Ordering {
+        match self {
+            AtomicOrdering::Relaxed => Ordering::Relaxed,
+            AtomicOrdering::AcquireRelease => Ordering::Acquire,
+            AtomicOrdering::SeqCst => Ordering::SeqCst,
+        }
+    }
+}
+
+// ways the atomics tests bump the shared counter
+#[derive(Clone, Copy)]
+enum AtomicOp {
+    FetchAdd,
+    // load, then compare_exchange until no other thread got in between
+    CompareExchange,
+}
+
+// from the first thread's start to the last one's end, as the threads timed themselves;
+// on a busy or single-cpu host the thread that spawned them may only get to run again
+// once they have all finished
+fn threads_span(spans: &[(Instant, Instant)]) -> Duration {
+    let first = spans.iter().map(|&(start, _)| start).min();
+    let last = spans.iter().map(|&(_, end)| end).max();
+    match (first, last) {
+        (Some(first), Some(last)) => last - first,
+        _ => Duration::ZERO,
+    }
+}
+
+// `ops` increments of one shared counter split across `threads`, which start together;
+// returns the final count, the failed compare_exchanges and the elapsed time
+fn atomic_run(op: AtomicOp, ordering: AtomicOrdering, threads: usize, ops: usize) -> (u64, u64, Duration) {
+    let counter = AtomicU64::new(0);
+    let barrier = std::sync::Barrier::new(threads);
+    let per_thread = ops / threads;
+    let (failures, elapsed) = thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                s.spawn(|| {
+                    barrier.wait();
+                    let start = Instant::now();
+                    let mut failures = 0u64;
+                    for _ in 0..per_thread {
+                        match op {
+                            AtomicOp::FetchAdd => {
+                                counter.fetch_add(1, ordering.rmw());
+                            }
+                            AtomicOp::CompareExchange => {
+                                let mut current = counter.load(ordering.load());
+                                while let Err(actual) = counter.compare_exchange(current, current + 1, ordering.rmw(), ordering.load()) {
+                                    current = actual;
+                                    failures += 1;
+                                }
+                            }
+                        }
+                    }
+                    (failures, start, Instant::now())
+                })
+            })
+            .collect();
+        let runs: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        let spans: Vec<_> = runs.iter().map(|&(_, start, end)| (start, end)).collect();
+        (runs.iter().map(|&(failures, _, _)| failures).sum(), threads_span(&spans))
+    });
+    (counter.into_inner(), failures, elapsed)
+}
+
+// atomics test: throughput of one contended counter per ordering, as a curve over the
+// thread counts; compare_exchange also reports how often it had to retry
+fn atomic_test(op: AtomicOp, thread_counts: &[usize], ops: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for ordering in AtomicOrdering::ALL {
+        let mut curve = Vec::with_capacity(thread_counts.len());
+        for &threads in thread_counts {
+            let (count, failures, elapsed) = atomic_run(op, ordering, threads, ops);
+            let expected = (ops / threads * threads) as u64;
+            if count != expected {
+                return Err(format!("{} threads counted {} of {} increments", threads, count, expected));
+            }
+            let point = format!("{} thread{} {:.1} M ops/s", threads, if threads == 1 { "" } else { "s" }, count as f64 / elapsed.as_secs_f64() / 1e6);
+            curve.push(match op {
+                AtomicOp::FetchAdd => point,
+                AtomicOp::CompareExchange => format!("{} ({:.2} retries/op)", point, failures as f64 / count as f64),
+            });
+        }
+        let name = match op {
+            AtomicOp::FetchAdd => "atomic_fetch_add",
+            AtomicOp::CompareExchange => "atomic_compare_exchange",
+        };
+        eprintln!("{} [{}]: {}", name, ordering.label(), curve.join(", "));
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// spin-based barrier: the last thread to arrive resets the count and moves the generation
+// on, the others spin until it has
+struct SpinBarrier {
+    threads: usize,
+    arrived: crossbeam::utils::CachePadded<AtomicUsize>,
+    generation: crossbeam::utils::CachePadded<AtomicUsize>,
+}
+
+impl SpinBarrier {
+    fn new(threads: usize) -> Self {
+        SpinBarrier {
+            threads,
+            arrived: crossbeam::utils::CachePadded::new(AtomicUsize::new(0)),
+            generation: crossbeam::utils::CachePadded::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+// what the phases synchronize on
+trait PhaseBarrier: Sync {
+    fn wait(&self);
+}
+
+impl PhaseBarrier for std::sync::Barrier {
+    fn wait(&self) {
+        std::sync::Barrier::wait(self);
+    }
+}
+
+impl PhaseBarrier for SpinBarrier {
+    fn wait(&self) {
+        let generation = self.generation.load(Ordering::Acquire);
+        if self.arrived.fetch_add(1, Ordering::AcqRel) + 1 == self.threads {
+            // reset before the release, so the next phase starts counting from zero
+            self.arrived.store(0, Ordering::Relaxed);
+            self.generation.store(generation + 1, Ordering::Release);
+        } else {
+            spin_until(&self.generation, generation + 1);
+        }
+    }
+}
+
+// no synchronization at all, for the cost of the phases' work alone
+struct NoBarrier;
+
+impl PhaseBarrier for NoBarrier {
+    fn wait(&self) {}
+}
+
+// ways the phased computation synchronizes between its phases
+#[derive(Clone, Copy)]
+enum PhaseSync {
+    Unsynchronized,
+    Std,
+    Spin,
+}
+
+impl PhaseSync {
+    fn label(self) -> &'static str {
+        match self {
+            PhaseSync::Unsynchronized => "work alone",
+            PhaseSync::Std => "std::sync::Barrier",
+            PhaseSync::Spin => "spin barrier",
+        }
+    }
+
+    fn run(self, threads: usize, phases: usize) -> Result<Duration, String> {
+        match self {
+            PhaseSync::Unsynchronized => phased_run(&NoBarrier, false, threads, phases),
+            PhaseSync::Std => phased_run(&std::sync::Barrier::new(threads), true, threads, phases),
+            PhaseSync::Spin => phased_run(&SpinBarrier::new(threads), true, threads, phases),
+        }
+    }
+}
+
+// cost of one phase's computation, in skewed_task_work units
+const PHASE_WORK: u32 = 10;
+
+// every thread computes, publishes the phase it finished and waits at the barrier, then
+// checks that nobody is still behind that phase; returns the elapsed time
+fn phased_run<B: PhaseBarrier>(barrier: &B, check: bool, threads: usize, phases: usize) -> Result<Duration, String> {
+    let finished: Vec<AtomicUsize> = (0..threads).map(|_| AtomicUsize::new(0)).collect();
+    let start_line = std::sync::Barrier::new(threads);
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let (finished, start_line) = (&finished, &start_line);
+                s.spawn(move || {
+                    start_line.wait();
+                    let start = Instant::now();
+                    for phase in 1..=phases {
+                        skewed_task_work(black_box(PHASE_WORK));
+                        finished[t].store(phase, Ordering::Release);
+                        barrier.wait();
+                        if check {
+                            // the fastest threads may already be one phase ahead, never behind
+                            if let Some(behind) = finished.iter().map(|f| f.load(Ordering::Acquire)).find(|&f| f < phase) {
+                                return Err(format!("phase {} passed the barrier with a thread still at {}", phase, behind));
+                            }
+                        }
+                    }
+                    Ok((start, Instant::now()))
+                })
+            })
+            .collect();
+        let spans = handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Result<Vec<_>, String>>()?;
+        Ok(threads_span(&spans))
+    })
+}
+
+// barrier test: ns per phase of a phased computation on each barrier, for every thread
+// count; the overhead is what a barrier adds to the phases' work alone
+fn barrier_test(thread_counts: &[usize], phases: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    for &threads in thread_counts {
+        let work_ns = PhaseSync::Unsynchronized.run(threads, phases)?.as_secs_f64() * 1e9 / phases as f64;
+        let mut line = format!("barrier [{} thread{}]: {} {:.0} ns/phase", threads, if threads == 1 { "" } else { "s" }, PhaseSync::Unsynchronized.label(), work_ns);
+        for sync in [PhaseSync::Std, PhaseSync::Spin] {
+            let ns = sync.run(threads, phases)?.as_secs_f64() * 1e9 / phases as f64;
+            line.push_str(&format!(", {} {:.0} ns/phase (+{:.0} ns)", sync.label(), ns, (ns - work_ns).max(0.0)));
+        }
+        eprintln!("{}", line);
+    }
+
+    Ok(timer.elapsed_ms())
+}
+
+// how the false sharing test lays out its per-thread counters
+#[derive(Clone, Copy)]
+enum CounterLayout {
+    // next to each other, several to a cache line
+    Adjacent,
+    // each on its own cache line
+    Padded,
+}
+
+impl CounterLayout {
+    fn label(self) -> &'static str {
+        match self {
+            CounterLayout::Adjacent => "adjacent",
+            CounterLayout::Padded => "padded",
+        }
+    }
+}
+
+// every thread bumps only its own counter, none of them ever reads another's
+fn bump_own_counters<T: Sync>(counters: &[T], counter: fn(&T) -> &AtomicU64, ops_per_thread: usize) -> Duration {
+    let barrier = std::sync::Barrier::new(counters.len());
+    thread::scope(|s| {
+        let handles: Vec<_> = counters
+            .iter()
+            .map(|slot| {
+                let barrier = &barrier;
+                s.spawn(move || {
+                    let own = counter(slot);
+                    barrier.wait();
+                    let start = Instant::now();
+                    for _ in 0..ops_per_thread {
+                        own.fetch_add(1, Ordering::Relaxed);
+                    }
+                    (start, Instant::now())
+                })
+            })
+            .collect();
+        let spans: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        threads_span(&spans)
+    })
+}
+
+// one counter per thread in the given layout; returns the final counts and the elapsed time
+fn false_sharing_run(layout: CounterLayout, threads: usize, ops_per_thread: usize) -> (Vec<u64>, Duration) {
+    match layout {
+        CounterLayout::Adjacent => {
+            let counters: Vec<AtomicU64> = (0..threads).map(|_| AtomicU64::new(0)).collect();
+            let elapsed = bump_own_counters(&counters, |slot| slot, ops_per_thread);
+            (counters.into_iter().map(AtomicU64::into_inner).collect(), elapsed)
+        }
+        CounterLayout::Padded => {
+            let counters: Vec<crossbeam::utils::CachePadded<AtomicU64>> =
+                (0..threads).map(|_| crossbeam::utils::CachePadded::new(AtomicU64::new(0))).collect();
+            let elapsed = bump_own_counters(&counters, |slot| slot, ops_per_thread);
+            (counters.into_iter().map(|slot| slot.into_inner().into_inner()).collect(), elapsed)
+        }
+    }
+}
+
+// false sharing test: the same per-thread increments with the counters adjacent and then
+// padded apart; the ratio between the two is the penalty of sharing a cache line
+fn false_sharing_test(threads: usize, ops_per_thread: usize) -> Result<f64, String> {
+    let timer = Timer::start();
+
+    let mut ns_per_op = [0.0; 2];
+    for (slot, layout) in [CounterLayout::Adjacent, CounterLayout::Padded].into_iter().enumerate() {
+        let (counts, elapsed) = false_sharing_run(layout, threads, ops_per_thread);
+        if let Some(count) = counts.iter().find(|&&count| count != ops_per_thread as u64) {
+            return Err(format!("{} counter reached {} of {}", layout.label(), count, ops_per_thread));
+        }
+        ns_per_op[slot] = elapsed.as_secs_f64() * 1e9 / (ops_per_thread * threads) as f64;
+        eprintln!("false_sharing [{}, {} threads]: {:.2} ns/op", layout.label(), threads, ns_per_op[slot]);
+    }
+    eprintln!(
+        "false_sharing: adjacent counters take {:.2}x the time of padded ones ({}-byte lines)",
+        ns_per_op[0] / ns_per_op[1],
+        std::mem::align_of::<crossbeam::utils::CachePadded<AtomicU64>>(),
+    );
+
+    Ok(timer.elapsed_ms())
+}
+
+// argument that makes this binary exit straight away, the trivial child for process_spawn;
+// the harness checks for it too, since in-process runs spawn the harness binary
+pub const SPAWN_CHILD_ARG: &str = "--spawn-child";
+
+// one spawn + exec + exit + wait round trip of this binary as a do-nothing child
+fn spawn_child() -> std::io::Result<Duration> {
+    let exe = env::current_exe()?;
+    let spawn_start = Instant::now();
+    let status = std::process::Command::new(exe)
+        .arg(SPAWN_CHILD_ARG)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("spawned child exited with {}", status)));
+    }
+    Ok(spawn_start.elapsed())
+}
+
+// process spawn test: the first (cold) start of this binary, then spawn round trips
+// one after another and with `parallel` children in flight at once
+fn process_spawn_test(spawns: usize, parallel: usize) -> Result<f64, String> {
+    let start = Instant::now();
+
+    let cold = spawn_child().map_err(|e| e.to_string())?;
+    eprintln!("process_spawn [cold start]: {:.0} us", cold.as_secs_f64() * 1e6);
+
+    let mut sequential = (0..spawns).map(|_| spawn_child()).collect::<std::io::Result<Vec<Duration>>>().map_err(|e| e.to_string())?;
+    sequential.sort();
+    eprintln!(
+        "process_spawn [sequential]: p50 {:.0} us, p99 {:.0} us per round trip",
+        percentile_us(&sequential, 0.50),
+        percentile_us(&sequential, 0.99),
+    );
+
+    let concurrent_start = Instant::now();
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..parallel)
+            .map(|_| {
+                scope.spawn(|| {
+                    while next.fetch_add(1, Ordering::Relaxed) < spawns {
+                        black_box(spawn_child()?);
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+        handles.into_iter().try_for_each(|h| h.join().unwrap())
+    })
+    .map_err(|e: std::io::Error| e.to_string())?;
+    let concurrent = concurrent_start.elapsed();
+    eprintln!(
+        "process_spawn [{} in flight]: {:.0} spawns/s ({:.0} spawns/s sequential)",
+        parallel,
+        spawns as f64 / concurrent.as_secs_f64(),
+        spawns as f64 / sequential.iter().sum::<Duration>().as_secs_f64(),
+    );
+
+    let duration = start.elapsed();
+    Ok(duration.as_secs_f64() * 1000.0)
+}
+
+// a fixed thread count for the sub-tests in THREAD_SCALED, what `benchmark run --threads`
+// sweeps; none when RUST_BENCH_THREADS is unset or isn't a thread count
+fn thread_override() -> Option<usize> {
+    let value = env::var("RUST_BENCH_THREADS").ok()?;
+    match value.trim().parse::<usize>() {
+        Ok(threads) if threads > 0 => Some(threads),
+        _ => {
+            eprintln!("Ignoring RUST_BENCH_THREADS={}, it isn't a thread count", value);
+            None
+        }
+    }
+}
+
+// thread count for the core multi-threaded tests, capped by a container cpu quota
+fn suite_threads() -> usize {
+    thread_override().unwrap_or_else(|| available_cpus().min(4))
+}
+
+// thread count for the tests that spread over every cpu we can use
+fn all_cpu_threads() -> usize {
+    thread_override().unwrap_or_else(available_cpus)
+}
+
+fn lock_threads() -> usize {
+    thread_override().unwrap_or(LOCK_THREADS)
+}
+
+// the sub-tests whose thread count RUST_BENCH_THREADS sets, the pools run twice as many
+pub const THREAD_SCALED: &[&str] = &[
+    "producer_consumer",
+    "parallel_math",
+    "thread_pool",
+    "tokio_mpsc",
+    "tokio_broadcast",
+    "tokio_watch",
+    "tokio_notify",
+    "locks_read_mostly",
+    "locks_balanced",
+    "locks_write_only",
+    "partition_stealing",
+    "partition_static",
+    "thread_pool_cpu",
+    "thread_pool_io",
+    "scoped_sharing",
+    "work_stealing",
+    "process_spawn",
+];
+
+// runs an async sub-test on a fresh runtime, outside of its own timing
+fn block_on_runtime<T, F: Future<Output = T>>(test: F) -> Result<T, String> {
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    Ok(runtime.block_on(test))
+}
+
+// under a container cpu quota the threaded tests keep the total work but use fewer threads
+pub const SUBTESTS: &[SubTest] = &[
+    SubTest {
+        name: "parallel_http",
+        params: |scale| format!("requests={} endpoint=/fast", scaled(50, scale)),
+        run: |scale| {
+            let server = MockServer::start().map_err(|e| e.to_string())?;
+            block_on_runtime(parallel_http_test(server.url("/fast"), scaled(50, scale)))?
+        },
+    },
+    SubTest {
+        name: "producer_consumer",
+        params: |scale| {
+            let threads = suite_threads();
+            format!("pairs={} items_per_thread={}", threads, scaled(4000, scale) / threads)
+        },
+        run: |scale| {
+            let threads = suite_threads();
+            Ok(producer_consumer_test(threads, scaled(4000, scale) / threads))
+        },
+    },
+    SubTest {
+        name: "parallel_math",
+        params: |scale| {
+            let threads = suite_threads();
+            format!("threads={} work_per_thread={}", threads, scaled(400, scale) / threads)
+        },
+        run: |scale| {
+            let threads = suite_threads();
+            Ok(parallel_math_test(threads, scaled(400, scale) / threads))
+        },
+    },
+    SubTest {
+        name: "async_file",
+        params: |scale| format!("files={}", scaled(20, scale)),
+        run: |scale| block_on_runtime(async_file_test(scaled(20, scale))),
+    },
+    SubTest {
+        name: "thread_pool",
+        params: |scale| format!("pool_size={} tasks={}", 2 * suite_threads(), scaled(500, scale)),
+        run: |scale| Ok(thread_pool_test(2 * suite_threads(), scaled(500, scale))),
+    },
+];
+
+fn echo_params(size: usize, round_trips: usize) -> String {
+    let connections: Vec<String> = ECHO_CONNECTIONS.iter().map(|c| c.to_string()).collect();
+    format!("message={} connections={} round_trips={}", size, connections.join(","), round_trips)
+}
+
+// rust-only sub-tests the other languages have no counterpart for: async runtime hazards,
+// cancellation, sharing and thread-local costs, process spawning, mpmc queue topologies, tokio::sync coordination, lock contention, atomic orderings,
+// barriers, false sharing, task spawning, work stealing against static partitioning,
+// thread pool throughput, http client protocols and connection reuse, tcp, udp and
+// websocket echo over loopback; they stay out of SUBTESTS and the total, the harness runs
+// them as sub-tests of their own
+pub const EXTENDED_SUBTESTS: &[SubTest] = &[
+    SubTest {
+        name: "blocking_in_async",
+        params: |scale| format!("light_tasks={} heavy_tasks={}", scaled(50, scale), scaled(8, scale)),
+        run: |scale| blocking_in_async_test(scaled(50, scale), scaled(8, scale)),
+    },
+    SubTest {
+        name: "scheduler_fairness",
+        params: |scale| format!("workers={} long_tasks={} short_tasks={}", FAIRNESS_WORKERS, FAIRNESS_LONG_TASKS, scaled(2000, scale)),
+        run: |scale| scheduler_fairness_test(FAIRNESS_WORKERS, FAIRNESS_LONG_TASKS, scaled(2000, scale)),
+    },
+    SubTest {
+        name: "spsc_vs_mpmc",
+        params: |scale| {
+            let batches: Vec<String> = SPSC_BATCHES.iter().map(|b| b.to_string()).collect();
+            format!("messages={} batches={}", scaled(1_000_000, scale), batches.join(","))
+        },
+        run: |scale| spsc_vs_mpmc_test(scaled(1_000_000, scale)),
+    },
+    SubTest {
+        name: "ping_pong",
+        params: |scale| format!("round_trips={} per mechanism", scaled(20_000, scale)),
+        run: |scale| Ok(ping_pong_test(scaled(20_000, scale))),
+    },
+    SubTest {
+        name: "park_unpark",
+        params: |scale| {
+            let sleepers: Vec<String> = PARK_SLEEPERS.iter().map(|s| s.to_string()).collect();
+            format!("rounds={} sleepers={}", scaled(200, scale), sleepers.join(","))
+        },
+        run: |scale| park_unpark_test(scaled(200, scale)),
+    },
+    SubTest {
+        name: "async_mutex",
+        params: |scale| {
+            let tasks: Vec<String> = ASYNC_LOCK_TASKS.iter().map(|t| t.to_string()).collect();
+            format!("ops={} tasks={} workers=4", scaled(200_000, scale), tasks.join(","))
+        },
+        run: |scale| async_mutex_test(scaled(200_000, scale)),
+    },
+    SubTest {
+        name: "futures_combinator",
+        params: |scale| format!("futures={} per style", scaled(1_000_000, scale)),
+        run: |scale| futures_combinator_test(scaled(1_000_000, scale) as u64),
+    },
+    SubTest {
+        name: "stream_pipeline",
+        params: |scale| {
+            let limits: Vec<String> = PIPELINE_LIMITS.iter().map(|l| l.to_string()).collect();
+            format!("items={} in_flight={} workers=4", scaled(2000, scale), limits.join(","))
+        },
+        run: |scale| stream_pipeline_test(scaled(2000, scale)),
+    },
+    SubTest {
+        name: "blocking_pool",
+        params: |scale| {
+            let limits: Vec<String> = BLOCKING_LIMITS.iter().map(|l| l.to_string()).collect();
+            format!("jobs={} max_blocking_threads={}", scaled(400, scale), limits.join(","))
+        },
+        run: |scale| blocking_pool_test(scaled(400, scale)),
+    },
+    SubTest {
+        name: "mixed_workload",
+        params: |scale| format!("cpu_tasks={} tiles_per_task={} io_tasks={} ops_per_task={}", MIXED_CPU_TASKS, scaled(500, scale), MIXED_IO_TASKS, scaled(50, scale)),
+        run: |scale| mixed_workload_test(MIXED_CPU_TASKS, scaled(500, scale), MIXED_IO_TASKS, scaled(50, scale)),
+    },
+    SubTest {
+        name: "cancellation",
+        params: |scale| format!("tasks={} long_running={} cancelled=25%", scaled(100_000, scale), scaled(100_000, scale) / 10),
+        run: |scale| cancellation_test(scaled(100_000, scale), 0.25),
+    },
+    SubTest {
+        name: "scoped_sharing",
+        params: |scale| format!("elements={} threads={}", scaled(2_000_000, scale), suite_threads()),
+        run: |scale| scoped_sharing_test(scaled(2_000_000, scale), suite_threads()),
+    },
+    SubTest {
+        name: "thread_local",
+        params: |scale| format!("ops_per_thread={} threads={:?}", scaled(5_000_000, scale), TLS_THREADS),
+        run: |scale| thread_local_test(scaled(5_000_000, scale)),
+    },
+    SubTest {
+        name: "work_stealing",
+        params: |scale| format!("tasks={} workers={}", scaled(100_000, scale), suite_threads()),
+        run: |scale| work_stealing_test(scaled(100_000, scale), suite_threads()),
+    },
+    SubTest {
+        name: "process_spawn",
+        params: |scale| format!("spawns={} in_flight={}", scaled(200, scale), suite_threads()),
+        run: |scale| process_spawn_test(scaled(200, scale), suite_threads()),
+    },
+    SubTest {
+        name: "mpmc_1to1",
+        params: |scale| format!("producers=1 consumers=1 messages={}", scaled(1_000_000, scale)),
+        run: |scale| mpmc_test(1, 1, scaled(1_000_000, scale)),
+    },
+    SubTest {
+        name: "mpmc_nto1",
+        params: |scale| format!("producers={} consumers=1 messages={}", MPMC_THREADS, scaled(1_000_000, scale)),
+        run: |scale| mpmc_test(MPMC_THREADS, 1, scaled(1_000_000, scale)),
+    },
+    SubTest {
+        name: "mpmc_ntom",
+        params: |scale| format!("producers={} consumers={} messages={}", MPMC_THREADS, MPMC_THREADS, scaled(1_000_000, scale)),
+        run: |scale| mpmc_test(MPMC_THREADS, MPMC_THREADS, scaled(1_000_000, scale)),
+    },
+    SubTest {
+        name: "tokio_mpsc",
+        params: |scale| format!("producers={0} consumers={0} messages={1} workers={2}", SYNC_TASKS, scaled(200_000, scale), suite_threads()),
+        run: |scale| tokio_sync_test(SyncPrimitive::Mpsc, suite_threads(), scaled(200_000, scale)),
+    },
+    SubTest {
+        name: "tokio_broadcast",
+        params: |scale| format!("producers={0} consumers={0} messages={1} workers={2}", SYNC_TASKS, scaled(200_000, scale), suite_threads()),
+        run: |scale| tokio_sync_test(SyncPrimitive::Broadcast, suite_threads(), scaled(200_000, scale)),
+    },
+    SubTest {
+        name: "tokio_watch",
+        params: |scale| format!("producers={0} consumers={0} messages={1} workers={2}", SYNC_TASKS, scaled(200_000, scale), suite_threads()),
+        run: |scale| tokio_sync_test(SyncPrimitive::Watch, suite_threads(), scaled(200_000, scale)),
+    },
+    SubTest {
+        name: "tokio_notify",
+        params: |scale| format!("producers={0} consumers={0} messages={1} workers={2}", SYNC_TASKS, scaled(200_000, scale), suite_threads()),
+        run: |scale| tokio_sync_test(SyncPrimitive::Notify, suite_threads(), scaled(200_000, scale)),
+    },
+    SubTest {
+        name: "locks_read_mostly",
+        params: |scale| format!("threads={} ops={} writes=5%", lock_threads(), scaled(1_000_000, scale)),
+        run: |scale| lock_contention_test(lock_threads(), scaled(1_000_000, scale), 5),
+    },
+    SubTest {
+        name: "locks_balanced",
+        params: |scale| format!("threads={} ops={} writes=50%", lock_threads(), scaled(1_000_000, scale)),
+        run: |scale| lock_contention_test(lock_threads(), scaled(1_000_000, scale), 50),
+    },
+    SubTest {
+        name: "locks_write_only",
+        params: |scale| format!("threads={} ops={} writes=100%", lock_threads(), scaled(1_000_000, scale)),
+        run: |scale| lock_contention_test(lock_threads(), scaled(1_000_000, scale), 100),
+    },
+    SubTest {
+        name: "spawn_current_thread",
+        params: |scale| format!("tasks={}", scaled(200_000, scale)),
+        run: |scale| spawn_test(&[SpawnRuntime::CurrentThread], scaled(200_000, scale)),
+    },
+    SubTest {
+        name: "spawn_multi_thread",
+        params: |scale| {
+            let workers: Vec<String> = SPAWN_WORKERS.iter().map(|w| w.to_string()).collect();
+            format!("tasks={} workers={}", scaled(200_000, scale), workers.join(","))
+        },
+        run: |scale| spawn_test(&SPAWN_WORKERS.map(SpawnRuntime::MultiThread), scaled(200_000, scale)),
+    },
+    SubTest {
+        name: "partition_stealing",
+        params: |scale| format!("tasks={} threads={}", scaled(20_000, scale), all_cpu_threads()),
+        run: |scale| partition_test(Partitioning::WorkStealing, scaled(20_000, scale), all_cpu_threads()),
+    },
+    SubTest {
+        name: "partition_static",
+        params: |scale| format!("tasks={} threads={}", scaled(20_000, scale), all_cpu_threads()),
+        run: |scale| partition_test(Partitioning::Static, scaled(20_000, scale), all_cpu_threads()),
+    },
+    SubTest {
+        name: "thread_pool_cpu",
+        params: |scale| format!("pool_size={} tasks={}", 2 * suite_threads(), scaled(20_000, scale)),
+        run: |scale| thread_pool_throughput_test(PoolLoad::Cpu, 2 * suite_threads(), scaled(20_000, scale)),
+    },
+    SubTest {
+        name: "thread_pool_io",
+        params: |scale| format!("pool_size={} tasks={} wait=1ms", 2 * suite_threads(), scaled(2000, scale)),
+        run: |scale| thread_pool_throughput_test(PoolLoad::Io, 2 * suite_threads(), scaled(2000, scale)),
+    },
+    SubTest {
+        name: "atomic_fetch_add",
+        params: |scale| format!("ops={} threads={}", scaled(2_000_000, scale), thread_list(&scaling_threads())),
+        run: |scale| atomic_test(AtomicOp::FetchAdd, &scaling_threads(), scaled(2_000_000, scale)),
+    },
+    SubTest {
+        name: "atomic_compare_exchange",
+        params: |scale| format!("ops={} threads={}", scaled(2_000_000, scale), thread_list(&scaling_threads())),
+        run: |scale| atomic_test(AtomicOp::CompareExchange, &scaling_threads(), scaled(2_000_000, scale)),
+    },
+    SubTest {
+        name: "barrier_phases",
+        params: |scale| format!("phases={} threads={}", scaled(20_000, scale), thread_list(&scaling_threads())),
+        run: |scale| barrier_test(&scaling_threads(), scaled(20_000, scale)),
+    },
+    SubTest {
+        name: "false_sharing",
+        // it takes at least two threads to share a line; the work grows with the threads,
+        // so RUST_BENCH_THREADS leaves it alone
+        params: |scale| format!("threads={} ops={} per thread", available_cpus().clamp(2, 4), scaled(10_000_000, scale)),
+        run: |scale| false_sharing_test(available_cpus().clamp(2, 4), scaled(10_000_000, scale)),
+    },
+    SubTest {
+        name: "http1_keepalive",
+        params: |scale| format!("requests={} in_flight={} endpoint=/fast", scaled(2000, scale), HTTP_IN_FLIGHT),
+        run: |scale| http_client_test(HttpClientMode::KeepAlive, scaled(2000, scale)),
+    },
+    SubTest {
+        name: "http1_fresh",
+        params: |scale| format!("requests={} in_flight={} endpoint=/fast", scaled(2000, scale), HTTP_IN_FLIGHT),
+        run: |scale| http_client_test(HttpClientMode::Fresh, scaled(2000, scale)),
+    },
+    SubTest {
+        name: "http2_multiplexed",
+        params: |scale| format!("requests={} in_flight={} endpoint=/fast", scaled(2000, scale), HTTP_IN_FLIGHT),
+        run: |scale| http_client_test(HttpClientMode::Multiplexed, scaled(2000, scale)),
+    },
+    SubTest {
+        name: "tcp_echo_small",
+        params: |scale| echo_params(64, scaled(20_000, scale)),
+        run: |scale| echo_test(EchoProtocol::Tcp, 64, scaled(20_000, scale)),
+    },
+    SubTest {
+        name: "tcp_echo_large",
+        params: |scale| echo_params(64 << 10, scaled(2000, scale)),
+        run: |scale| echo_test(EchoProtocol::Tcp, 64 << 10, scaled(2000, scale)),
+    },
+    SubTest {
+        name: "udp_echo",
+        params: |scale| echo_params(512, scaled(20_000, scale)),
+        run: |scale| echo_test(EchoProtocol::Udp, 512, scaled(20_000, scale)),
+    },
+    SubTest {
+        name: "websocket_echo_small",
+        params: |scale| echo_params(64, scaled(20_000, scale)),
+        run: |scale| echo_test(EchoProtocol::WebSocket, 64, scaled(20_000, scale)),
+    },
+    SubTest {
+        name: "websocket_echo_large",
+        params: |scale| echo_params(64 << 10, scaled(2000, scale)),
+        run: |scale| echo_test(EchoProtocol::WebSocket, 64 << 10, scaled(2000, scale)),
+    },
+];
+
+fn expect_count<T: PartialEq + std::fmt::Display>(what: &str, got: T, expected: T) -> Result<(), String> {
+    if got == expected {
+        Ok(())
+    } else {
+        Err(format!("{} {}, expected {}", got, what, expected))
+    }
+}
+
+// every light task finishes all of its sleeps wherever the heavy work runs
+fn check_blocking_in_async() -> Result<(), String> {
+    for placement in HeavyPlacement::ALL {
+        let delays = light_task_latencies(placement, 3, 1);
+        expect_count(&format!("wakeups with {}", placement.label()), delays.len(), 3 * LIGHT_ROUNDS)?;
+    }
+    Ok(())
+}
+
+// every short task runs once on every executor, behind the long ones or not
+fn check_scheduler_fairness() -> Result<(), String> {
+    for executor in FairnessExecutor::ALL {
+        for long_tasks in [0, 2] {
+            let latencies = short_task_latencies(executor, 2, long_tasks, 50);
+            expect_count(&format!("short tasks run on {}", executor.label()), latencies.len(), 50)?;
+        }
+    }
+    Ok(())
+}
+
+// every queue hands over every message, including through more messages than the ring
+// holds and batches that don't divide them evenly
+fn check_spsc_vs_mpmc() -> Result<(), String> {
+    let messages = 3 * 1024 + 5;
+    let expected = (0..messages as u64).sum::<u64>();
+    for batch in [1, 7, 256] {
+        for kind in QueueKind::ALL {
+            let (checksum, _) = transfer_messages(kind, messages, batch);
+            expect_count(&format!("summed through {} at batch {}", kind.label(), batch), checksum, expected)?;
+        }
+    }
+    Ok(())
+}
+
+// every sleeper wakes once per round, alone and with others
+fn check_park_unpark() -> Result<(), String> {
+    for sleepers in [1, 3] {
+        expect_count("park/unpark wakeups", park_unpark_latencies(sleepers, 5).len(), sleepers * 5)?;
+        expect_count("notify_all wakeups", parking_lot_notify_latencies(sleepers, 5).len(), sleepers * 5)?;
+    }
+    Ok(())
+}
+
+// every mutex runs every critical section exactly once, from one task and from several
+fn check_async_mutex() -> Result<(), String> {
+    for kind in AsyncLockKind::ALL {
+        for tasks in [1, 5] {
+            let (_, latencies, value) = async_lock_run(kind, tasks, 1000);
+            expect_count(&format!("critical sections under {}", kind.label()), latencies.len(), 1000)?;
+            if value != critical_sections_result(1000) {
+                return Err(format!("{} with {} tasks lost critical sections", kind.label(), tasks));
+            }
+        }
+    }
+    Ok(())
+}
+
+// every style awaits the futures it should, including counts that don't split into
+// whole groups
+fn check_futures_combinator() -> Result<(), String> {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().map_err(|e| e.to_string())?;
+    for style in CombinatorStyle::ALL {
+        for n in [0, 7, 400] {
+            let checksum = runtime.block_on(drive_combinator(style, n));
+            expect_count(&format!("summed over {} futures by {}", n, style.label()), checksum, style.checksum(n))?;
+        }
+    }
+    Ok(())
+}
+
+// every item comes out of the pipeline once, with fewer in flight than items and more
+fn check_stream_pipeline() -> Result<(), String> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    for limit in [1, 8, 512] {
+        let (checksum, latencies) = runtime.block_on(stream_pipeline_run(100, limit));
+        expect_count(&format!("items through buffer_unordered {}", limit), latencies.len(), 100)?;
+        expect_count(&format!("checksum through buffer_unordered {}", limit), checksum, stream_pipeline_checksum(100))?;
+    }
+    Ok(())
+}
+
+// every job runs, with fewer blocking threads than jobs and more
+fn check_blocking_pool() -> Result<(), String> {
+    for max_blocking in [1, 64] {
+        let (delays, _) = blocking_pool_run(max_blocking, 20).map_err(|e| e.to_string())?;
+        expect_count(&format!("jobs run on {} blocking threads", max_blocking), delays.len(), 20)?;
+    }
+    Ok(())
+}
+
+// every io operation completes with its bytes intact, alone and next to the cpu tiles
+fn check_mixed_workload() -> Result<(), String> {
+    for classes in [MixedClasses::IoOnly, MixedClasses::Both] {
+        let (_, latencies) = mixed_workload_run(classes, 2, 2, 3, 4).map_err(|e| e.to_string())?;
+        expect_count("io operations", latencies.len(), 3 * 4)?;
+    }
+    Ok(())
+}
+
+// every style's pass covers each element once, with a length that doesn't split evenly
+fn check_scoped_sharing() -> Result<(), String> {
+    let data = Arc::new((0..1001u64).collect::<Vec<u64>>());
+    let expected = checksum_chunk(&data);
+    for style in SharingStyle::ALL {
+        expect_count(&format!("checksum through {}", style.label()), sharing_pass(style, &data, 3, true), expected)?;
+    }
+    Ok(())
+}
+
+// every flavour counts each thread's bumps, with more threads than cpus
+fn check_thread_local() -> Result<(), String> {
+    for &access in TlsAccess::ALL {
+        let (counted, _) = access.run(4, 1000);
+        expect_count(&format!("bumps through {}", access.label()), counted, 4 * 1000)?;
+    }
+    Ok(())
+}
+
+// both schedulers run every task exactly once, including with more workers than cpus
+fn check_work_stealing() -> Result<(), String> {
+    let costs = skewed_task_costs(500);
+    let expected = costs.iter().fold(0u64, |acc, &cost| acc.wrapping_add(skewed_task_work(cost)));
+    expect_count("summed through work stealing", work_stealing_run(&costs, 4).2, expected)?;
+    expect_count("summed through the shared queue", shared_queue_run(&costs, 4).1, expected)
+}
+
+// every queue delivers every message exactly once, including through more messages
+// than the bounded queues hold
+fn check_mpmc(producers: usize, consumers: usize) -> Result<(), String> {
+    let messages = 3 * MpmcQueue::CAPACITY + 7;
+    let expected = (0..messages as u64).sum::<u64>();
+    for queue in MpmcQueue::ALL {
+        let (received, sum, _) = queue.run(producers, consumers, messages);
+        expect_count(&format!("messages through {}", queue.label()), received, messages)?;
+        expect_count(&format!("summed through {}", queue.label()), sum, expected)?;
+    }
+    Ok(())
+}
+
+// no primitive loses a bump, and the mix of reads and writes is the one asked for
+fn check_lock_contention(write_percent: u32) -> Result<(), String> {
+    let ops = 3 * 4000;
+    for primitive in LockPrimitive::ALL {
+        let (writes, total, _) = primitive.run(3, ops, write_percent);
+        expect_count(&format!("counted through {}", primitive.label()), total, writes)?;
+        let share = writes as f64 * 100.0 / ops as f64;
+        if (share - write_percent as f64).abs() > 3.0 {
+            return Err(format!("{} wrote {:.1}% of the time, expected {}%", primitive.label(), share, write_percent));
+        }
+    }
+    Ok(())
+}
+
+// every spawned task runs once and hands its result back
+fn check_spawn(runtime: SpawnRuntime) -> Result<(), String> {
+    let (sum, _) = spawn_trivial_tasks(runtime, 1000).map_err(|e| e.to_string())?;
+    expect_count("summed by the tasks", sum, 999 * 1000)
+}
+
+// every task runs exactly once, whatever the thread count, including counts that don't
+// divide the tasks evenly
+fn check_partitioning(partitioning: Partitioning) -> Result<(), String> {
+    let costs = rising_task_costs(101);
+    let expected = costs.iter().fold(0u64, |acc, &cost| acc.wrapping_add(skewed_task_work(cost)));
+    for workers in [1, 3, 4] {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(workers).build().map_err(|e| e.to_string())?;
+        expect_count(&format!("checksum on {} threads", workers), partitioning.run(&pool, &costs, workers), expected)?;
+    }
+    Ok(())
+}
+
+// every message, update or permit reaches the consumers, with one worker and with several
+fn check_tokio_sync(primitive: SyncPrimitive) -> Result<(), String> {
+    for workers in [1, 3] {
+        primitive.run(workers, 3, 2, 3000)?;
+    }
+    Ok(())
+}
+
+// no increment is lost at any ordering, and a single thread never retries
+fn check_atomics(op: AtomicOp) -> Result<(), String> {
+    for ordering in AtomicOrdering::ALL {
+        for threads in [1, 3] {
+            let (count, failures, _) = atomic_run(op, ordering, threads, 30_000);
+            expect_count(&format!("counted by {} {} threads", threads, ordering.label()), count, 30_000)?;
+            if threads == 1 {
+                expect_count("compare_exchange retries on one thread", failures, 0)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// no thread gets past either barrier while another is still a phase behind
+fn check_barriers() -> Result<(), String> {
+    for sync in [PhaseSync::Std, PhaseSync::Spin] {
+        for threads in [1, 3] {
+            sync.run(threads, 500).map_err(|e| format!("{}: {}", sync.label(), e))?;
+        }
+    }
+    Ok(())
+}
+
+// every thread's counter ends at its own increments in both layouts
+fn check_false_sharing() -> Result<(), String> {
+    for layout in [CounterLayout::Adjacent, CounterLayout::Padded] {
+        let (counts, _) = false_sharing_run(layout, 3, 20_000);
+        expect_count(&format!("{} counters", layout.label()), counts.len() as u64, 3)?;
+        for count in counts {
+            expect_count(&format!("increments of one {} counter", layout.label()), count, 20_000)?;
+        }
+    }
+    Ok(())
+}
+
+// every request is answered, over the mode's protocol version
+fn check_http_client(mode: HttpClientMode) -> Result<(), String> {
+    let server = MockServer::start().map_err(|e| e.to_string())?;
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    let latencies = runtime.block_on(http_client_latencies(mode, &server.url("/fast"), 20, 4))?;
+    expect_count("requests answered", latencies.len(), 20)
+}
+
+// every round trip of a few clients comes back answered and unchanged
+fn check_echo(protocol: EchoProtocol, size: usize) -> Result<(), String> {
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime.block_on(async {
+        let addr = protocol.serve().await.map_err(|e| e.to_string())?;
+        let run = protocol.clients(addr, size, 3, 30).await.map_err(|e| e.to_string())?;
+        expect_count("round trips answered", run.latencies.len(), 30)?;
+        expect_count("echoes intact", run.intact, 30)
+    })
+}
+
+pub const SELF_CHECKS: &[SelfCheck] = &[
+    SelfCheck {
+        name: "parallel_http",
+        check: || {
+            let server = MockServer::start().map_err(|e| e.to_string())?;
+            let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+            runtime.block_on(async {
+                expect_count("requests answered", parallel_http_requests(&server.url("/fast"), 8).await, 8)?;
+                // the delay holds back the answer, and every request waits it out concurrently
+                let start = Instant::now();
+                expect_count("requests answered", parallel_http_requests(&server.url("/slow?delay_ms=50"), 4).await, 4)?;
+                match start.elapsed() {
+                    waited if waited >= Duration::from_millis(50) && waited < SLOW_DELAY => Ok(()),
+                    waited => Err(format!("4 requests for /slow?delay_ms=50 took {:?}", waited)),
+                }
+            })
+        },
+    },
+    SelfCheck {
+        name: "producer_consumer",
+        check: || expect_count("items processed", producer_consumer_run(2, 500), 1000),
+    },
+    SelfCheck {
+        name: "parallel_math",
+        check: || {
+            let sequence: Vec<u64> = (0..=10).map(fibonacci).collect();
+            if sequence != [0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55] || fibonacci(35) != 9227465 {
+                return Err(format!("fibonacci sequence is off: {:?}", sequence));
+            }
+            let per_item = 9227465 + (0..1000i64).map(|k| k * k).sum::<i64>();
+            expect_count("summed", parallel_math_run(2, 3), 6 * per_item)
+        },
+    },
+    SelfCheck {
+        name: "async_file",
+        check: || {
+            let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+            expect_count("files processed", runtime.block_on(async_file_run(5)), 5)
+        },
+    },
+    SelfCheck {
+        name: "thread_pool",
+        check: || expect_count("tasks completed", thread_pool_run(2, 20), 20),
+    },
+    SelfCheck {
+        name: "blocking_in_async",
+        check: check_blocking_in_async,
+    },
+    SelfCheck {
+        name: "scheduler_fairness",
+        check: check_scheduler_fairness,
+    },
+    SelfCheck {
+        name: "spsc_vs_mpmc",
+        check: check_spsc_vs_mpmc,
+    },
+    SelfCheck {
+        // each mechanism hands the turn back and forth to the end instead of deadlocking
+        name: "ping_pong",
+        check: || {
+            for (_, mechanism) in PING_PONGS {
+                mechanism(200);
+            }
+            Ok(())
+        },
+    },
+    SelfCheck {
+        name: "park_unpark",
+        check: check_park_unpark,
+    },
+    SelfCheck {
+        name: "async_mutex",
+        check: check_async_mutex,
+    },
+    SelfCheck {
+        name: "futures_combinator",
+        check: check_futures_combinator,
+    },
+    SelfCheck {
+        name: "stream_pipeline",
+        check: check_stream_pipeline,
+    },
+    SelfCheck {
+        name: "blocking_pool",
+        check: check_blocking_pool,
+    },
+    SelfCheck {
+        name: "mixed_workload",
+        check: check_mixed_workload,
+    },
+    SelfCheck {
+        // every cancelled task notices its token and every aborted one is cancelled
+        name: "cancellation",
+        check: || cancellation_test(200, 0.25).map(|_| ()),
+    },
+    SelfCheck {
+        name: "scoped_sharing",
+        check: check_scoped_sharing,
+    },
+    SelfCheck {
+        name: "thread_local",
+        check: check_thread_local,
+    },
+    SelfCheck {
+        name: "work_stealing",
+        check: check_work_stealing,
+    },
+    SelfCheck {
+        // the binary re-runs itself as the child and the child exits cleanly
+        name: "process_spawn",
+        check: || spawn_child().map(|_| ()).map_err(|e| e.to_string()),
+    },
+    SelfCheck {
+        name: "mpmc_1to1",
+        check: || check_mpmc(1, 1),
+    },
+    SelfCheck {
+        name: "mpmc_nto1",
+        check: || check_mpmc(3, 1),
+    },
+    SelfCheck {
+        name: "mpmc_ntom",
+        check: || check_mpmc(3, 2),
+    },
+    SelfCheck {
+        name: "tokio_mpsc",
+        check: || check_tokio_sync(SyncPrimitive::Mpsc),
+    },
+    SelfCheck {
+        name: "tokio_broadcast",
+        check: || check_tokio_sync(SyncPrimitive::Broadcast),
+    },
+    SelfCheck {
+        name: "tokio_watch",
+        check: || check_tokio_sync(SyncPrimitive::Watch),
+    },
+    SelfCheck {
+        name: "tokio_notify",
+        check: || check_tokio_sync(SyncPrimitive::Notify),
+    },
+    SelfCheck {
+        name: "locks_read_mostly",
+        check: || check_lock_contention(5),
+    },
+    SelfCheck {
+        name: "locks_balanced",
+        check: || check_lock_contention(50),
+    },
+    SelfCheck {
+        name: "locks_write_only",
+        check: || check_lock_contention(100),
+    },
+    SelfCheck {
+        name: "spawn_current_thread",
+        check: || check_spawn(SpawnRuntime::CurrentThread),
+    },
+    SelfCheck {
+        name: "spawn_multi_thread",
+        check: || check_spawn(SpawnRuntime::MultiThread(2)),
+    },
+    SelfCheck {
+        name: "partition_stealing",
+        check: || check_partitioning(Partitioning::WorkStealing),
+    },
+    SelfCheck {
+        name: "partition_static",
+        check: || check_partitioning(Partitioning::Static),
+    },
+    SelfCheck {
+        name: "thread_pool_cpu",
+        check: || expect_count("tasks completed", thread_pool_run_load(2, 50, PoolLoad::Cpu), 50),
+    },
+    SelfCheck {
+        name: "thread_pool_io",
+        check: || {
+            // 20 waits of 1ms on 4 threads can't finish in under 5ms
+            let start = Instant::now();
+            expect_count("tasks completed", thread_pool_run_load(4, 20, PoolLoad::Io), 20)?;
+            match start.elapsed() {
+                elapsed if elapsed >= 5 * POOL_IO_WAIT => Ok(()),
+                elapsed => Err(format!("20 simulated waits on 4 threads took only {:?}", elapsed)),
+            }
+        },
+    },
+    SelfCheck {
+        name: "atomic_fetch_add",
+        check: || check_atomics(AtomicOp::FetchAdd),
+    },
+    SelfCheck {
+        name: "atomic_compare_exchange",
+        check: || check_atomics(AtomicOp::CompareExchange),
+    },
+    SelfCheck {
+        name: "barrier_phases",
+        check: check_barriers,
+    },
+    SelfCheck {
+        name: "false_sharing",
+        check: check_false_sharing,
+    },
+    SelfCheck {
+        name: "http1_keepalive",
+        check: || check_http_client(HttpClientMode::KeepAlive),
+    },
+    SelfCheck {
+        name: "http1_fresh",
+        check: || check_http_client(HttpClientMode::Fresh),
+    },
+    SelfCheck {
+        name: "http2_multiplexed",
+        check: || check_http_client(HttpClientMode::Multiplexed),
+    },
+    SelfCheck {
+        name: "tcp_echo_small",
+        check: || check_echo(EchoProtocol::Tcp, 64),
+    },
+    SelfCheck {
+        name: "tcp_echo_large",
+        check: || check_echo(EchoProtocol::Tcp, 64 << 10),
+    },
+    SelfCheck {
+        name: "udp_echo",
+        check: || check_echo(EchoProtocol::Udp, 512),
+    },
+    SelfCheck {
+        name: "websocket_echo_small",
+        check: || check_echo(EchoProtocol::WebSocket, 64),
+    },
+    SelfCheck {
+        name: "websocket_echo_large",
+        check: || check_echo(EchoProtocol::WebSocket, 64 << 10),
+    },
+];
+
+pub struct Concurrency;
+
+impl Benchmark for Concurrency {
+    const NAME: &'static str = "concurrency";
+    const SUBTESTS: &'static [SubTest] = SUBTESTS;
+    const SELF_CHECKS: &'static [SelfCheck] = SELF_CHECKS;
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    // the child process_spawn starts, it has to do as little as possible
+    if args.get(1).map(String::as_str) == Some(SPAWN_CHILD_ARG) {
+        return;
+    }
+    if args.iter().any(|a| a == "--self-test") {
+        std::process::exit(if Concurrency::self_test() { 0 } else { 1 });
+    }
+    // unoptimized timings would be reported next to the other languages as rust's
+    if cfg!(debug_assertions) && !args.iter().any(|a| a == "--allow-debug") {
+        eprintln!("This binary was built without optimizations, rebuild it in release mode or pass --allow-debug after the scale");
+        std::process::exit(1);
+    }
+    let config = ScaleConfig::from_args(&args).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+
+    let threads = suite_threads();
+    if let Some(threads) = thread_override() {
+        eprintln!("RUST_BENCH_THREADS set: using {} threads", threads);
+    } else if threads < 4 {
+        eprintln!("Container cpu quota detected: using {} threads", threads);
+    }
+
+    let outcomes = Concurrency::measure(&config);
+
+    // rust-only sub-tests, reported on stderr and kept out of the cross-language total
+    if extended_enabled() {
+        for test in EXTENDED_SUBTESTS {
+            match (test.run)(config.scale) {
+                Ok(ms) => eprintln!("{} [{}]: {:.3} ms", test.name, (test.params)(config.scale), ms),
+                Err(err) => eprintln!("{} failed: {}", test.name, err),
+            }
+        }
+    }
+
+    Concurrency::report(&config, &outcomes);
+    if Concurrency::failures(&outcomes) > 0 {
+        std::process::exit(1);
+    }
+}
@@ -161,11 +161,23 @@ edition = "2021"
 name = "concurrency_bench"
 path = "concurrency.rs"
 
+[features]
+# enables #[thread_local] statics in the tls sub-test, needs a nightly toolchain
+nightly = []
+
 [dependencies]
+bench-core = { path = "../bench-core" }
 tokio = { version = "1.0", features = ["full"] }
 reqwest = { version = "0.11", features = ["json"] }
+hyper = { version = "0.14", features = ["server", "http1", "http2", "tcp"] }
+tokio-tungstenite = "0.24"
 rayon = "1.5"
 tempfile = "3.0"
+crossbeam = "0.8"
+flume = "0.11"
+parking_lot = "0.12"
+futures = "0.3"
+tokio-util = "0.7"
 EOF
 
 echo "All dependencies prepared."
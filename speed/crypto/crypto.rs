@@ -0,0 +1,409 @@
+use std::env;
+use std::hint::black_box;
+
+use aes_gcm::aead::{AeadInPlace, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce, Tag};
+use bench_core::{scaled, Benchmark, InputRng, Rng, ScaleConfig, SelfCheck, SubTest, Timer};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use sha2::{Digest, Sha256};
+
+// debug flag - set via environment variable
+fn debug_enabled() -> bool {
+    env::var("RUST_BENCH_DEBUG").is_ok()
+}
+
+// debug print helper
+macro_rules! debug_print {
+    ($($arg:tt)*) => {
+        if debug_enabled() {
+            eprintln!("[DEBUG] {}", format!($($arg)*));
+        }
+    };
+}
+
+// bytes each sub-test pushes through its primitive per payload size at scale 1
+const HASH_VOLUME: usize = 32 << 20;
+const AEAD_VOLUME: usize = 16 << 20;
+// signing hashes the message twice and does a scalar multiplication, so less of it
+const SIGNATURE_VOLUME: usize = 512 << 10;
+
+const DEFAULT_PAYLOADS: &[usize] = &[64, 4096, 1 << 20];
+
+// fixed keys, the sub-tests measure the primitives and not key generation
+const AES_KEY: [u8; 32] = [0x42; 32];
+const ED25519_SEED: [u8; 32] = [0x24; 32];
+
+// message sizes in bytes, RUST_BENCH_CRYPTO_PAYLOADS=64,1024 overrides the defaults
+fn payload_sizes() -> Vec<usize> {
+    let Ok(list) = env::var("RUST_BENCH_CRYPTO_PAYLOADS") else {
+        return DEFAULT_PAYLOADS.to_vec();
+    };
+    let sizes: Option<Vec<usize>> = list
+        .split(',')
+        .map(|s| s.trim().parse::<usize>().ok().filter(|&size| size > 0))
+        .collect();
+    match sizes {
+        Some(sizes) if !sizes.is_empty() => sizes,
+        _ => {
+            eprintln!("Ignoring RUST_BENCH_CRYPTO_PAYLOADS={}, it isn't a list of byte counts", list);
+            DEFAULT_PAYLOADS.to_vec()
+        }
+    }
+}
+
+fn payload_list() -> String {
+    payload_sizes().iter().map(|size| size.to_string()).collect::<Vec<_>>().join(",")
+}
+
+// deterministic message bytes, the same for every run and every primitive
+fn payload(size: usize) -> Vec<u8> {
+    let mut rng = InputRng::xorshift(42);
+    (0..size).map(|_| rng.next_u64() as u8).collect()
+}
+
+// how many messages of `size` bytes make up `volume`, at least one
+fn messages(volume: usize, size: usize) -> usize {
+    (volume / size).max(1)
+}
+
+// a distinct nonce per message, gcm must never see a key and nonce pair twice
+fn nonce_bytes(index: usize) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..8].copy_from_slice(&(index as u64).to_le_bytes());
+    nonce
+}
+
+fn report_throughput(primitive: &str, size: usize, count: usize, ms: f64) {
+    let mb_per_s = (size * count) as f64 / (1 << 20) as f64 / (ms / 1000.0);
+    debug_print!("{}: {} messages of {} bytes in {:.3}ms ({:.1} MiB/s)", primitive, count, size, ms, mb_per_s);
+}
+
+// sha-256 digests of `volume` bytes per payload size
+fn sha256_test(volume: usize) -> f64 {
+    let mut total_ms = 0.0;
+    for size in payload_sizes() {
+        let data = payload(size);
+        let count = messages(volume, size);
+        let mut acc = 0u8;
+        let timer = Timer::start();
+        for _ in 0..count {
+            acc ^= Sha256::digest(black_box(&data))[0];
+        }
+        let ms = timer.elapsed_ms();
+        black_box(acc);
+        report_throughput("sha256", size, count, ms);
+        total_ms += ms;
+    }
+    total_ms
+}
+
+// blake3 digests of `volume` bytes per payload size
+fn blake3_test(volume: usize) -> f64 {
+    let mut total_ms = 0.0;
+    for size in payload_sizes() {
+        let data = payload(size);
+        let count = messages(volume, size);
+        let mut acc = 0u8;
+        let timer = Timer::start();
+        for _ in 0..count {
+            acc ^= blake3::hash(black_box(&data)).as_bytes()[0];
+        }
+        let ms = timer.elapsed_ms();
+        black_box(acc);
+        report_throughput("blake3", size, count, ms);
+        total_ms += ms;
+    }
+    total_ms
+}
+
+// aes-256-gcm sealing of `volume` bytes per payload size, in place
+fn aes_gcm_encrypt_test(volume: usize) -> Result<f64, String> {
+    let cipher = Aes256Gcm::new_from_slice(&AES_KEY).map_err(|e| e.to_string())?;
+    let mut total_ms = 0.0;
+    for size in payload_sizes() {
+        let count = messages(volume, size);
+        let mut buffer = payload(size);
+        let mut acc = 0u8;
+        let timer = Timer::start();
+        for i in 0..count {
+            let tag = cipher
+                .encrypt_in_place_detached(Nonce::from_slice(&nonce_bytes(i)), b"", &mut buffer)
+                .map_err(|_| "aes-gcm encryption failed".to_string())?;
+            acc ^= tag[0];
+        }
+        let ms = timer.elapsed_ms();
+        black_box(acc);
+        report_throughput("aes_gcm_encrypt", size, count, ms);
+        total_ms += ms;
+    }
+    Ok(total_ms)
+}
+
+// aes-256-gcm opening of `volume` bytes per payload size; the messages are sealed
+// untimed first so every tag checks out
+fn aes_gcm_decrypt_test(volume: usize) -> Result<f64, String> {
+    let cipher = Aes256Gcm::new_from_slice(&AES_KEY).map_err(|e| e.to_string())?;
+    let mut total_ms = 0.0;
+    for size in payload_sizes() {
+        let count = messages(volume, size);
+        let data = payload(size);
+        let mut sealed = Vec::with_capacity(count * size);
+        for _ in 0..count {
+            sealed.extend_from_slice(&data);
+        }
+        let mut tags: Vec<Tag> = Vec::with_capacity(count);
+        for (i, message) in sealed.chunks_mut(size).enumerate() {
+            let tag = cipher
+                .encrypt_in_place_detached(Nonce::from_slice(&nonce_bytes(i)), b"", message)
+                .map_err(|_| "aes-gcm encryption failed".to_string())?;
+            tags.push(tag);
+        }
+
+        let timer = Timer::start();
+        for (i, (message, tag)) in sealed.chunks_mut(size).zip(&tags).enumerate() {
+            cipher
+                .decrypt_in_place_detached(Nonce::from_slice(&nonce_bytes(i)), b"", message, tag)
+                .map_err(|_| format!("aes-gcm rejected message {}", i))?;
+        }
+        let ms = timer.elapsed_ms();
+        black_box(&sealed);
+        report_throughput("aes_gcm_decrypt", size, count, ms);
+        total_ms += ms;
+    }
+    Ok(total_ms)
+}
+
+// ed25519 signatures over `volume` bytes per payload size
+fn ed25519_sign_test(volume: usize) -> f64 {
+    let key = SigningKey::from_bytes(&ED25519_SEED);
+    let mut total_ms = 0.0;
+    for size in payload_sizes() {
+        let data = payload(size);
+        let count = messages(volume, size);
+        let mut acc = 0u8;
+        let timer = Timer::start();
+        for _ in 0..count {
+            acc ^= key.sign(black_box(&data)).to_bytes()[0];
+        }
+        let ms = timer.elapsed_ms();
+        black_box(acc);
+        report_throughput("ed25519_sign", size, count, ms);
+        total_ms += ms;
+    }
+    total_ms
+}
+
+// ed25519 verification over `volume` bytes per payload size, of a signature made untimed
+fn ed25519_verify_test(volume: usize) -> Result<f64, String> {
+    let key = SigningKey::from_bytes(&ED25519_SEED);
+    let verifying_key = key.verifying_key();
+    let mut total_ms = 0.0;
+    for size in payload_sizes() {
+        let data = payload(size);
+        let signature = key.sign(&data);
+        let count = messages(volume, size);
+        let timer = Timer::start();
+        for _ in 0..count {
+            verifying_key
+                .verify(black_box(&data), &signature)
+                .map_err(|e| format!("ed25519 rejected a valid signature: {}", e))?;
+        }
+        let ms = timer.elapsed_ms();
+        report_throughput("ed25519_verify", size, count, ms);
+        total_ms += ms;
+    }
+    Ok(total_ms)
+}
+
+pub const SUBTESTS: &[SubTest] = &[
+    SubTest {
+        name: "sha256",
+        params: |scale| format!("payloads={} volume={}", payload_list(), scaled(HASH_VOLUME, scale)),
+        run: |scale| Ok(sha256_test(scaled(HASH_VOLUME, scale))),
+    },
+    SubTest {
+        name: "blake3",
+        params: |scale| format!("payloads={} volume={}", payload_list(), scaled(HASH_VOLUME, scale)),
+        run: |scale| Ok(blake3_test(scaled(HASH_VOLUME, scale))),
+    },
+    SubTest {
+        name: "aes_gcm_encrypt",
+        params: |scale| format!("payloads={} volume={}", payload_list(), scaled(AEAD_VOLUME, scale)),
+        run: |scale| aes_gcm_encrypt_test(scaled(AEAD_VOLUME, scale)),
+    },
+    SubTest {
+        name: "aes_gcm_decrypt",
+        params: |scale| format!("payloads={} volume={}", payload_list(), scaled(AEAD_VOLUME, scale)),
+        run: |scale| aes_gcm_decrypt_test(scaled(AEAD_VOLUME, scale)),
+    },
+    SubTest {
+        name: "ed25519_sign",
+        params: |scale| format!("payloads={} volume={}", payload_list(), scaled(SIGNATURE_VOLUME, scale)),
+        run: |scale| Ok(ed25519_sign_test(scaled(SIGNATURE_VOLUME, scale))),
+    },
+    SubTest {
+        name: "ed25519_verify",
+        params: |scale| format!("payloads={} volume={}", payload_list(), scaled(SIGNATURE_VOLUME, scale)),
+        run: |scale| ed25519_verify_test(scaled(SIGNATURE_VOLUME, scale)),
+    },
+];
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(text: &str) -> Vec<u8> {
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap()).collect()
+}
+
+fn expect_hex(what: &str, bytes: &[u8], expected: &str) -> Result<(), String> {
+    if hex(bytes) == expected {
+        Ok(())
+    } else {
+        Err(format!("{} is {}, expected {}", what, hex(bytes), expected))
+    }
+}
+
+// published test vectors, so a dependency bump can't quietly change what gets computed
+pub const SELF_CHECKS: &[SelfCheck] = &[
+    SelfCheck {
+        name: "sha256",
+        check: || {
+            // fips 180-2 example "abc"
+            expect_hex(
+                "sha256(\"abc\")",
+                &Sha256::digest(b"abc"),
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            )?;
+            finished(sha256_test(4096))
+        },
+    },
+    SelfCheck {
+        name: "blake3",
+        check: || {
+            expect_hex(
+                "blake3(\"\")",
+                blake3::hash(b"").as_bytes(),
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            )?;
+            finished(blake3_test(4096))
+        },
+    },
+    SelfCheck {
+        name: "aes_gcm_encrypt",
+        check: || {
+            // gcm spec test case 14: zero key, zero iv, one zero block
+            let cipher = Aes256Gcm::new_from_slice(&[0u8; 32]).map_err(|e| e.to_string())?;
+            let mut block = [0u8; 16];
+            let tag = cipher
+                .encrypt_in_place_detached(Nonce::from_slice(&[0u8; 12]), b"", &mut block)
+                .map_err(|_| "aes-gcm encryption failed".to_string())?;
+            expect_hex("ciphertext", &block, "cea7403d4d606b6e074ec5d3baf39d18")?;
+            expect_hex("tag", &tag, "d0d1c8a799996bf0265b98b5d48ab919")?;
+            finished(aes_gcm_encrypt_test(4096)?)
+        },
+    },
+    SelfCheck {
+        name: "aes_gcm_decrypt",
+        check: || {
+            let cipher = Aes256Gcm::new_from_slice(&AES_KEY).map_err(|e| e.to_string())?;
+            let data = payload(100);
+            let mut message = data.clone();
+            let tag = cipher
+                .encrypt_in_place_detached(Nonce::from_slice(&nonce_bytes(7)), b"", &mut message)
+                .map_err(|_| "aes-gcm encryption failed".to_string())?;
+            let mut tampered = message.clone();
+            tampered[0] ^= 1;
+            if cipher.decrypt_in_place_detached(Nonce::from_slice(&nonce_bytes(7)), b"", &mut tampered, &tag).is_ok() {
+                return Err("a tampered ciphertext was accepted".to_string());
+            }
+            cipher
+                .decrypt_in_place_detached(Nonce::from_slice(&nonce_bytes(7)), b"", &mut message, &tag)
+                .map_err(|_| "the round trip was rejected".to_string())?;
+            if message != data {
+                return Err("the round trip changed the message".to_string());
+            }
+            finished(aes_gcm_decrypt_test(4096)?)
+        },
+    },
+    SelfCheck {
+        name: "ed25519_sign",
+        check: || {
+            // rfc 8032 section 7.1, test 1: the empty message
+            let seed: [u8; 32] = unhex("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").try_into().unwrap();
+            let key = SigningKey::from_bytes(&seed);
+            expect_hex(
+                "public key",
+                key.verifying_key().as_bytes(),
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            )?;
+            expect_hex(
+                "signature",
+                &key.sign(b"").to_bytes(),
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            )?;
+            finished(ed25519_sign_test(4096))
+        },
+    },
+    SelfCheck {
+        name: "ed25519_verify",
+        check: || {
+            let key = SigningKey::from_bytes(&ED25519_SEED);
+            let data = payload(100);
+            let signature: Signature = key.sign(&data);
+            let mut altered = data.clone();
+            altered[0] ^= 1;
+            if key.verifying_key().verify(&altered, &signature).is_ok() {
+                return Err("a signature verified for a different message".to_string());
+            }
+            finished(ed25519_verify_test(4096)?)
+        },
+    },
+];
+
+// the kernels have no observable result beyond the vectors above, they only have to
+// finish with a sane timing
+fn finished(ms: f64) -> Result<(), String> {
+    if ms.is_finite() && ms >= 0.0 {
+        Ok(())
+    } else {
+        Err(format!("reported {} ms", ms))
+    }
+}
+
+pub struct Crypto;
+
+impl Benchmark for Crypto {
+    const NAME: &'static str = "crypto";
+    const SUBTESTS: &'static [SubTest] = SUBTESTS;
+    const SELF_CHECKS: &'static [SelfCheck] = SELF_CHECKS;
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--self-test") {
+        std::process::exit(if Crypto::self_test() { 0 } else { 1 });
+    }
+    // unoptimized timings would end up in the results as if they were representative
+    if cfg!(debug_assertions) && !args.iter().any(|a| a == "--allow-debug") {
+        eprintln!("This binary was built without optimizations, rebuild it in release mode or pass --allow-debug after the scale");
+        std::process::exit(1);
+    }
+    let config = ScaleConfig::from_args(&args).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+
+    debug_print!("Scale factor: {}, payloads: {}", config.scale, payload_list());
+
+    let outcomes = Crypto::measure(&config);
+    for (test, outcome) in SUBTESTS.iter().zip(&outcomes) {
+        if let Err(err) = outcome {
+            debug_print!("{} test failed: {}", test.name, err);
+        }
+    }
+    Crypto::report(&config, &outcomes);
+    if Crypto::failures(&outcomes) > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,462 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::env;
+use std::hint::black_box;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use bench_core::{scaled, Benchmark, InputRng, Rng, ScaleConfig, SelfCheck, SubTest, Timer};
+
+// debug flag - set via environment variable
+fn debug_enabled() -> bool {
+    env::var("RUST_BENCH_DEBUG").is_ok()
+}
+
+// debug print helper
+macro_rules! debug_print {
+    ($($arg:tt)*) => {
+        if debug_enabled() {
+            eprintln!("[DEBUG] {}", format!($($arg)*));
+        }
+    };
+}
+
+// graph size at scale 1, RUST_BENCH_GRAPH_NODES and RUST_BENCH_GRAPH_EDGES override them
+const DEFAULT_NODES: usize = 200_000;
+const DEFAULT_EDGES: usize = 1_000_000;
+
+// heaviest edge weight, weights are drawn from 1..=MAX_WEIGHT
+const MAX_WEIGHT: u32 = 100;
+
+// searches started per sub-test, spread over the node ids
+const BFS_SOURCES: usize = 4;
+const DIJKSTRA_SOURCES: usize = 2;
+
+const PAGERANK_ITERATIONS: usize = 20;
+const DAMPING: f64 = 0.85;
+
+fn count_from_env(name: &str, default: usize) -> usize {
+    let Ok(value) = env::var(name) else {
+        return default;
+    };
+    match value.trim().parse::<usize>() {
+        Ok(count) if count > 0 && count <= u32::MAX as usize => count,
+        _ => {
+            eprintln!("Ignoring {}={}, it isn't a positive count", name, value);
+            default
+        }
+    }
+}
+
+// nodes and edges at `scale`, at least two nodes so every sub-test has somewhere to go;
+// the environment is read once so a bad value is only reported once
+fn graph_size(scale: f64) -> (usize, usize) {
+    static BASE: OnceLock<(usize, usize)> = OnceLock::new();
+    let (nodes, edges) = *BASE.get_or_init(|| {
+        (count_from_env("RUST_BENCH_GRAPH_NODES", DEFAULT_NODES), count_from_env("RUST_BENCH_GRAPH_EDGES", DEFAULT_EDGES))
+    });
+    (scaled(nodes, scale).max(2), scaled(edges, scale))
+}
+
+// compressed sparse rows: the out-edges of node u are targets[offsets[u]..offsets[u + 1]],
+// with the matching weights alongside
+pub struct Graph {
+    offsets: Vec<usize>,
+    targets: Vec<u32>,
+    weights: Vec<u32>,
+}
+
+impl Graph {
+    // undirected graphs store every edge once in each direction
+    fn from_edges(nodes: usize, edges: &[(u32, u32, u32)], undirected: bool) -> Graph {
+        let mut offsets = vec![0usize; nodes + 1];
+        for &(from, to, _) in edges {
+            offsets[from as usize + 1] += 1;
+            if undirected {
+                offsets[to as usize + 1] += 1;
+            }
+        }
+        for u in 0..nodes {
+            offsets[u + 1] += offsets[u];
+        }
+        let mut next = offsets.clone();
+        let mut targets = vec![0u32; offsets[nodes]];
+        let mut weights = vec![0u32; offsets[nodes]];
+        let mut place = |from: u32, to: u32, weight: u32| {
+            let slot = &mut next[from as usize];
+            targets[*slot] = to;
+            weights[*slot] = weight;
+            *slot += 1;
+        };
+        for &(from, to, weight) in edges {
+            place(from, to, weight);
+            if undirected {
+                place(to, from, weight);
+            }
+        }
+        Graph { offsets, targets, weights }
+    }
+
+    fn nodes(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn edges(&self, u: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        let range = self.offsets[u]..self.offsets[u + 1];
+        self.targets[range.clone()].iter().map(|&v| v as usize).zip(self.weights[range].iter().copied())
+    }
+}
+
+// uniformly random endpoints, so neighbours are scattered all over memory
+fn random_edges(nodes: usize, edges: usize, seed: u64) -> Vec<(u32, u32, u32)> {
+    let mut rng = InputRng::xorshift(seed);
+    (0..edges)
+        .map(|_| {
+            let from = (rng.next_u64() % nodes as u64) as u32;
+            let to = (rng.next_u64() % nodes as u64) as u32;
+            let weight = (rng.next_u64() % MAX_WEIGHT as u64) as u32 + 1;
+            (from, to, weight)
+        })
+        .collect()
+}
+
+// one random graph in the shapes the sub-tests want: the raw edge list for union-find,
+// undirected rows for the searches and directed rows for pagerank
+pub struct Input {
+    edges: Vec<(u32, u32, u32)>,
+    undirected: Graph,
+    directed: Graph,
+}
+
+// the last input built, with the node and edge counts it was built for
+type CachedInput = ((usize, usize), Arc<Input>);
+
+// building the rows takes longer than some of the sub-tests, so the last size stays
+// cached for the ones that follow
+fn input(nodes: usize, edges: usize) -> Arc<Input> {
+    static CACHE: Mutex<Option<CachedInput>> = Mutex::new(None);
+    let mut cache = CACHE.lock().unwrap();
+    if let Some((cached, input)) = cache.as_ref() {
+        if *cached == (nodes, edges) {
+            return input.clone();
+        }
+    }
+    let list = random_edges(nodes, edges, 42);
+    let input = Arc::new(Input {
+        undirected: Graph::from_edges(nodes, &list, true),
+        directed: Graph::from_edges(nodes, &list, false),
+        edges: list,
+    });
+    *cache = Some(((nodes, edges), input.clone()));
+    input
+}
+
+// hop counts from `source`, u32::MAX where it can't reach
+fn bfs(graph: &Graph, source: usize) -> Vec<u32> {
+    let mut distance = vec![u32::MAX; graph.nodes()];
+    let mut queue = VecDeque::new();
+    distance[source] = 0;
+    queue.push_back(source);
+    while let Some(u) = queue.pop_front() {
+        for (v, _) in graph.edges(u) {
+            if distance[v] == u32::MAX {
+                distance[v] = distance[u] + 1;
+                queue.push_back(v);
+            }
+        }
+    }
+    distance
+}
+
+// weighted distances from `source` with a binary heap and lazy deletion, u64::MAX where
+// it can't reach
+fn dijkstra(graph: &Graph, source: usize) -> Vec<u64> {
+    let mut distance = vec![u64::MAX; graph.nodes()];
+    let mut heap = BinaryHeap::new();
+    distance[source] = 0;
+    heap.push(Reverse((0u64, source)));
+    while let Some(Reverse((d, u))) = heap.pop() {
+        if d > distance[u] {
+            continue;
+        }
+        for (v, weight) in graph.edges(u) {
+            let candidate = d + weight as u64;
+            if candidate < distance[v] {
+                distance[v] = candidate;
+                heap.push(Reverse((candidate, v)));
+            }
+        }
+    }
+    distance
+}
+
+// power iteration pushing each node's rank along its out-edges; nodes without any spread
+// theirs evenly, so the ranks keep summing to 1
+fn pagerank(graph: &Graph, iterations: usize) -> Vec<f64> {
+    let n = graph.nodes();
+    let mut rank = vec![1.0 / n as f64; n];
+    let mut next = vec![0.0; n];
+    for _ in 0..iterations {
+        let mut dangling = 0.0;
+        next.iter_mut().for_each(|r| *r = 0.0);
+        for (u, &own) in rank.iter().enumerate() {
+            let degree = graph.offsets[u + 1] - graph.offsets[u];
+            if degree == 0 {
+                dangling += own;
+                continue;
+            }
+            let share = own / degree as f64;
+            for (v, _) in graph.edges(u) {
+                next[v] += share;
+            }
+        }
+        let base = (1.0 - DAMPING + DAMPING * dangling) / n as f64;
+        for (r, pushed) in rank.iter_mut().zip(&next) {
+            *r = base + DAMPING * pushed;
+        }
+    }
+    rank
+}
+
+// union-find with path halving and union by size, straight off the edge list
+fn connected_components(nodes: usize, edges: &[(u32, u32, u32)]) -> usize {
+    let mut parent: Vec<u32> = (0..nodes as u32).collect();
+    let mut size = vec![1u32; nodes];
+    let find = |parent: &mut [u32], mut x: u32| {
+        while parent[x as usize] != x {
+            parent[x as usize] = parent[parent[x as usize] as usize];
+            x = parent[x as usize];
+        }
+        x
+    };
+    let mut components = nodes;
+    for &(from, to, _) in edges {
+        let (mut a, mut b) = (find(&mut parent, from), find(&mut parent, to));
+        if a == b {
+            continue;
+        }
+        if size[a as usize] < size[b as usize] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        parent[b as usize] = a;
+        size[a as usize] += size[b as usize];
+        components -= 1;
+    }
+    components
+}
+
+// `count` start nodes spread evenly over the ids
+fn sources(nodes: usize, count: usize) -> impl Iterator<Item = usize> {
+    (0..count).map(move |i| i * nodes / count)
+}
+
+fn bfs_test(nodes: usize, edges: usize) -> Result<f64, String> {
+    let input = input(nodes, edges);
+    let timer = Timer::start();
+    let mut reached = 0;
+    for source in sources(nodes, BFS_SOURCES) {
+        reached += bfs(black_box(&input.undirected), source).iter().filter(|&&d| d != u32::MAX).count();
+    }
+    let ms = timer.elapsed_ms();
+    debug_print!("bfs: {} nodes reached from {} sources in {:.3}ms", reached, BFS_SOURCES, ms);
+    black_box(reached);
+    Ok(ms)
+}
+
+fn dijkstra_test(nodes: usize, edges: usize) -> Result<f64, String> {
+    let input = input(nodes, edges);
+    let timer = Timer::start();
+    let mut farthest = 0;
+    for source in sources(nodes, DIJKSTRA_SOURCES) {
+        let distance = dijkstra(black_box(&input.undirected), source);
+        farthest = farthest.max(distance.iter().filter(|&&d| d != u64::MAX).max().copied().unwrap_or(0));
+    }
+    let ms = timer.elapsed_ms();
+    debug_print!("dijkstra: farthest node at {} from {} sources in {:.3}ms", farthest, DIJKSTRA_SOURCES, ms);
+    black_box(farthest);
+    Ok(ms)
+}
+
+fn pagerank_test(nodes: usize, edges: usize) -> Result<f64, String> {
+    let input = input(nodes, edges);
+    let timer = Timer::start();
+    let rank = pagerank(black_box(&input.directed), PAGERANK_ITERATIONS);
+    let ms = timer.elapsed_ms();
+    let top = rank.iter().cloned().fold(0.0, f64::max);
+    debug_print!("pagerank: top rank {:.3e} after {} iterations in {:.3}ms", top, PAGERANK_ITERATIONS, ms);
+    black_box(rank);
+    Ok(ms)
+}
+
+fn components_test(nodes: usize, edges: usize) -> Result<f64, String> {
+    let input = input(nodes, edges);
+    let timer = Timer::start();
+    let components = connected_components(nodes, black_box(&input.edges));
+    let ms = timer.elapsed_ms();
+    debug_print!("connected components: {} in {:.3}ms", components, ms);
+    black_box(components);
+    Ok(ms)
+}
+
+fn params(scale: f64, extra: String) -> String {
+    let (nodes, edges) = graph_size(scale);
+    format!("nodes={} edges={} {}", nodes, edges, extra)
+}
+
+pub const SUBTESTS: &[SubTest] = &[
+    SubTest {
+        name: "bfs",
+        params: |scale| params(scale, format!("sources={}", BFS_SOURCES)),
+        run: |scale| {
+            let (nodes, edges) = graph_size(scale);
+            bfs_test(nodes, edges)
+        },
+    },
+    SubTest {
+        name: "dijkstra",
+        params: |scale| params(scale, format!("sources={} max_weight={}", DIJKSTRA_SOURCES, MAX_WEIGHT)),
+        run: |scale| {
+            let (nodes, edges) = graph_size(scale);
+            dijkstra_test(nodes, edges)
+        },
+    },
+    SubTest {
+        name: "pagerank",
+        params: |scale| params(scale, format!("iterations={} damping={}", PAGERANK_ITERATIONS, DAMPING)),
+        run: |scale| {
+            let (nodes, edges) = graph_size(scale);
+            pagerank_test(nodes, edges)
+        },
+    },
+    SubTest {
+        name: "connected_components",
+        params: |scale| params(scale, "algorithm=union-find".to_string()),
+        run: |scale| {
+            let (nodes, edges) = graph_size(scale);
+            components_test(nodes, edges)
+        },
+    },
+];
+
+// two triangles joined by a heavy edge, a cheaper detour through node 6, and node 7 on
+// its own
+const SMALL_NODES: usize = 8;
+const SMALL_EDGES: &[(u32, u32, u32)] =
+    &[(0, 1, 1), (1, 2, 1), (2, 0, 1), (2, 3, 10), (3, 4, 1), (4, 5, 1), (5, 3, 1), (2, 6, 2), (6, 4, 2)];
+
+fn check_bfs() -> Result<(), String> {
+    let graph = Graph::from_edges(SMALL_NODES, SMALL_EDGES, true);
+    let expected = [0, 1, 1, 2, 3, 3, 2, u32::MAX];
+    let got = bfs(&graph, 0);
+    if got != expected {
+        return Err(format!("hop counts {:?}, expected {:?}", got, expected));
+    }
+    Ok(())
+}
+
+// the hand-worked distances, then a random graph against bellman-ford
+fn check_dijkstra() -> Result<(), String> {
+    let graph = Graph::from_edges(SMALL_NODES, SMALL_EDGES, true);
+    let expected = [0, 1, 1, 6, 5, 6, 3, u64::MAX];
+    let got = dijkstra(&graph, 0);
+    if got != expected {
+        return Err(format!("distances {:?}, expected {:?}", got, expected));
+    }
+    let (nodes, list) = (300, random_edges(300, 900, 7));
+    let mut reference = vec![u64::MAX; nodes];
+    reference[0] = 0;
+    for _ in 0..nodes {
+        for &(a, b, w) in &list {
+            for (from, to) in [(a as usize, b as usize), (b as usize, a as usize)] {
+                if reference[from] != u64::MAX && reference[from] + (w as u64) < reference[to] {
+                    reference[to] = reference[from] + w as u64;
+                }
+            }
+        }
+    }
+    if dijkstra(&Graph::from_edges(nodes, &list, true), 0) != reference {
+        return Err("a random graph disagreed with bellman-ford".to_string());
+    }
+    Ok(())
+}
+
+// a directed cycle ranks every node the same, and ranks always sum to 1
+fn check_pagerank() -> Result<(), String> {
+    let cycle: Vec<(u32, u32, u32)> = (0..5).map(|u| (u, (u + 1) % 5, 1)).collect();
+    let rank = pagerank(&Graph::from_edges(5, &cycle, false), 30);
+    if rank.iter().any(|r| (r - 0.2).abs() > 1e-12) {
+        return Err(format!("a 5-cycle ranked {:?}", rank));
+    }
+    let rank = pagerank(&Graph::from_edges(SMALL_NODES, SMALL_EDGES, false), PAGERANK_ITERATIONS);
+    let sum: f64 = rank.iter().sum();
+    if (sum - 1.0).abs() > 1e-9 {
+        return Err(format!("ranks summed to {}", sum));
+    }
+    Ok(())
+}
+
+// the small graph has two components, and a sparse random graph agrees with bfs
+fn check_components() -> Result<(), String> {
+    let got = connected_components(SMALL_NODES, SMALL_EDGES);
+    if got != 2 {
+        return Err(format!("{} components, expected 2", got));
+    }
+    let (nodes, list) = (1000, random_edges(1000, 600, 9));
+    let graph = Graph::from_edges(nodes, &list, true);
+    let mut seen = vec![false; nodes];
+    let mut expected = 0;
+    for u in 0..nodes {
+        if !seen[u] {
+            expected += 1;
+            for (v, d) in bfs(&graph, u).iter().enumerate() {
+                seen[v] |= *d != u32::MAX;
+            }
+        }
+    }
+    let got = connected_components(nodes, &list);
+    if got != expected {
+        return Err(format!("a random graph had {} components, bfs found {}", got, expected));
+    }
+    Ok(())
+}
+
+pub const SELF_CHECKS: &[SelfCheck] = &[
+    SelfCheck { name: "bfs", check: check_bfs },
+    SelfCheck { name: "dijkstra", check: check_dijkstra },
+    SelfCheck { name: "pagerank", check: check_pagerank },
+    SelfCheck { name: "connected_components", check: check_components },
+];
+
+pub struct Graphs;
+
+impl Benchmark for Graphs {
+    const NAME: &'static str = "graph";
+    const SUBTESTS: &'static [SubTest] = SUBTESTS;
+    const SELF_CHECKS: &'static [SelfCheck] = SELF_CHECKS;
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--self-test") {
+        std::process::exit(if Graphs::self_test() { 0 } else { 1 });
+    }
+    // unoptimized timings would end up in the results as if they were representative
+    if cfg!(debug_assertions) && !args.iter().any(|a| a == "--allow-debug") {
+        eprintln!("This binary was built without optimizations, rebuild it in release mode or pass --allow-debug after the scale");
+        std::process::exit(1);
+    }
+    let config = ScaleConfig::from_args(&args).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+
+    let outcomes = Graphs::measure(&config);
+    for (test, outcome) in SUBTESTS.iter().zip(&outcomes) {
+        if let Err(err) = outcome {
+            debug_print!("{} test failed: {}", test.name, err);
+        }
+    }
+    Graphs::report(&config, &outcomes);
+    if Graphs::failures(&outcomes) > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,92 @@
+#!/bin/bash
+
+SCALE_FACTOR=1 # this controls the node and edge counts of the random graph
+
+# detect if we're running on windows/mingw64
+if [[ "$OSTYPE" == "msys" ]] || [[ "$OSTYPE" == "win32" ]] || [[ "$MINGW_CHOST" ]]; then
+    IS_WINDOWS=true
+    EXE_EXT=".exe"
+    echo "Detected Windows/MINGW64 environment"
+else
+    IS_WINDOWS=false
+    EXE_EXT=""
+    echo "Detected Unix-like environment"
+fi
+
+# only rust so far, the other languages don't have these kernels yet
+echo "Checking if all required tools are installed"
+TOOLS=("rustc" "cargo" "hyperfine")
+all_tools_found=true
+for tool in "${TOOLS[@]}"; do
+  if ! command -v $tool &> /dev/null; then
+    echo "Error: Command not found -> $tool. Please install it first."
+    all_tools_found=false
+  fi
+done
+
+if [ "$all_tools_found" = false ]; then
+    echo "Stopping script because some tools are missing."
+    exit 1
+fi
+echo "All tools found. Let's continue."
+echo ""
+
+echo "Compiling Rust code with Cargo..."
+# create a cargo.toml file to handle dependencies
+CARGO_TOML="Cargo.toml"
+echo "Creating Cargo.toml for Rust dependencies..."
+echo '[package]' > $CARGO_TOML
+echo 'name = "graph_bench"' >> $CARGO_TOML
+echo 'version = "0.1.0"' >> $CARGO_TOML
+echo 'edition = "2021"' >> $CARGO_TOML
+echo '' >> $CARGO_TOML
+echo '[[bin]]' >> $CARGO_TOML
+echo 'name = "graph_bench"' >> $CARGO_TOML
+echo 'path = "graph.rs"' >> $CARGO_TOML
+echo '' >> $CARGO_TOML
+echo '[dependencies]' >> $CARGO_TOML
+echo 'bench-core = { path = "../bench-core" }' >> $CARGO_TOML
+
+cargo build --release --quiet
+if [ $? -ne 0 ]; then echo "Rust compilation failed. Stopping."; exit 1; fi
+cp "target/release/graph_bench${EXE_EXT}" "graph_rust${EXE_EXT}"
+
+echo "Compilation was successful!"
+echo ""
+
+echo "Running the graph algorithms performance test!"
+echo "Algorithms: BFS, Dijkstra, PageRank, connected components"
+echo "Graph at scale 1: ${RUST_BENCH_GRAPH_NODES:-200000} nodes, ${RUST_BENCH_GRAPH_EDGES:-1000000} edges"
+echo "Scale factor ${SCALE_FACTOR} selected"
+echo ""
+
+RUST_CMD="./graph_rust${EXE_EXT} ${SCALE_FACTOR}"
+echo "  Rust: $RUST_CMD"
+echo ""
+
+hyperfine -N --warmup 3 --runs 8 --ignore-failure \
+  --command-name "Rust Graph Benchmark" "$RUST_CMD"
+
+echo ""
+echo "Per sub-test timings:"
+./graph_rust${EXE_EXT} ${SCALE_FACTOR} --output tsv
+
+echo ""
+echo "Cleaning up compiled files..."
+
+# windows-specific cleanup to handle file locking issues
+if [ "$IS_WINDOWS" = true ]; then
+    sleep 1  # give windows time to release file handles
+    cmd //c "del graph_rust.exe *.pdb 2>nul"
+    # fallback to rm if cmd fails
+    rm -f graph_rust.exe *.pdb 2>/dev/null
+else
+    rm -f graph_rust
+fi
+
+# cleanup generated files
+echo "Cleaning up generated files..."
+rm -f Cargo.toml Cargo.lock
+rm -rf target
+
+echo "All done! Thanks for running the graph benchmark!"
@@ -0,0 +1,28 @@
+use std::env;
+use std::process::Command;
+
+// hand the build configuration to the binary, it ends up in every result's metadata
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(name, _)| name.strip_prefix("CARGO_FEATURE_").map(|f| f.to_lowercase().replace('_', "-")))
+        .collect();
+    features.sort();
+    // cargo separates the flags with 0x1f so they can contain spaces
+    let rustflags = env::var("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default().replace('\x1f', " ");
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+
+    println!("cargo:rustc-env=BENCH_BUILD_PROFILE={}", env::var("PROFILE").unwrap_or_default());
+    println!("cargo:rustc-env=BENCH_BUILD_OPT_LEVEL={}", env::var("OPT_LEVEL").unwrap_or_default());
+    println!("cargo:rustc-env=BENCH_BUILD_TARGET={}", env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=BENCH_BUILD_FEATURES={}", features.join(","));
+    println!("cargo:rustc-env=BENCH_BUILD_RUSTFLAGS={}", rustflags);
+    println!("cargo:rustc-env=BENCH_BUILD_RUSTC={}", rustc_version);
+}
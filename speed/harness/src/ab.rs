@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::interrupt;
+use crate::metadata::Metadata;
+use crate::orchestrate;
+use crate::Result;
+
+// two-sided 95% critical values of student's t for 1 to 30 degrees of freedom
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160, 2.145, 2.131,
+    2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+#[derive(Args)]
+pub struct AbArgs {
+    /// Baseline benchmark binary, e.g. a suite binary built from the old commit
+    baseline: PathBuf,
+    /// Candidate benchmark binary, e.g. the same suite built from the new commit
+    candidate: PathBuf,
+    /// Number of baseline/candidate pairs to run
+    #[arg(long, default_value_t = 10)]
+    pairs: usize,
+    /// Scale factor passed to both binaries
+    #[arg(long, default_value_t = 1)]
+    scale: usize,
+    /// Directory both binaries run from, the suite folder when they read data files
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+    /// Also write the pairs and statistics as JSON to this file
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct Pair {
+    baseline_ms: f64,
+    candidate_ms: f64,
+}
+
+// statistics of the per-pair differences, candidate minus baseline
+#[derive(Serialize)]
+struct PairedStats {
+    mean_diff_ms: f64,
+    stddev_diff_ms: f64,
+    ci95_low_ms: f64,
+    ci95_high_ms: f64,
+    change_pct: f64,
+    ci95_low_pct: f64,
+    ci95_high_pct: f64,
+    candidate_slower_pairs: usize,
+    significant: bool,
+}
+
+#[derive(Serialize)]
+struct AbReport {
+    metadata: Metadata,
+    baseline: String,
+    candidate: String,
+    scale: usize,
+    pairs: Vec<Pair>,
+    stats: PairedStats,
+}
+
+fn command_line(binary: &Path, scale: usize) -> Result<String> {
+    let binary = binary
+        .canonicalize()
+        .map_err(|e| format!("cannot find {}: {}", binary.display(), e))?;
+    Ok(format!("{} {}", binary.display(), scale))
+}
+
+fn paired_stats(pairs: &[Pair]) -> PairedStats {
+    let n = pairs.len() as f64;
+    let diffs: Vec<f64> = pairs.iter().map(|p| p.candidate_ms - p.baseline_ms).collect();
+    let mean_diff = diffs.iter().sum::<f64>() / n;
+    let variance = diffs.iter().map(|d| (d - mean_diff).powi(2)).sum::<f64>() / (n - 1.0);
+    let t = T_95.get(pairs.len() - 2).copied().unwrap_or(1.96);
+    let margin = t * variance.sqrt() / n.sqrt();
+    let baseline_mean = pairs.iter().map(|p| p.baseline_ms).sum::<f64>() / n;
+    let pct = |ms: f64| if baseline_mean > 0.0 { ms / baseline_mean * 100.0 } else { 0.0 };
+
+    PairedStats {
+        mean_diff_ms: mean_diff,
+        stddev_diff_ms: variance.sqrt(),
+        ci95_low_ms: mean_diff - margin,
+        ci95_high_ms: mean_diff + margin,
+        change_pct: pct(mean_diff),
+        ci95_low_pct: pct(mean_diff - margin),
+        ci95_high_pct: pct(mean_diff + margin),
+        candidate_slower_pairs: diffs.iter().filter(|&&d| d > 0.0).count(),
+        // the interval not straddling zero is the 95% paired t-test
+        significant: mean_diff - margin > 0.0 || mean_diff + margin < 0.0,
+    }
+}
+
+// run the two binaries alternately and compare them pair by pair; the order flips every
+// pair (A B, B A, A B, ...) so a steady drift in machine speed hits both sides equally
+pub fn run(args: AbArgs) -> Result<()> {
+    if args.pairs < 2 {
+        return Err("--pairs needs at least 2 pairs for a paired comparison".into());
+    }
+    let baseline = command_line(&args.baseline, args.scale)?;
+    let candidate = command_line(&args.candidate, args.scale)?;
+    interrupt::install();
+
+    let mut pairs = Vec::with_capacity(args.pairs);
+    for i in 0..args.pairs {
+        if interrupt::requested() {
+            break;
+        }
+        eprintln!("pair {}/{}", i + 1, args.pairs);
+        let (baseline_ms, candidate_ms) = if i % 2 == 0 {
+            let a = orchestrate::run_once(&baseline, &args.dir).map_err(|e| format!("baseline: {}", e))?;
+            let b = orchestrate::run_once(&candidate, &args.dir).map_err(|e| format!("candidate: {}", e))?;
+            (a, b)
+        } else {
+            let b = orchestrate::run_once(&candidate, &args.dir).map_err(|e| format!("candidate: {}", e))?;
+            let a = orchestrate::run_once(&baseline, &args.dir).map_err(|e| format!("baseline: {}", e))?;
+            (a, b)
+        };
+        pairs.push(Pair { baseline_ms, candidate_ms });
+    }
+    if pairs.len() < 2 {
+        return Err("interrupted before two pairs completed".into());
+    }
+
+    let stats = paired_stats(&pairs);
+    println!("{} vs {} (scale {}, {} pairs)", args.candidate.display(), args.baseline.display(), args.scale, pairs.len());
+    println!();
+    println!("{:>6} {:>14} {:>14} {:>12}", "pair", "baseline", "candidate", "diff");
+    for (i, pair) in pairs.iter().enumerate() {
+        println!(
+            "{:>6} {:>11.3} ms {:>11.3} ms {:>+9.3} ms",
+            i + 1,
+            pair.baseline_ms,
+            pair.candidate_ms,
+            pair.candidate_ms - pair.baseline_ms
+        );
+    }
+    println!();
+    println!(
+        "mean difference {:+.3} ms ({:+.2}%), 95% CI [{:+.3}, {:+.3}] ms ([{:+.2}%, {:+.2}%])",
+        stats.mean_diff_ms,
+        stats.change_pct,
+        stats.ci95_low_ms,
+        stats.ci95_high_ms,
+        stats.ci95_low_pct,
+        stats.ci95_high_pct
+    );
+    println!("candidate slower in {} of {} pairs", stats.candidate_slower_pairs, pairs.len());
+    println!(
+        "{}",
+        match (stats.significant, stats.mean_diff_ms > 0.0) {
+            (true, true) => "candidate is significantly slower",
+            (true, false) => "candidate is significantly faster",
+            (false, _) => "no significant difference",
+        }
+    );
+
+    if let Some(path) = &args.output {
+        let report = AbReport {
+            metadata: Metadata::collect(),
+            baseline: baseline.clone(),
+            candidate: candidate.clone(),
+            scale: args.scale,
+            pairs,
+            stats,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        println!("Results written to {}", path.display());
+    }
+    if interrupt::requested() {
+        println!("Interrupted, the statistics cover the pairs that completed");
+    }
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use std::env;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::doctor;
+use crate::dryrun::{human_time, probe, scaling_exponent, Probe};
+use crate::interrupt;
+use crate::run::remove_scratch_files;
+use crate::suites::{SubTest, Suite};
+use crate::Result;
+
+// probing starts this small and doubles until a probe runs long enough to fit
+const FIRST_PROBE_SCALE: f64 = 0.0625;
+const MIN_PROBE_MS: f64 = 50.0;
+// below this exponent a sub-test doesn't grow with the scale (e.g. reading a fixed file)
+const MIN_EXPONENT: f64 = 0.1;
+const MIN_SCALE: f64 = 0.01;
+const MAX_SCALE: f64 = 1000.0;
+
+// the scale picked for one sub-test and the parameters that works out to
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CalibratedTest {
+    pub suite: String,
+    pub name: String,
+    pub scale: f64,
+    pub params: String,
+    pub estimated_ms: f64,
+}
+
+// what `run --budget` settled on, stored with the results so a run can be reproduced
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Calibration {
+    pub budget_s: f64,
+    pub rounds: usize,
+    pub tests: Vec<CalibratedTest>,
+}
+
+impl Calibration {
+    pub fn scale_of(&self, suite: &str, name: &str) -> Option<f64> {
+        self.tests.iter().find(|t| t.suite == suite && t.name == name).map(|t| t.scale)
+    }
+}
+
+// value = a * scale^k, fitted like the dry run extrapolates
+struct Fit {
+    a: f64,
+    k: f64,
+}
+
+impl Fit {
+    fn through(scale_1: f64, at_1: f64, scale_2: f64, at_2: f64) -> Fit {
+        let (at_1, at_2) = (at_1.max(f64::MIN_POSITIVE), at_2.max(f64::MIN_POSITIVE));
+        let k = scaling_exponent(scale_1, at_1, scale_2, at_2);
+        Fit { a: at_2 / scale_2.powf(k), k }
+    }
+
+    fn at(&self, scale: f64) -> f64 {
+        self.a * scale.powf(self.k)
+    }
+
+    // the scale at which the fitted value reaches `value`
+    fn scale_for(&self, value: f64) -> f64 {
+        (value / self.a).powf(1.0 / self.k)
+    }
+}
+
+struct Probed {
+    time: Fit,
+    // none where peak memory can't be tracked
+    memory: Option<Fit>,
+}
+
+// probes at doubling scales until the last one takes MIN_PROBE_MS, then fits time (and
+// peak memory) through the last two; stops early before a probe could use more than
+// `memory_limit` bytes
+fn fit(test: &SubTest, dir: &Path, memory_limit: Option<u64>) -> std::result::Result<Probed, String> {
+    let mut scale = FIRST_PROBE_SCALE;
+    let mut previous: Option<(f64, Probe)> = None;
+    loop {
+        let probe = probe(test, scale, dir)?;
+        if let Some((last_scale, last)) = previous {
+            let time = Fit::through(last_scale, last.ms, scale, probe.ms);
+            let memory = match (last.peak_memory_bytes, probe.peak_memory_bytes) {
+                (Some(m1), Some(m2)) => Some(Fit::through(last_scale, m1 as f64, scale, m2 as f64)),
+                _ => None,
+            };
+            let next_memory = memory.as_ref().map(|m| m.at(scale * 2.0));
+            let memory_bound = matches!((next_memory, memory_limit), (Some(next), Some(limit)) if next > limit as f64);
+            if probe.ms >= MIN_PROBE_MS || scale * 2.0 > MAX_SCALE || memory_bound {
+                return Ok(Probed { time, memory });
+            }
+        }
+        previous = Some((scale, probe));
+        scale *= 2.0;
+    }
+}
+
+// probe every sub-test, then split each round's share of the budget between them in
+// proportion to their time at scale 1; sub-tests that don't grow with the scale keep
+// scale 1 and their time comes off the top, and no scale may need more than half the
+// memory that's available now
+pub fn calibrate(suites: &[&Suite], root: &Path, budget: Duration, rounds: usize) -> Result<Calibration> {
+    let started = Instant::now();
+    let start_dir = env::current_dir()?;
+    let memory_limit = doctor::available_memory().map(|bytes| bytes / 2);
+    let mut fits = Vec::new();
+    for suite in suites {
+        let dir = start_dir.join(root).join(suite.name);
+        env::set_current_dir(&dir)
+            .map_err(|e| format!("cannot enter suite directory {}: {}", dir.display(), e))?;
+        for test in &suite.subtests {
+            if interrupt::requested() {
+                env::set_current_dir(&start_dir)?;
+                return Err("interrupted while calibrating".into());
+            }
+            eprintln!("calibrating {} {}", suite.name, test.name);
+            match fit(test, &dir, memory_limit) {
+                Ok(fit) => fits.push((*suite, test, fit)),
+                Err(err) => eprintln!("warning: {}.{} failed to calibrate, it runs at scale 1: {}", suite.name, test.name, err),
+            }
+        }
+        remove_scratch_files(suite);
+    }
+    env::set_current_dir(&start_dir)?;
+
+    let round_ms = budget.as_secs_f64() * 1000.0 / rounds as f64;
+    let scales = |p: &Probed| p.time.k >= MIN_EXPONENT;
+    let fixed_ms: f64 = fits.iter().filter(|(_, _, p)| !scales(p)).map(|(_, _, p)| p.time.at(1.0)).sum();
+    let scalable_ms: f64 = fits.iter().filter(|(_, _, p)| scales(p)).map(|(_, _, p)| p.time.at(1.0)).sum();
+    let spare_ms = round_ms - fixed_ms;
+    if spare_ms <= 0.0 {
+        return Err(format!(
+            "a {} budget is too small, the sub-tests that don't scale take {} per round on their own",
+            human_time(Some(round_ms * rounds as f64)),
+            human_time(Some(fixed_ms))
+        )
+        .into());
+    }
+
+    let tests = fits
+        .iter()
+        .map(|(suite, test, probed)| {
+            let mut scale = 1.0;
+            if scales(probed) {
+                let target_ms = spare_ms * probed.time.at(1.0) / scalable_ms;
+                scale = probed.time.scale_for(target_ms);
+                if let (Some(memory), Some(limit)) = (&probed.memory, memory_limit) {
+                    if memory.k >= MIN_EXPONENT {
+                        scale = scale.min(memory.scale_for(limit as f64));
+                    }
+                }
+                scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+            }
+            CalibratedTest {
+                suite: suite.name.to_string(),
+                name: test.name.to_string(),
+                scale,
+                params: (test.params)(scale),
+                estimated_ms: probed.time.at(scale),
+            }
+        })
+        .collect();
+
+    let calibration = Calibration {
+        budget_s: budget.as_secs_f64(),
+        rounds,
+        tests,
+    };
+    print_calibration(&calibration, started.elapsed());
+    Ok(calibration)
+}
+
+fn print_calibration(calibration: &Calibration, took: Duration) {
+    println!(
+        "Calibrated for a {} budget in {:.1} s",
+        human_time(Some(calibration.budget_s * 1000.0)),
+        took.as_secs_f64()
+    );
+    println!("{:<36} {:>8} {:<44} {:>10}", "test", "scale", "parameters", "estimate");
+    let mut total_ms = 0.0;
+    for test in &calibration.tests {
+        total_ms += test.estimated_ms * calibration.rounds as f64;
+        println!(
+            "{:<36} {:>8.3} {:<44} {:>10}",
+            format!("{}.{}", test.suite, test.name),
+            test.scale,
+            test.params,
+            human_time(Some(test.estimated_ms))
+        );
+    }
+    println!("estimated total: {}", human_time(Some(total_ms)));
+    println!();
+}
@@ -0,0 +1,289 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::counters::{Counter, RATIOS};
+use crate::profiles;
+use crate::run::RunReport;
+use crate::store;
+use crate::term::{self, ColorChoice};
+use crate::Result;
+
+// how many entries of each ranked section get printed
+const RANKED_ROWS: usize = 5;
+
+#[derive(Args)]
+pub struct CompareArgs {
+    /// Baseline results: a shipped profile name, a results file, or a run in a history
+    /// file as history.jsonl@N (0 is the oldest, -1 the latest)
+    baseline: String,
+    /// Candidate results, in any of the baseline's forms
+    candidate: String,
+    /// Changes smaller than this (in percent) are not highlighted
+    #[arg(long, default_value_t = 5.0)]
+    threshold: f64,
+    /// Exit with status 1 when any sub-test got slower by more than --threshold
+    #[arg(long)]
+    fail_on_regression: bool,
+    /// Result history (JSON lines) to draw sparklines from
+    #[arg(long)]
+    store: Option<PathBuf>,
+    /// Number of stored runs shown in each sparkline
+    #[arg(long, default_value_t = 20)]
+    last: usize,
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+}
+
+#[derive(Args)]
+pub struct HistoryArgs {
+    /// Result history written by `run --store`
+    store: PathBuf,
+    /// Only show these suites (comma separated)
+    #[arg(long = "suite", value_delimiter = ',')]
+    suites: Vec<String>,
+    /// Number of most recent runs to show
+    #[arg(long, default_value_t = 20)]
+    last: usize,
+    /// Changes smaller than this (in percent) are not highlighted
+    #[arg(long, default_value_t = 5.0)]
+    threshold: f64,
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+}
+
+struct Row {
+    test: String,
+    // ran at another scale in the two runs, shown but not ranked or counted as a regression
+    rescaled: bool,
+    before_ms: f64,
+    after_ms: f64,
+    change_pct: f64,
+    history: Vec<f64>,
+}
+
+// the last `last` timings of one sub-test in the history, oldest first
+fn series(history: &[RunReport], suite: &str, name: &str, scale: f64, last: usize) -> Vec<f64> {
+    let values: Vec<f64> = history
+        .iter()
+        .filter(|r| r.scale_of(suite, name) == scale)
+        .filter_map(|r| r.results.iter().find(|t| t.suite == suite && t.name == name))
+        .filter_map(|t| t.ms)
+        .collect();
+    values[values.len().saturating_sub(last)..].to_vec()
+}
+
+fn print_rows(rows: &[Row], labels: (&str, &str), threshold: f64, color: bool) {
+    let width = rows.iter().map(|r| r.test.len()).max().unwrap_or(4).max(4);
+    println!(
+        "{}",
+        term::bold(
+            &format!("{:<width$} {:>12} {:>12} {:>9}  history", "test", labels.0, labels.1, "change"),
+            color
+        )
+    );
+    for row in rows {
+        println!(
+            "{:<width$} {:>9.3} ms {:>9.3} ms {}  {}{}",
+            row.test,
+            row.before_ms,
+            row.after_ms,
+            term::delta(row.change_pct, threshold, color),
+            term::sparkline(&row.history),
+            if row.rescaled { "  (other scale)" } else { "" }
+        );
+    }
+}
+
+// biggest regressions first, then the biggest improvements
+fn print_ranked(rows: &[Row], threshold: f64, color: bool) {
+    let mut sorted: Vec<&Row> = rows.iter().filter(|r| !r.rescaled).collect();
+    sorted.sort_by(|a, b| b.change_pct.total_cmp(&a.change_pct));
+
+    let regressions: Vec<&&Row> = sorted.iter().filter(|r| r.change_pct > threshold).take(RANKED_ROWS).collect();
+    let improvements: Vec<&&Row> = sorted
+        .iter()
+        .rev()
+        .filter(|r| r.change_pct < -threshold)
+        .take(RANKED_ROWS)
+        .collect();
+
+    println!();
+    println!("{}", term::bold("Biggest regressions", color));
+    if regressions.is_empty() {
+        println!("  none over {:.1}%", threshold);
+    }
+    for row in regressions {
+        println!("  {} {}", term::delta(row.change_pct, threshold, color), row.test);
+    }
+    println!("{}", term::bold("Biggest improvements", color));
+    if improvements.is_empty() {
+        println!("  none over {:.1}%", threshold);
+    }
+    for row in improvements {
+        println!("  {} {}", term::delta(row.change_pct, threshold, color), row.test);
+    }
+}
+
+// the hardware counts behind the sub-tests that changed beyond the threshold, when both
+// runs recorded them with --counters, so a regression can be told apart as executing more
+// instructions or stalling on the same ones
+fn print_counter_changes(baseline: &RunReport, candidate: &RunReport, rows: &[Row], threshold: f64, color: bool) {
+    let mut lines = Vec::new();
+    for row in rows.iter().filter(|r| r.change_pct.abs() > threshold) {
+        let find = |report: &RunReport| {
+            report
+                .results
+                .iter()
+                .find(|r| format!("{}.{}", r.suite, r.name) == row.test)
+                .map(|r| r.counters.clone())
+                .unwrap_or_default()
+        };
+        let (before, after) = (find(baseline), find(candidate));
+        let shared: Vec<Counter> = after.keys().copied().filter(|c| before.contains_key(c)).collect();
+        if shared.is_empty() {
+            continue;
+        }
+        let mut line = format!("  {} {:<36}", term::delta(row.change_pct, threshold, color), row.test);
+        for counter in &shared {
+            let (b, a) = (before[counter] as f64, after[counter] as f64);
+            if b > 0.0 {
+                line.push_str(&format!("  {} {:+.1}%", counter.name(), (a - b) / b * 100.0));
+            }
+        }
+        for ratio in RATIOS.iter().filter(|r| r.available(&shared)) {
+            if let (Some(b), Some(a)) = (ratio.of(&before), ratio.of(&after)) {
+                line.push_str(&format!("  {} {:.2} -> {:.2}", ratio.name, b, a));
+            }
+        }
+        lines.push(line);
+    }
+    if !lines.is_empty() {
+        println!();
+        println!("{}", term::bold("Hardware counters of the changed sub-tests", color));
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+}
+
+pub fn compare(args: CompareArgs) -> Result<()> {
+    let baseline = profiles::load(&args.baseline)?;
+    let candidate = profiles::load(&args.candidate)?;
+    let history = match &args.store {
+        Some(path) => store::load(path)?,
+        None => Vec::new(),
+    };
+    for (label, report) in [(&args.baseline, &baseline), (&args.candidate, &candidate)] {
+        if report.metadata.build.unoptimized() {
+            eprintln!("warning: {} comes from an unoptimized build", label);
+        }
+    }
+    let per_test = |r: &RunReport| r.calibration.is_some() || !r.overrides.is_empty();
+    if per_test(&baseline) || per_test(&candidate) {
+        let differing: Vec<String> = candidate
+            .results
+            .iter()
+            .filter(|r| candidate.scale_of(&r.suite, &r.name) != baseline.scale_of(&r.suite, &r.name))
+            .map(|r| format!("{}.{}", r.suite, r.name))
+            .collect();
+        if !differing.is_empty() {
+            eprintln!(
+                "warning: {} ran at different --budget or --set scales in the two runs, their deltas aren't ranked or counted as regressions",
+                differing.join(", ")
+            );
+        }
+    } else if baseline.scale != candidate.scale {
+        eprintln!(
+            "warning: comparing scale {} against scale {}, the deltas aren't ranked or counted as regressions",
+            candidate.scale, baseline.scale
+        );
+    }
+    if baseline.rng != candidate.rng {
+        let name = |r: &RunReport| r.rng.clone().unwrap_or_else(|| "native".to_string());
+        eprintln!(
+            "warning: the runs drew their inputs from different generators ({} against {}), the deltas partly reflect that",
+            name(&candidate),
+            name(&baseline)
+        );
+    }
+
+    let mut rows = Vec::new();
+    let mut unmatched = Vec::new();
+    for result in &candidate.results {
+        let before = baseline
+            .results
+            .iter()
+            .find(|b| b.suite == result.suite && b.name == result.name)
+            .and_then(|b| b.ms);
+        match (before, result.ms) {
+            (Some(before_ms), Some(after_ms)) if before_ms > 0.0 => rows.push(Row {
+                test: format!("{}.{}", result.suite, result.name),
+                rescaled: candidate.scale_of(&result.suite, &result.name) != baseline.scale_of(&result.suite, &result.name),
+                before_ms,
+                after_ms,
+                change_pct: (after_ms - before_ms) / before_ms * 100.0,
+                history: series(&history, &result.suite, &result.name, candidate.scale_of(&result.suite, &result.name), args.last),
+            }),
+            _ => unmatched.push(format!("{}.{}", result.suite, result.name)),
+        }
+    }
+
+    let color = args.color.enabled();
+    println!("{} vs {}", args.candidate, args.baseline);
+    println!();
+    print_rows(&rows, ("baseline", "candidate"), args.threshold, color);
+    if !unmatched.is_empty() {
+        println!("not compared: {}", unmatched.join(", "));
+    }
+    print_ranked(&rows, args.threshold, color);
+    print_counter_changes(&baseline, &candidate, &rows, args.threshold, color);
+
+    let regressed = rows.iter().filter(|r| !r.rescaled && r.change_pct > args.threshold).count();
+    if args.fail_on_regression && regressed > 0 {
+        return Err(format!("{} sub-test(s) regressed by more than {:.1}%", regressed, args.threshold).into());
+    }
+    Ok(())
+}
+
+pub fn history(args: HistoryArgs) -> Result<()> {
+    let history = store::load(&args.store)?;
+    let latest = history.last().ok_or("the result history is empty")?;
+
+    // compare the latest run with the one before it at the same scale
+    let previous = history[..history.len() - 1].iter().rev().find(|r| r.scale == latest.scale);
+    let scales_differ = |suite: &str, name: &str| previous.is_some_and(|p| p.scale_of(suite, name) != latest.scale_of(suite, name));
+    let mut rows = Vec::new();
+    for result in &latest.results {
+        if !args.suites.is_empty() && !args.suites.contains(&result.suite) {
+            continue;
+        }
+        let Some(after_ms) = result.ms else { continue };
+        let before_ms = previous
+            .and_then(|p| p.results.iter().find(|t| t.suite == result.suite && t.name == result.name))
+            .and_then(|t| t.ms)
+            .unwrap_or(after_ms);
+        let change_pct = if before_ms > 0.0 { (after_ms - before_ms) / before_ms * 100.0 } else { 0.0 };
+        rows.push(Row {
+            test: format!("{}.{}", result.suite, result.name),
+            rescaled: scales_differ(&result.suite, &result.name),
+            before_ms,
+            after_ms,
+            change_pct,
+            history: series(&history, &result.suite, &result.name, latest.scale_of(&result.suite, &result.name), args.last),
+        });
+    }
+
+    let color = args.color.enabled();
+    println!(
+        "{} runs in {}, latest on {} (scale {})",
+        history.len(),
+        args.store.display(),
+        latest.metadata.hostname,
+        latest.scale
+    );
+    println!();
+    print_rows(&rows, ("previous", "latest"), args.threshold, color);
+    print_ranked(&rows, args.threshold, color);
+    Ok(())
+}
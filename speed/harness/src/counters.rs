@@ -0,0 +1,420 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+// hardware events a run can ask for; every backend supplies what its platform exposes
+// and names the rest as gaps instead of reporting zeros
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Counter {
+    Cycles,
+    Instructions,
+    CacheMisses,
+    BranchMisses,
+}
+
+impl Counter {
+    pub fn name(self) -> &'static str {
+        match self {
+            Counter::Cycles => "cycles",
+            Counter::Instructions => "instructions",
+            Counter::CacheMisses => "cache-misses",
+            Counter::BranchMisses => "branch-misses",
+        }
+    }
+}
+
+// a count relative to another, which says more than either alone: a sub-test that got
+// slower at the same instruction count with a lower ipc is stalling, on cache misses when
+// its mpki went up with it
+pub struct Ratio {
+    pub name: &'static str,
+    numerator: Counter,
+    denominator: Counter,
+    factor: f64,
+}
+
+pub const RATIOS: &[Ratio] = &[
+    Ratio {
+        name: "ipc",
+        numerator: Counter::Instructions,
+        denominator: Counter::Cycles,
+        factor: 1.0,
+    },
+    Ratio {
+        name: "cache-mpki",
+        numerator: Counter::CacheMisses,
+        denominator: Counter::Instructions,
+        factor: 1000.0,
+    },
+    Ratio {
+        name: "branch-mpki",
+        numerator: Counter::BranchMisses,
+        denominator: Counter::Instructions,
+        factor: 1000.0,
+    },
+];
+
+impl Ratio {
+    // whether both counts are among `counters`
+    pub fn available(&self, counters: &[Counter]) -> bool {
+        counters.contains(&self.numerator) && counters.contains(&self.denominator)
+    }
+
+    pub fn of(&self, counts: &BTreeMap<Counter, u64>) -> Option<f64> {
+        let numerator = *counts.get(&self.numerator)?;
+        let denominator = *counts.get(&self.denominator)?;
+        (denominator > 0).then(|| numerator as f64 / denominator as f64 * self.factor)
+    }
+}
+
+// counts the requested events around one stretch of work on the calling thread
+pub trait Counters {
+    // the platform facility the counts come from
+    fn backend(&self) -> &'static str;
+    // requested counters this backend can't supply, with the reason
+    fn gaps(&self) -> &BTreeMap<Counter, String>;
+    fn start(&mut self);
+    // counts since start, for every requested counter that isn't a gap
+    fn stop(&mut self) -> BTreeMap<Counter, u64>;
+}
+
+// the best backend for this platform, opened for the requested counters
+#[cfg(all(feature = "perf-counters", target_os = "linux"))]
+pub fn open(requested: &[Counter]) -> Box<dyn Counters> {
+    Box::new(perf::PerfEvents::open(requested))
+}
+
+#[cfg(all(feature = "perf-counters", target_os = "macos"))]
+pub fn open(requested: &[Counter]) -> Box<dyn Counters> {
+    Box::new(rusage::ProcessRusage::open(requested))
+}
+
+#[cfg(all(feature = "perf-counters", windows))]
+pub fn open(requested: &[Counter]) -> Box<dyn Counters> {
+    Box::new(thread_cycles::ThreadCycles::open(requested))
+}
+
+#[cfg(not(all(feature = "perf-counters", any(target_os = "linux", target_os = "macos", windows))))]
+pub fn open(requested: &[Counter]) -> Box<dyn Counters> {
+    let reason = if cfg!(feature = "perf-counters") {
+        "no hardware counter backend for this platform"
+    } else {
+        "this build has no `perf-counters` feature"
+    };
+    Box::new(Unsupported::open(requested, reason))
+}
+
+// every requested counter the platform has no source for, with the same reason
+#[cfg(not(all(feature = "perf-counters", target_os = "linux")))]
+fn gaps_for(requested: &[Counter], supplied: &[Counter], reason: &str) -> BTreeMap<Counter, String> {
+    requested
+        .iter()
+        .filter(|c| !supplied.contains(c))
+        .map(|c| (*c, reason.to_string()))
+        .collect()
+}
+
+#[cfg(not(all(feature = "perf-counters", any(target_os = "linux", target_os = "macos", windows))))]
+struct Unsupported {
+    gaps: BTreeMap<Counter, String>,
+}
+
+#[cfg(not(all(feature = "perf-counters", any(target_os = "linux", target_os = "macos", windows))))]
+impl Unsupported {
+    fn open(requested: &[Counter], reason: &str) -> Self {
+        Unsupported {
+            gaps: gaps_for(requested, &[], reason),
+        }
+    }
+}
+
+#[cfg(not(all(feature = "perf-counters", any(target_os = "linux", target_os = "macos", windows))))]
+impl Counters for Unsupported {
+    fn backend(&self) -> &'static str {
+        "none"
+    }
+
+    fn gaps(&self) -> &BTreeMap<Counter, String> {
+        &self.gaps
+    }
+
+    fn start(&mut self) {}
+
+    fn stop(&mut self) -> BTreeMap<Counter, u64> {
+        BTreeMap::new()
+    }
+}
+
+// linux: one perf_event fd per counter, user space only so perf_event_paranoid 2 is
+// enough, inherited by threads the sub-test starts (they're counted once they exit)
+#[cfg(all(feature = "perf-counters", target_os = "linux"))]
+mod perf {
+    use std::collections::BTreeMap;
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    use super::{Counter, Counters};
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_FORMAT_TOTAL_TIME_ENABLED: u64 = 1;
+    const PERF_FORMAT_TOTAL_TIME_RUNNING: u64 = 2;
+    // disabled, inherit, exclude_kernel and exclude_hv in the attr bitfield
+    const FLAGS: u64 = 1 | 1 << 1 | 1 << 5 | 1 << 6;
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 8;
+    const PERF_EVENT_IOC_ENABLE: u64 = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: u64 = 0x2401;
+    const PERF_EVENT_IOC_RESET: u64 = 0x2403;
+
+    // struct perf_event_attr up to PERF_ATTR_SIZE_VER7, the fields after flags stay zero
+    #[repr(C)]
+    struct PerfEventAttr {
+        kind: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        rest: [u64; 10],
+    }
+
+    fn config(counter: Counter) -> u64 {
+        match counter {
+            Counter::Cycles => 0,
+            Counter::Instructions => 1,
+            Counter::CacheMisses => 3,
+            Counter::BranchMisses => 5,
+        }
+    }
+
+    pub struct PerfEvents {
+        events: Vec<(Counter, File)>,
+        gaps: BTreeMap<Counter, String>,
+        // enabled and running times of every event when it was last started
+        started: Vec<[u64; 3]>,
+    }
+
+    impl PerfEvents {
+        pub fn open(requested: &[Counter]) -> Self {
+            let mut events = Vec::new();
+            let mut gaps = BTreeMap::new();
+            for &counter in requested {
+                let attr = PerfEventAttr {
+                    kind: PERF_TYPE_HARDWARE,
+                    size: std::mem::size_of::<PerfEventAttr>() as u32,
+                    config: config(counter),
+                    sample_period: 0,
+                    sample_type: 0,
+                    read_format: PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_TOTAL_TIME_RUNNING,
+                    flags: FLAGS,
+                    rest: [0; 10],
+                };
+                let fd = unsafe {
+                    libc::syscall(
+                        libc::SYS_perf_event_open,
+                        &attr as *const PerfEventAttr,
+                        0 as libc::pid_t,
+                        -1 as libc::c_int,
+                        -1 as libc::c_int,
+                        PERF_FLAG_FD_CLOEXEC,
+                    )
+                };
+                if fd < 0 {
+                    let err = io::Error::last_os_error();
+                    let reason = match err.raw_os_error() {
+                        Some(libc::ENOENT) | Some(libc::EOPNOTSUPP) => {
+                            "not exposed by this cpu or hypervisor".to_string()
+                        }
+                        Some(libc::EACCES) | Some(libc::EPERM) => {
+                            "not permitted, perf_event_paranoid must be 2 or lower".to_string()
+                        }
+                        _ => format!("perf_event_open failed: {}", err),
+                    };
+                    gaps.insert(counter, reason);
+                } else {
+                    events.push((counter, unsafe { File::from_raw_fd(fd as i32) }));
+                }
+            }
+            PerfEvents { events, gaps, started: Vec::new() }
+        }
+    }
+
+    fn ioctl(file: &File, request: u64) {
+        unsafe {
+            libc::ioctl(file.as_raw_fd(), request as _, 0);
+        }
+    }
+
+    // (count, time enabled, time running); reset only clears the count, the times keep adding up
+    fn read_raw(mut file: &File) -> Option<[u64; 3]> {
+        let mut buf = [0u8; 24];
+        file.read_exact(&mut buf).ok()?;
+        Some([0, 1, 2].map(|i| u64::from_ne_bytes(buf[i * 8..i * 8 + 8].try_into().unwrap())))
+    }
+
+    impl Counters for PerfEvents {
+        fn backend(&self) -> &'static str {
+            "perf_event"
+        }
+
+        fn gaps(&self) -> &BTreeMap<Counter, String> {
+            &self.gaps
+        }
+
+        fn start(&mut self) {
+            self.started.clear();
+            for (_, file) in &self.events {
+                ioctl(file, PERF_EVENT_IOC_RESET);
+                self.started.push(read_raw(file).unwrap_or_default());
+            }
+            for (_, file) in &self.events {
+                ioctl(file, PERF_EVENT_IOC_ENABLE);
+            }
+        }
+
+        // counts scaled up by enabled / running when the kernel had to multiplex them
+        fn stop(&mut self) -> BTreeMap<Counter, u64> {
+            for (_, file) in &self.events {
+                ioctl(file, PERF_EVENT_IOC_DISABLE);
+            }
+            self.events
+                .iter()
+                .zip(&self.started)
+                .filter_map(|((counter, file), started)| {
+                    let [value, enabled, running] = read_raw(file)?;
+                    let (enabled, running) = (enabled - started[1], running - started[2]);
+                    (running > 0).then(|| (*counter, (value as f64 * enabled as f64 / running as f64) as u64))
+                })
+                .collect()
+        }
+    }
+}
+
+// macos: the per-process cycle and instruction totals of proc_pid_rusage, which need
+// no privileges; cache and branch misses are only exposed through kperf, which needs root
+#[cfg(all(feature = "perf-counters", target_os = "macos"))]
+mod rusage {
+    use std::collections::BTreeMap;
+
+    use super::{gaps_for, Counter, Counters};
+
+    const SUPPLIED: &[Counter] = &[Counter::Cycles, Counter::Instructions];
+
+    pub struct ProcessRusage {
+        requested: Vec<Counter>,
+        gaps: BTreeMap<Counter, String>,
+        start: (u64, u64),
+    }
+
+    // (cycles, instructions) of this whole process so far
+    fn read() -> Option<(u64, u64)> {
+        let mut info: libc::rusage_info_v4 = unsafe { std::mem::zeroed() };
+        let status = unsafe {
+            libc::proc_pid_rusage(
+                libc::getpid(),
+                libc::RUSAGE_INFO_V4,
+                &mut info as *mut libc::rusage_info_v4 as *mut libc::rusage_info_t,
+            )
+        };
+        (status == 0).then_some((info.ri_cycles, info.ri_instructions))
+    }
+
+    impl ProcessRusage {
+        pub fn open(requested: &[Counter]) -> Self {
+            ProcessRusage {
+                requested: requested.iter().copied().filter(|c| SUPPLIED.contains(c)).collect(),
+                gaps: gaps_for(requested, SUPPLIED, "only available through kperf, which needs root"),
+                start: (0, 0),
+            }
+        }
+    }
+
+    impl Counters for ProcessRusage {
+        fn backend(&self) -> &'static str {
+            "proc_pid_rusage"
+        }
+
+        fn gaps(&self) -> &BTreeMap<Counter, String> {
+            &self.gaps
+        }
+
+        fn start(&mut self) {
+            self.start = read().unwrap_or_default();
+        }
+
+        fn stop(&mut self) -> BTreeMap<Counter, u64> {
+            let Some((cycles, instructions)) = read() else {
+                return BTreeMap::new();
+            };
+            self.requested
+                .iter()
+                .map(|&counter| match counter {
+                    Counter::Instructions => (counter, instructions.saturating_sub(self.start.1)),
+                    _ => (counter, cycles.saturating_sub(self.start.0)),
+                })
+                .collect()
+        }
+    }
+}
+
+// windows: QueryThreadCycleTime counts the calling thread's cycles without privileges;
+// the other events need an ETW session with PMC sources, which needs administrator rights
+#[cfg(all(feature = "perf-counters", windows))]
+mod thread_cycles {
+    use std::collections::BTreeMap;
+    use std::ffi::c_void;
+
+    use super::{gaps_for, Counter, Counters};
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentThread() -> *mut c_void;
+        fn QueryThreadCycleTime(thread: *mut c_void, cycles: *mut u64) -> i32;
+    }
+
+    fn read() -> Option<u64> {
+        let mut cycles = 0;
+        let ok = unsafe { QueryThreadCycleTime(GetCurrentThread(), &mut cycles) };
+        (ok != 0).then_some(cycles)
+    }
+
+    pub struct ThreadCycles {
+        cycles: bool,
+        gaps: BTreeMap<Counter, String>,
+        start: u64,
+    }
+
+    impl ThreadCycles {
+        pub fn open(requested: &[Counter]) -> Self {
+            ThreadCycles {
+                cycles: requested.contains(&Counter::Cycles),
+                gaps: gaps_for(requested, &[Counter::Cycles], "needs an ETW session with PMC sources (administrator)"),
+                start: 0,
+            }
+        }
+    }
+
+    impl Counters for ThreadCycles {
+        fn backend(&self) -> &'static str {
+            "QueryThreadCycleTime"
+        }
+
+        fn gaps(&self) -> &BTreeMap<Counter, String> {
+            &self.gaps
+        }
+
+        fn start(&mut self) {
+            self.start = read().unwrap_or_default();
+        }
+
+        fn stop(&mut self) -> BTreeMap<Counter, u64> {
+            match read() {
+                Some(cycles) if self.cycles => BTreeMap::from([(Counter::Cycles, cycles.saturating_sub(self.start))]),
+                _ => BTreeMap::new(),
+            }
+        }
+    }
+}
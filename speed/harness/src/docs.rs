@@ -0,0 +1,56 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+
+use crate::{Cli, Result};
+
+#[derive(Args)]
+pub struct CompletionsArgs {
+    /// Shell to generate the completion script for
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+#[derive(Args)]
+pub struct ManpageArgs {
+    /// Write benchmark.1 plus one page per subcommand into this directory instead of
+    /// printing the main page
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+}
+
+// e.g. `benchmark completions bash > /etc/bash_completion.d/benchmark`
+pub fn completions(args: CompletionsArgs) -> Result<()> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(args.shell, &mut command, name, &mut io::stdout());
+    Ok(())
+}
+
+pub fn manpage(args: ManpageArgs) -> Result<()> {
+    let command = Cli::command();
+    let Some(dir) = args.out_dir else {
+        clap_mangen::Man::new(command).render(&mut io::stdout())?;
+        return Ok(());
+    };
+
+    fs::create_dir_all(&dir)?;
+    let name = command.get_name().to_string();
+    let mut pages = vec![(name.clone(), command.clone())];
+    // hidden subcommands like `worker` are internal, they get no page
+    for sub in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        // subcommand pages are named like git's, benchmark-run.1 and so on
+        pages.push((format!("{}-{}", name, sub.get_name()), sub.clone().bin_name(format!("{} {}", name, sub.get_name()))));
+    }
+    for (page_name, page) in pages {
+        let file = format!("{}.1", page_name);
+        let mut buffer = Vec::new();
+        clap_mangen::Man::new(page).title(page_name).render(&mut buffer)?;
+        fs::write(dir.join(&file), buffer)?;
+        println!("{}", dir.join(&file).display());
+    }
+    Ok(())
+}
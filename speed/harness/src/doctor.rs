@@ -0,0 +1,272 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use crate::counters::{self, Counter};
+use crate::run;
+use crate::suites::{self, Suite};
+use crate::Result;
+
+// the io write tests produce roughly 8 MiB per scale step, doubled for headroom
+const SCRATCH_BYTES_PER_SCALE: u64 = 16 << 20;
+// memory_intensive_test holds two 100 MiB buffers per scale step
+const MEMORY_BYTES_PER_SCALE: u64 = 200 << 20;
+// below this, mlock-based experiments can't pin meaningful buffers
+const MIN_MEMLOCK_BYTES: u64 = 64 << 20;
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Only check what these suites need (comma separated, defaults to all of them)
+    #[arg(long = "suite", value_delimiter = ',')]
+    suites: Vec<String>,
+    /// Directory holding the suite folders
+    #[arg(long, default_value = ".")]
+    root: PathBuf,
+    /// Scale factor the run will use, for the space checks
+    #[arg(long, default_value_t = 1.0, value_parser = run::parse_scale)]
+    scale: f64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Pass => "PASS",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+            Status::Skip => "SKIP",
+        }
+    }
+}
+
+pub struct Check {
+    pub status: Status,
+    pub name: String,
+    pub detail: String,
+}
+
+fn check(status: Status, name: impl Into<String>, detail: impl Into<String>) -> Check {
+    Check {
+        status,
+        name: name.into(),
+        detail: detail.into(),
+    }
+}
+
+fn mib(bytes: u64) -> u64 {
+    bytes >> 20
+}
+
+// the libc field types differ between platforms, hence the casts
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn free_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    Some(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn memlock_limit() -> Option<u64> {
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    if unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) } != 0 {
+        return None;
+    }
+    Some(limit.rlim_cur as u64)
+}
+
+#[cfg(not(unix))]
+fn memlock_limit() -> Option<u64> {
+    None
+}
+
+pub(crate) fn available_memory() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+fn space_check(name: &str, dir: &Path, needed: u64) -> Check {
+    match free_space(dir) {
+        Some(free) if free >= needed => check(
+            Status::Pass,
+            name,
+            format!("{} MiB free in {}, about {} MiB needed", mib(free), dir.display(), mib(needed)),
+        ),
+        Some(free) => check(
+            Status::Fail,
+            name,
+            format!("only {} MiB free in {}, about {} MiB needed", mib(free), dir.display(), mib(needed)),
+        ),
+        None => check(Status::Skip, name, "free space can't be read on this platform"),
+    }
+}
+
+// which of the --counters events this platform can supply, and why the others can't be
+fn counters_check() -> Check {
+    let all = [Counter::Cycles, Counter::Instructions, Counter::CacheMisses, Counter::BranchMisses];
+    let counters = counters::open(&all);
+    let gaps = counters.gaps();
+    let supplied: Vec<&str> = all.iter().filter(|c| !gaps.contains_key(c)).map(|c| c.name()).collect();
+    if gaps.is_empty() {
+        return check(Status::Pass, "hardware counters", format!("{} via {}", supplied.join(", "), counters.backend()));
+    }
+    // counters usually fail for the same reason, so list each reason once
+    let mut reasons: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (counter, reason) in gaps {
+        match reasons.iter_mut().find(|(r, _)| r == reason) {
+            Some((_, names)) => names.push(counter.name()),
+            None => reasons.push((reason, vec![counter.name()])),
+        }
+    }
+    let missing: Vec<String> = reasons.iter().map(|(reason, names)| format!("{} ({})", names.join(", "), reason)).collect();
+    let detail = if supplied.is_empty() {
+        format!("none via {}: {}", counters.backend(), missing.join(", "))
+    } else {
+        format!("{} via {}, missing {}", supplied.join(", "), counters.backend(), missing.join(", "))
+    };
+    check(Status::Warn, "hardware counters", detail)
+}
+
+// everything the selected suites need before any timing starts
+pub fn preflight(suites: &[&Suite], root: &Path, scale: f64) -> Vec<Check> {
+    let mut checks = Vec::new();
+    // bytes needed at this scale, from a per scale step figure
+    let at_scale = |bytes: u64| (bytes as f64 * scale).ceil() as u64;
+
+    for suite in suites {
+        let dir = root.join(suite.name);
+        if !dir.is_dir() {
+            checks.push(check(
+                Status::Fail,
+                format!("{} directory", suite.name),
+                format!("{} not found, pass --root", dir.display()),
+            ));
+            continue;
+        }
+        for file in suite.data_files {
+            let path = dir.join(file);
+            let name = format!("{} {}", suite.name, file);
+            match fs::metadata(&path) {
+                Ok(meta) if meta.len() > 0 => {
+                    checks.push(check(Status::Pass, name, format!("{} KiB", meta.len() >> 10)))
+                }
+                _ => checks.push(check(
+                    Status::Fail,
+                    name,
+                    match suite.generate {
+                        Some(_) => format!("missing, generate it with `benchmark run --suite {} --generate-data`", suite.name),
+                        None => format!("missing, generate it with `python3 dependencies/dependencies.py <scale>` in {}", dir.display()),
+                    },
+                )),
+            }
+        }
+    }
+
+    if suites.iter().any(|s| !s.scratch_files.is_empty()) {
+        checks.push(space_check("scratch space", root, at_scale(SCRATCH_BYTES_PER_SCALE)));
+    }
+    checks.push(space_check("temp space", &std::env::temp_dir(), SCRATCH_BYTES_PER_SCALE));
+
+    if suites.iter().any(|s| s.name == "memory") {
+        let needed = at_scale(MEMORY_BYTES_PER_SCALE);
+        checks.push(match available_memory() {
+            Some(free) if free >= needed => check(
+                Status::Pass,
+                "memory",
+                format!("{} MiB available, about {} MiB needed", mib(free), mib(needed)),
+            ),
+            Some(free) => check(
+                Status::Warn,
+                "memory",
+                format!("{} MiB available, about {} MiB needed, expect swapping", mib(free), mib(needed)),
+            ),
+            None => check(Status::Skip, "memory", "available memory can't be read on this platform"),
+        });
+    }
+
+    if let Ok(text) = fs::read_to_string("/proc/sys/kernel/perf_event_paranoid") {
+        checks.push(match text.trim().parse::<i32>() {
+            Ok(level) if level <= 2 => check(Status::Pass, "perf counters", format!("perf_event_paranoid = {}", level)),
+            Ok(level) => check(
+                Status::Warn,
+                "perf counters",
+                format!("perf_event_paranoid = {}, hardware counters need 2 or lower", level),
+            ),
+            Err(_) => check(Status::Warn, "perf counters", "unreadable perf_event_paranoid"),
+        });
+    }
+    checks.push(counters_check());
+
+    checks.push(match memlock_limit() {
+        Some(limit) if limit >= MIN_MEMLOCK_BYTES => check(
+            Status::Pass,
+            "mlock limit",
+            if limit == u64::MAX { "unlimited".to_string() } else { format!("{} MiB", mib(limit)) },
+        ),
+        Some(limit) => check(
+            Status::Warn,
+            "mlock limit",
+            format!("{} KiB, raise it with `ulimit -l` to pin buffers", limit >> 10),
+        ),
+        None => check(Status::Skip, "mlock limit", "not available on this platform"),
+    });
+
+    checks
+}
+
+pub fn print_checks(checks: &[Check], only_problems: bool) {
+    for c in checks {
+        if only_problems && matches!(c.status, Status::Pass | Status::Skip) {
+            continue;
+        }
+        println!("  {}  {:<28} {}", c.status.label(), c.name, c.detail);
+    }
+}
+
+pub fn failed(checks: &[Check]) -> usize {
+    checks.iter().filter(|c| c.status == Status::Fail).count()
+}
+
+pub fn run(args: DoctorArgs) -> Result<()> {
+    let all = suites::all();
+    suites::check_selection(&all, &args.suites)?;
+    let checks = preflight(&run::selected(&all, &args.suites), &args.root, args.scale);
+    println!("Preflight checks (scale {})", args.scale);
+    print_checks(&checks, false);
+
+    let warnings = checks.iter().filter(|c| c.status == Status::Warn).count();
+    let failures = failed(&checks);
+    println!();
+    println!(
+        "{} passed, {} warnings, {} failed",
+        checks.iter().filter(|c| c.status == Status::Pass).count(),
+        warnings,
+        failures
+    );
+    if failures > 0 {
+        return Err(format!("{} preflight check(s) failed", failures).into());
+    }
+    Ok(())
+}
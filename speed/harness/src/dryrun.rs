@@ -0,0 +1,190 @@
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::interrupt;
+use crate::memusage::{reset_peak_rss, status_bytes};
+use crate::overrides::{self, Override};
+use crate::run::{remove_scratch_files, RunReport};
+use crate::suites::{Suite, SubTest};
+use crate::Result;
+
+// what one probe run of a sub-test cost
+pub(crate) struct Probe {
+    pub(crate) ms: f64,
+    pub(crate) peak_memory_bytes: Option<u64>,
+    pub(crate) disk_bytes: u64,
+}
+
+struct Estimate {
+    ms: Option<f64>,
+    time_source: &'static str,
+    peak_memory_bytes: Option<u64>,
+    disk_bytes: Option<u64>,
+    error: Option<String>,
+}
+
+// bytes in files under `dir` that were written since `since`
+fn bytes_written_since(dir: &Path, since: SystemTime) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else { return 0 };
+    let mut total = 0;
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else { continue };
+        if meta.is_dir() {
+            total += bytes_written_since(&entry.path(), since);
+        } else if meta.modified().map(|m| m >= since).unwrap_or(false) {
+            total += meta.len();
+        }
+    }
+    total
+}
+
+pub(crate) fn probe(test: &SubTest, scale: f64, dir: &Path) -> std::result::Result<Probe, String> {
+    let start = SystemTime::now();
+    let tracked = reset_peak_rss();
+    let rss_before = status_bytes("VmRSS:");
+    let ms = (test.run)(scale)?;
+    let peak_memory_bytes = match (tracked, rss_before, status_bytes("VmHWM:")) {
+        (true, Some(before), Some(peak)) => Some(peak.saturating_sub(before)),
+        _ => None,
+    };
+    Ok(Probe {
+        ms,
+        peak_memory_bytes,
+        disk_bytes: bytes_written_since(dir, start),
+    })
+}
+
+// k in value = a * scale^k through two probes, clamped to [0, 3]; copes with tests that
+// grow quadratically or worse in their parameters, and with ones that don't grow at all
+pub(crate) fn scaling_exponent(scale_1: f64, at_1: f64, scale_2: f64, at_2: f64) -> f64 {
+    ((at_2 / at_1).ln() / (scale_2 / scale_1).ln()).clamp(0.0, 3.0)
+}
+
+// fit value = a * scale^k through the scale 1 and 2 probes and read it off at `scale`
+fn extrapolate(at_1: f64, at_2: f64, scale: f64) -> f64 {
+    if at_1 <= 0.0 || at_2 <= 0.0 {
+        return at_2.max(at_1) * scale / 2.0;
+    }
+    at_1 * scale.powf(scaling_exponent(1.0, at_1, 2.0, at_2))
+}
+
+// latest stored timing of a sub-test on this host at exactly this scale
+fn from_history(history: &[RunReport], hostname: &str, suite: &str, name: &str, scale: f64) -> Option<f64> {
+    history
+        .iter()
+        .rev()
+        .filter(|r| r.scale_of(suite, name) == scale && r.metadata.hostname == hostname)
+        .find_map(|r| r.results.iter().find(|t| t.suite == suite && t.name == name)?.ms)
+}
+
+fn estimate(test: &SubTest, scale: f64, dir: &Path, recorded_ms: Option<f64>) -> Estimate {
+    let probes = if scale == 1.0 { vec![probe(test, 1.0, dir)] } else { vec![probe(test, 1.0, dir), probe(test, 2.0, dir)] };
+    let probes: std::result::Result<Vec<Probe>, String> = probes.into_iter().collect();
+    let probes = match probes {
+        Ok(probes) => probes,
+        Err(err) => {
+            return Estimate {
+                ms: recorded_ms,
+                time_source: if recorded_ms.is_some() { "history" } else { "-" },
+                peak_memory_bytes: None,
+                disk_bytes: None,
+                error: Some(err),
+            }
+        }
+    };
+
+    let (first, last) = (&probes[0], &probes[probes.len() - 1]);
+    let scaled = |a: f64, b: f64| if probes.len() == 1 { a } else { extrapolate(a, b, scale) };
+    let peak_memory_bytes = match (first.peak_memory_bytes, last.peak_memory_bytes) {
+        (Some(a), Some(b)) => Some(scaled(a as f64, b as f64) as u64),
+        _ => None,
+    };
+    Estimate {
+        ms: Some(recorded_ms.unwrap_or_else(|| scaled(first.ms, last.ms))),
+        time_source: if recorded_ms.is_some() { "history" } else { "probe" },
+        peak_memory_bytes,
+        disk_bytes: Some(scaled(first.disk_bytes as f64, last.disk_bytes as f64) as u64),
+        error: None,
+    }
+}
+
+pub(crate) fn human_bytes(bytes: Option<u64>) -> String {
+    match bytes {
+        None => "n/a".to_string(),
+        Some(b) if b >= 1 << 30 => format!("{:.1} GiB", b as f64 / (1u64 << 30) as f64),
+        Some(b) if b >= 1 << 20 => format!("{:.1} MiB", b as f64 / (1u64 << 20) as f64),
+        Some(b) if b >= 1 << 10 => format!("{:.1} KiB", b as f64 / (1u64 << 10) as f64),
+        Some(b) => format!("{} B", b),
+    }
+}
+
+pub(crate) fn human_time(ms: Option<f64>) -> String {
+    match ms {
+        None => "n/a".to_string(),
+        Some(ms) if ms >= 60_000.0 => format!("{:.1} min", ms / 60_000.0),
+        Some(ms) if ms >= 1000.0 => format!("{:.1} s", ms / 1000.0),
+        Some(ms) => format!("{:.1} ms", ms),
+    }
+}
+
+// print what a run would do without doing it: each sub-test is probed at scale 1 and 2
+// (or taken from the history when it was already run at this scale) and extrapolated
+pub fn dry_run(
+    suites: &[&Suite],
+    root: &Path,
+    scale: f64,
+    overrides: &[Override],
+    history: &[RunReport],
+    hostname: &str,
+) -> Result<()> {
+    let start_dir = std::env::current_dir()?;
+    println!("Dry run at scale {}, estimates are rough (probe runs at scale 1 and 2)", scale);
+    println!();
+    println!(
+        "{:<36} {:<44} {:>10} {:>8} {:>11} {:>11}",
+        "test", "parameters", "time", "from", "peak mem", "disk"
+    );
+
+    let (mut total_ms, mut max_memory, mut total_disk) = (0.0, 0u64, 0u64);
+    for suite in suites {
+        let dir = start_dir.join(root).join(suite.name);
+        std::env::set_current_dir(&dir)
+            .map_err(|e| format!("cannot enter suite directory {}: {}", dir.display(), e))?;
+        for test in &suite.subtests {
+            if interrupt::requested() {
+                break;
+            }
+            eprintln!("probing {} {}", suite.name, test.name);
+            let scale = overrides::scale_of(overrides, suite.name, test.name).unwrap_or(scale);
+            let recorded = from_history(history, hostname, suite.name, test.name, scale);
+            let estimate = estimate(test, scale, &dir, recorded);
+            total_ms += estimate.ms.unwrap_or(0.0);
+            max_memory = max_memory.max(estimate.peak_memory_bytes.unwrap_or(0));
+            total_disk += estimate.disk_bytes.unwrap_or(0);
+            println!(
+                "{:<36} {:<44} {:>10} {:>8} {:>11} {:>11}",
+                format!("{}.{}", suite.name, test.name),
+                (test.params)(scale),
+                human_time(estimate.ms),
+                estimate.time_source,
+                human_bytes(estimate.peak_memory_bytes),
+                human_bytes(estimate.disk_bytes)
+            );
+            if let Some(err) = estimate.error {
+                println!("  probe failed: {}", err);
+            }
+        }
+        remove_scratch_files(suite);
+    }
+    std::env::set_current_dir(&start_dir)?;
+
+    println!();
+    println!(
+        "total: about {}, peak memory {}, {} written to disk",
+        human_time(Some(total_ms)),
+        human_bytes(Some(max_memory)),
+        human_bytes(Some(total_disk))
+    );
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::run::RunReport;
+use crate::store;
+use crate::Result;
+
+#[derive(Args)]
+pub struct ReportArgs {
+    /// Result history written by `run --store` (or `--record`)
+    store: PathBuf,
+    /// Where to write the page
+    #[arg(long, short, default_value = "report.html")]
+    output: PathBuf,
+    /// Only include these suites (comma separated)
+    #[arg(long = "suite", value_delimiter = ',')]
+    suites: Vec<String>,
+    /// Number of most recent runs drawn in each trend line
+    #[arg(long, default_value_t = 30)]
+    last: usize,
+    /// Changes smaller than this (in percent) are not highlighted
+    #[arg(long, default_value_t = 5.0)]
+    threshold: f64,
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em auto;max-width:1100px;color:#222}\
+h1{font-size:1.5em}h2{margin-top:2em;border-bottom:1px solid #ddd}\
+.meta{color:#666}.cards{display:flex;flex-wrap:wrap;gap:1em}\
+.card{border:1px solid #ddd;border-radius:4px;padding:.5em}.card h3{font-size:1em;margin:0 0 .3em}\
+.worse{color:#c0392b}.better{color:#27ae60}svg text{font-size:11px;fill:#444}\
+.latest{fill:#2f6fb0}.previous{fill:#bbb}.line{fill:none;stroke:#2f6fb0;stroke-width:1.5}\
+.point{fill:#2f6fb0}.axis{stroke:#ccc}";
+
+// bar chart geometry: label column, bar area and the two stacked bars of a row
+const LABEL_WIDTH: f64 = 220.0;
+const BAR_WIDTH: f64 = 420.0;
+const ROW_HEIGHT: f64 = 30.0;
+
+// trend chart geometry
+const TREND_WIDTH: f64 = 340.0;
+const TREND_HEIGHT: f64 = 110.0;
+const TREND_PAD: f64 = 24.0;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// yyyy-mm-dd hh:mm in UTC, from days since the epoch to a civil date (Howard Hinnant's
+// algorithm) so the page needs no date crate
+fn date(timestamp_secs: u64) -> String {
+    let days = (timestamp_secs / 86_400) as i64;
+    let (hour, minute) = (timestamp_secs % 86_400 / 3600, timestamp_secs % 3600 / 60);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+}
+
+// the short commit of a run, or its position in the history when it has none
+fn label(report: &RunReport, index: usize) -> String {
+    match &report.metadata.commit {
+        Some(commit) => commit.chars().take(7).collect(),
+        None => format!("run {}", index + 1),
+    }
+}
+
+fn change_class(change_pct: f64, threshold: f64) -> &'static str {
+    if change_pct > threshold {
+        "worse"
+    } else if change_pct < -threshold {
+        "better"
+    } else {
+        ""
+    }
+}
+
+// one suite's sub-tests as horizontal bars, the latest run over the previous one
+fn bar_chart(rows: &[(&str, Option<f64>, Option<f64>)]) -> String {
+    let max = rows
+        .iter()
+        .flat_map(|(_, latest, previous)| [*latest, *previous])
+        .flatten()
+        .fold(0.0, f64::max);
+    let height = rows.len() as f64 * ROW_HEIGHT + 10.0;
+    let mut svg = format!(
+        "<svg width=\"{}\" height=\"{}\" role=\"img\">",
+        LABEL_WIDTH + BAR_WIDTH + 90.0,
+        height
+    );
+    for (i, (name, latest, previous)) in rows.iter().enumerate() {
+        let y = i as f64 * ROW_HEIGHT + 5.0;
+        let _ = write!(svg, "<text x=\"0\" y=\"{:.1}\">{}</text>", y + 15.0, escape(name));
+        let width = |ms: f64| if max > 0.0 { ms / max * BAR_WIDTH } else { 0.0 };
+        if let Some(ms) = previous {
+            let _ = write!(
+                svg,
+                "<rect class=\"previous\" x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"8\"><title>previous {:.3} ms</title></rect>",
+                LABEL_WIDTH,
+                y + 2.0,
+                width(*ms),
+                ms
+            );
+        }
+        match latest {
+            Some(ms) => {
+                let _ = write!(
+                    svg,
+                    "<rect class=\"latest\" x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"12\"><title>latest {:.3} ms</title></rect>\
+                     <text x=\"{:.1}\" y=\"{:.1}\">{:.3} ms</text>",
+                    LABEL_WIDTH,
+                    y + 11.0,
+                    width(*ms),
+                    ms,
+                    LABEL_WIDTH + width(*ms) + 4.0,
+                    y + 21.0,
+                    ms
+                );
+            }
+            None => {
+                let _ = write!(svg, "<text x=\"{}\" y=\"{:.1}\" class=\"worse\">failed</text>", LABEL_WIDTH, y + 21.0);
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+// one sub-test's timings across runs, oldest on the left, with the commit of the first
+// and last point under the axis and every point's details in its tooltip
+fn trend_chart(points: &[(String, String, f64)]) -> String {
+    let mut svg = format!("<svg width=\"{}\" height=\"{}\" role=\"img\">", TREND_WIDTH, TREND_HEIGHT);
+    let (min, max) = points.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| (lo.min(p.2), hi.max(p.2)));
+    let bottom = TREND_HEIGHT - TREND_PAD;
+    let x = |i: usize| match points.len() {
+        1 => TREND_WIDTH / 2.0,
+        n => TREND_PAD + i as f64 * (TREND_WIDTH - 2.0 * TREND_PAD) / (n - 1) as f64,
+    };
+    let y = |ms: f64| match max - min {
+        range if range > 0.0 => bottom - (ms - min) / range * (bottom - TREND_PAD / 2.0),
+        _ => (bottom + TREND_PAD / 2.0) / 2.0,
+    };
+    let _ = write!(
+        svg,
+        "<line class=\"axis\" x1=\"{p}\" y1=\"{b:.1}\" x2=\"{w:.1}\" y2=\"{b:.1}\"/>\
+         <text x=\"0\" y=\"{top:.1}\">{max:.3}</text><text x=\"0\" y=\"{b:.1}\">{min:.3}</text>",
+        p = TREND_PAD,
+        b = bottom,
+        w = TREND_WIDTH - TREND_PAD,
+        top = TREND_PAD / 2.0 + 4.0,
+        max = max,
+        min = min
+    );
+    let path: Vec<String> = points.iter().enumerate().map(|(i, p)| format!("{:.1},{:.1}", x(i), y(p.2))).collect();
+    let _ = write!(svg, "<polyline class=\"line\" points=\"{}\"/>", path.join(" "));
+    for (i, (commit, when, ms)) in points.iter().enumerate() {
+        let _ = write!(
+            svg,
+            "<circle class=\"point\" cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\"><title>{} {} {:.3} ms</title></circle>",
+            x(i),
+            y(*ms),
+            escape(commit),
+            when,
+            ms
+        );
+    }
+    if let (Some(first), Some(last)) = (points.first(), points.last()) {
+        let _ = write!(svg, "<text x=\"{}\" y=\"{}\">{}</text>", TREND_PAD, TREND_HEIGHT - 6.0, escape(&first.0));
+        if points.len() > 1 {
+            let _ = write!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+                TREND_WIDTH - TREND_PAD,
+                TREND_HEIGHT - 6.0,
+                escape(&last.0)
+            );
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+// the latest run's sub-tests, each with a bar against the previous run at the same scale
+// and a trend line over the stored runs at that scale
+fn page(history: &[RunReport], args: &ReportArgs) -> Result<String> {
+    let latest = history.last().ok_or("the result history is empty")?;
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Benchmark report</title><style>{}</style></head><body>",
+        STYLE
+    );
+    let mut hosts: Vec<&str> = history.iter().map(|r| r.metadata.hostname.as_str()).collect();
+    hosts.sort_unstable();
+    hosts.dedup();
+    let _ = write!(
+        html,
+        "<h1>Benchmark report</h1><p class=\"meta\">{} runs from {} to {} on {}.<br>Latest: {} at scale {}, {}, {}.</p>",
+        history.len(),
+        date(history[0].metadata.timestamp_secs),
+        date(latest.metadata.timestamp_secs),
+        escape(&hosts.join(", ")),
+        escape(&label(latest, history.len() - 1)),
+        latest.scale,
+        escape(&latest.metadata.build.rustc),
+        escape(&latest.metadata.summary())
+    );
+
+    let mut suites: Vec<&str> = latest.results.iter().map(|r| r.suite.as_str()).collect();
+    suites.dedup();
+    suites.retain(|suite| args.suites.is_empty() || args.suites.iter().any(|s| s == suite));
+    if suites.is_empty() {
+        return Err("none of the selected suites are in the latest run".into());
+    }
+    for suite in suites {
+        let _ = write!(html, "<h2>{}</h2>", escape(suite));
+        let results: Vec<_> = latest.results.iter().filter(|r| r.suite == suite).collect();
+        let mut bars = Vec::new();
+        let mut cards = String::new();
+        for result in results {
+            let scale = latest.scale_of(suite, &result.name);
+            // runs at the same scale that have this sub-test, oldest first
+            let comparable: Vec<(usize, &RunReport, f64)> = history
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.scale_of(suite, &result.name) == scale)
+                .filter_map(|(i, r)| {
+                    let ms = r.results.iter().find(|t| t.suite == suite && t.name == result.name)?.ms?;
+                    Some((i, r, ms))
+                })
+                .collect();
+            let previous = match result.ms {
+                Some(_) => comparable.iter().rev().nth(1).map(|c| c.2),
+                None => comparable.last().map(|c| c.2),
+            };
+            bars.push((result.name.as_str(), result.ms, previous));
+
+            let recent = &comparable[comparable.len().saturating_sub(args.last)..];
+            let points: Vec<(String, String, f64)> =
+                recent.iter().map(|(i, r, ms)| (label(r, *i), date(r.metadata.timestamp_secs), *ms)).collect();
+            let change = match (result.ms, previous) {
+                (Some(now), Some(before)) if before > 0.0 => {
+                    let pct = (now - before) / before * 100.0;
+                    format!(" <span class=\"{}\">{:+.1}%</span>", change_class(pct, args.threshold), pct)
+                }
+                _ => String::new(),
+            };
+            let _ = write!(
+                cards,
+                "<div class=\"card\"><h3>{}{}</h3>{}</div>",
+                escape(&result.name),
+                change,
+                if points.is_empty() { "<p class=\"meta\">no timings yet</p>".to_string() } else { trend_chart(&points) }
+            );
+        }
+        let _ = write!(html, "{}<div class=\"cards\">{}</div>", bar_chart(&bars), cards);
+    }
+    html.push_str("</body></html>\n");
+    Ok(html)
+}
+
+pub fn report(args: ReportArgs) -> Result<()> {
+    let history = store::load(&args.store)?;
+    let html = page(&history, &args)?;
+    fs::write(&args.output, html)?;
+    println!("Report of {} runs written to {}", history.len(), args.output.display());
+    Ok(())
+}
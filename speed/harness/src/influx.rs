@@ -0,0 +1,104 @@
+#[cfg(feature = "webhooks")]
+use std::time::Duration;
+
+use crate::metadata::Metadata;
+use crate::orchestrate::OrchestrateReport;
+use crate::run::RunReport;
+use crate::Result;
+
+const MEASUREMENT: &str = "benchmark";
+
+// tag values can't carry raw commas, spaces or equals signs
+fn escape_tag(value: &str) -> String {
+    value.replace(',', "\\,").replace(' ', "\\ ").replace('=', "\\=")
+}
+
+// the measurement and the machine and commit tags every point carries
+fn common_tags(metadata: &Metadata) -> String {
+    let mut common = format!(
+        "{},host={},os={},arch={}",
+        MEASUREMENT,
+        escape_tag(&metadata.hostname),
+        escape_tag(&metadata.os),
+        escape_tag(&metadata.arch)
+    );
+    if let Some(commit) = &metadata.commit {
+        common.push_str(&format!(",commit={}", escape_tag(commit)));
+    }
+    common
+}
+
+// one influx line-protocol point per suite and language that produced a timing
+pub fn line_protocol(report: &OrchestrateReport) -> String {
+    let timestamp_ns = report.metadata.timestamp_secs as u128 * 1_000_000_000;
+    let common = common_tags(&report.metadata);
+
+    let mut lines = String::new();
+    for suite in &report.suites {
+        for result in &suite.results {
+            let Some(median_ms) = result.median_ms else { continue };
+            let mut fields = format!("median_ms={},scale={}i", median_ms, report.scale);
+            if let Some(ratio) = result.relative_to_reference {
+                fields.push_str(&format!(",relative={}", ratio));
+            }
+            lines.push_str(&format!(
+                "{},suite={},language={} {} {}\n",
+                common,
+                escape_tag(&suite.suite),
+                escape_tag(&result.language),
+                fields,
+                timestamp_ns
+            ));
+        }
+    }
+    lines
+}
+
+// one point per sub-test of a `run` that produced a timing, at the scale it ran at
+pub fn run_line_protocol(report: &RunReport) -> String {
+    let timestamp_ns = report.metadata.timestamp_secs as u128 * 1_000_000_000;
+    let common = common_tags(&report.metadata);
+
+    let mut lines = String::new();
+    for result in &report.results {
+        let Some(ms) = result.ms else { continue };
+        lines.push_str(&format!(
+            "{},suite={},test={},language=rust ms={},scale={} {}\n",
+            common,
+            escape_tag(&result.suite),
+            escape_tag(&result.name),
+            ms,
+            report.scale_of(&result.suite, &result.name),
+            timestamp_ns
+        ));
+    }
+    lines
+}
+
+// POST the points to an influx write endpoint, e.g. http://host:8086/write?db=bench (1.x)
+// or https://host:8086/api/v2/write?org=me&bucket=bench (2.x, token from INFLUX_TOKEN)
+#[cfg(feature = "webhooks")]
+pub fn push(url: &str, body: &str) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()?;
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(body.to_string());
+    if let Ok(token) = std::env::var("INFLUX_TOKEN") {
+        request = request.header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+    }
+    let response = request.send()?;
+    let status = response.status();
+    if !status.is_success() {
+        let detail = response.text().unwrap_or_default();
+        return Err(format!("influx write failed: {} {}", status, detail.trim()).trim_end().into());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "webhooks"))]
+pub fn push(_url: &str, _body: &str) -> Result<()> {
+    Err("this build has no `webhooks` feature, results can't be pushed to influx".into())
+}
@@ -0,0 +1,22 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// the first ctrl-c asks the running command to stop after the current sub-test, a
+// second one gives up on that and exits right away
+pub fn install() {
+    let result = ctrlc::set_handler(|| {
+        if REQUESTED.swap(true, Ordering::SeqCst) {
+            eprintln!("Interrupted again, exiting without saving");
+            std::process::exit(130);
+        }
+        eprintln!("Interrupted, finishing the current sub-test (press Ctrl-C again to abort)");
+    });
+    if let Err(err) = result {
+        eprintln!("warning: could not install the Ctrl-C handler: {}", err);
+    }
+}
+
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
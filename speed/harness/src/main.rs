@@ -0,0 +1,103 @@
+use clap::{Parser, Subcommand};
+
+mod ab;
+mod calibrate;
+mod compare;
+mod counters;
+mod docs;
+mod doctor;
+mod dryrun;
+mod html;
+mod influx;
+mod interrupt;
+mod memusage;
+mod metadata;
+mod multiproc;
+mod notify;
+mod orchestrate;
+mod overrides;
+mod profiles;
+mod run;
+mod scaling;
+mod score;
+mod selftest;
+mod serve;
+mod simd;
+mod store;
+mod suites;
+mod term;
+mod watch;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[global_allocator]
+static ALLOCATOR: memusage::CountingAlloc = memusage::CountingAlloc;
+
+// rust-side harness for the speed benchmark suites
+#[derive(Parser)]
+#[command(name = "benchmark", version, about = "Rust harness for the speed benchmark suites")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the rust sub-tests in-process, optionally scored against a reference profile
+    Run(run::RunArgs),
+    /// Compare two result files per sub-test, biggest regressions first
+    Compare(compare::CompareArgs),
+    /// Show how the sub-tests in a result history evolved, with sparklines
+    History(compare::HistoryArgs),
+    /// Render a result history as a static HTML page with bar charts and trend lines
+    Report(html::ReportArgs),
+    /// Print a shell completion script
+    Completions(docs::CompletionsArgs),
+    /// Check data files, endpoints, free space and permissions before a run
+    Doctor(doctor::DoctorArgs),
+    /// Print the man page, or write one per subcommand with --out-dir
+    Manpage(docs::ManpageArgs),
+    /// Run every language's suite binaries and compare them against the reference language
+    Orchestrate(orchestrate::OrchestrateArgs),
+    /// List the shipped reference-machine profiles usable with `run --normalize-to`
+    Profiles,
+    /// Serve an HTTP API that runs suites on demand and answers with the JSON report
+    Serve(serve::ServeArgs),
+    /// Alternate two benchmark binaries and report paired-difference statistics
+    Ab(ab::AbArgs),
+    /// One copy of a sub-test for `run --processes`
+    #[command(hide = true)]
+    Worker(multiproc::WorkerArgs),
+}
+
+fn main() {
+    // concurrency's process_spawn re-runs the binary it runs in as a do-nothing child
+    #[cfg(feature = "concurrency")]
+    if std::env::args().nth(1).as_deref() == Some(suites::SPAWN_CHILD_ARG) {
+        return;
+    }
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Run(args) => run::run(args),
+        Command::Compare(args) => compare::compare(args),
+        Command::History(args) => compare::history(args),
+        Command::Report(args) => html::report(args),
+        Command::Completions(args) => docs::completions(args),
+        Command::Doctor(args) => doctor::run(args),
+        Command::Manpage(args) => docs::manpage(args),
+        Command::Orchestrate(args) => orchestrate::run(args),
+        Command::Profiles => profiles::list(),
+        Command::Serve(args) => serve::run(args),
+        Command::Ab(args) => ab::run(args),
+        Command::Worker(args) => multiproc::worker(args),
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+    if interrupt::requested() {
+        std::process::exit(130);
+    }
+}
@@ -0,0 +1,186 @@
+// per sub-test memory use for `run --memory`: a counting wrapper around the system
+// allocator for the heap, and the platform's resident-set high-water mark for the rest
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+// forwards everything to the system allocator and only counts while a tracker runs, so
+// runs without --memory pay one relaxed load per allocation
+pub struct CountingAlloc;
+
+static TRACKING: AtomicBool = AtomicBool::new(false);
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+// heap bytes live relative to the tracker's start, negative when it frees older memory
+static LIVE: AtomicI64 = AtomicI64::new(0);
+static PEAK: AtomicI64 = AtomicI64::new(0);
+
+fn grew(bytes: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED.fetch_add(bytes as u64, Ordering::Relaxed);
+    let live = LIVE.fetch_add(bytes as i64, Ordering::Relaxed) + bytes as i64;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() && TRACKING.load(Ordering::Relaxed) {
+            grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() && TRACKING.load(Ordering::Relaxed) {
+            grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        if TRACKING.load(Ordering::Relaxed) {
+            LIVE.fetch_sub(layout.size() as i64, Ordering::Relaxed);
+        }
+    }
+
+    // a growing realloc counts as one allocation of the bytes it added
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() && TRACKING.load(Ordering::Relaxed) {
+            if new_size > layout.size() {
+                grew(new_size - layout.size());
+            } else {
+                LIVE.fetch_sub((layout.size() - new_size) as i64, Ordering::Relaxed);
+            }
+        }
+        new
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MemoryUse {
+    // the process's resident-set high-water mark while the sub-test ran; none where the
+    // platform's mark can't be reset and an earlier sub-test had already set it higher
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_rss_bytes: Option<u64>,
+    // the most heap bytes live at once beyond what was live when the sub-test started
+    pub peak_heap_bytes: u64,
+    pub allocated_bytes: u64,
+    pub allocations: u64,
+}
+
+impl MemoryUse {
+    // over interleaved rounds: the highest peaks and the median of the counts
+    pub fn combine(rounds: &[MemoryUse]) -> Option<MemoryUse> {
+        let median = |value: fn(&MemoryUse) -> u64| {
+            let values: Vec<f64> = rounds.iter().map(|r| value(r) as f64).collect();
+            crate::orchestrate::median(&values).unwrap_or(0.0) as u64
+        };
+        Some(MemoryUse {
+            peak_rss_bytes: rounds.iter().filter_map(|r| r.peak_rss_bytes).max(),
+            peak_heap_bytes: rounds.iter().map(|r| r.peak_heap_bytes).max()?,
+            allocated_bytes: median(|r| r.allocated_bytes),
+            allocations: median(|r| r.allocations),
+        })
+    }
+}
+
+// counts the memory use of one stretch of work; only one runs at a time
+pub struct Tracker {
+    reset: bool,
+    rss_before: Option<u64>,
+}
+
+impl Tracker {
+    pub fn start() -> Tracker {
+        let reset = reset_peak_rss();
+        let rss_before = high_water_mark();
+        ALLOCATED.store(0, Ordering::Relaxed);
+        ALLOCATIONS.store(0, Ordering::Relaxed);
+        LIVE.store(0, Ordering::Relaxed);
+        PEAK.store(0, Ordering::Relaxed);
+        TRACKING.store(true, Ordering::Relaxed);
+        Tracker { reset, rss_before }
+    }
+
+    pub fn stop(self) -> MemoryUse {
+        TRACKING.store(false, Ordering::Relaxed);
+        let rss_after = high_water_mark();
+        MemoryUse {
+            peak_rss_bytes: match (self.reset, self.rss_before, rss_after) {
+                (true, _, after) => after,
+                (false, Some(before), Some(after)) if after > before => Some(after),
+                _ => None,
+            },
+            peak_heap_bytes: PEAK.load(Ordering::Relaxed).max(0) as u64,
+            allocated_bytes: ALLOCATED.load(Ordering::Relaxed),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// a field of /proc/self/status in bytes, linux only
+pub(crate) fn status_bytes(field: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with(field))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+// writing 5 to clear_refs resets the VmHWM peak to the current rss, linux only
+pub(crate) fn reset_peak_rss() -> bool {
+    std::fs::write("/proc/self/clear_refs", "5").is_ok()
+}
+
+#[cfg(target_os = "linux")]
+fn high_water_mark() -> Option<u64> {
+    status_bytes("VmHWM:")
+}
+
+// ru_maxrss is in bytes on macos and in KiB on the other unixes
+#[cfg(all(unix, not(target_os = "linux")))]
+fn high_water_mark() -> Option<u64> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return None;
+    }
+    let unit = if cfg!(target_os = "macos") { 1 } else { 1024 };
+    Some(usage.ru_maxrss as u64 * unit)
+}
+
+#[cfg(windows)]
+fn high_water_mark() -> Option<u64> {
+    use std::ffi::c_void;
+
+    // PROCESS_MEMORY_COUNTERS
+    #[repr(C)]
+    #[derive(Default)]
+    struct Counters {
+        cb: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        rest: [usize; 7],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn K32GetProcessMemoryInfo(process: *mut c_void, counters: *mut Counters, cb: u32) -> i32;
+    }
+
+    let mut counters = Counters {
+        cb: std::mem::size_of::<Counters>() as u32,
+        ..Counters::default()
+    };
+    let ok = unsafe { K32GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, counters.cb) };
+    (ok != 0).then_some(counters.peak_working_set_size as u64)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn high_water_mark() -> Option<u64> {
+    None
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bench_core::CgroupLimits;
+use serde::{Deserialize, Serialize};
+
+use crate::simd::SimdInfo;
+
+// where and under which limits a set of results was produced
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Metadata {
+    pub hostname: String,
+    pub commit: Option<String>,
+    pub timestamp_secs: u64,
+    pub os: String,
+    pub arch: String,
+    pub host_cpus: usize,
+    pub effective_cpus: usize,
+    #[serde(with = "CgroupLimitsDef")]
+    pub cgroup: CgroupLimits,
+    // results recorded before these were captured deserialize with them empty
+    #[serde(default)]
+    pub build: BuildInfo,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub simd: SimdInfo,
+}
+
+// bench-core stays std only, so the cgroup limits it detects are serialized through this
+// mirror of their fields
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "CgroupLimits")]
+struct CgroupLimitsDef {
+    version: Option<u8>,
+    cpu_quota: Option<f64>,
+    memory_limit_bytes: Option<u64>,
+}
+
+// how the harness binary that produced the results was compiled, captured by build.rs
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BuildInfo {
+    pub profile: String,
+    pub opt_level: String,
+    pub debug_assertions: bool,
+    pub target: String,
+    pub rustc: String,
+    pub rustflags: String,
+    pub features: Vec<String>,
+    pub allocator: String,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        let features = env!("BENCH_BUILD_FEATURES");
+        BuildInfo {
+            profile: env!("BENCH_BUILD_PROFILE").to_string(),
+            opt_level: env!("BENCH_BUILD_OPT_LEVEL").to_string(),
+            debug_assertions: cfg!(debug_assertions),
+            target: env!("BENCH_BUILD_TARGET").to_string(),
+            rustc: env!("BENCH_BUILD_RUSTC").to_string(),
+            rustflags: env!("BENCH_BUILD_RUSTFLAGS").to_string(),
+            features: features.split(',').filter(|f| !f.is_empty()).map(str::to_string).collect(),
+            // the #[global_allocator] only counts for `run --memory`, every block comes from the system one
+            allocator: "system".to_string(),
+        }
+    }
+
+    // results recorded before the build was captured have an empty opt-level and pass
+    pub fn unoptimized(&self) -> bool {
+        self.debug_assertions || self.opt_level == "0"
+    }
+}
+
+// runtime variables that change what the sub-tests do or how fast they run
+const ENV_VARS: &[&str] = &[
+    "RUST_BENCH_",
+    "RUSTFLAGS",
+    "RUST_MIN_STACK",
+    "RAYON_NUM_THREADS",
+    "TOKIO_WORKER_THREADS",
+    "MALLOC_",
+    "LD_PRELOAD",
+    "OMP_NUM_THREADS",
+];
+
+// entries ending in an underscore match every variable with that prefix
+fn relevant_env() -> BTreeMap<String, String> {
+    std::env::vars()
+        .filter(|(name, _)| {
+            ENV_VARS.iter().any(|v| if v.ends_with('_') { name.starts_with(v) } else { name == v })
+        })
+        .collect()
+}
+
+impl Metadata {
+    pub fn collect() -> Self {
+        let cgroup = CgroupLimits::detect();
+        Metadata {
+            hostname: hostname(),
+            commit: git_commit(),
+            timestamp_secs: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            host_cpus: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            effective_cpus: cgroup.effective_cpus(),
+            cgroup,
+            build: BuildInfo::current(),
+            env: relevant_env(),
+            simd: SimdInfo::detect(),
+        }
+    }
+
+    // one line for the top of human-readable reports
+    pub fn summary(&self) -> String {
+        let mut line = format!("{} ({}/{}), {} cpus", self.hostname, self.os, self.arch, self.effective_cpus);
+        if let Some(quota) = self.cgroup.cpu_quota {
+            line.push_str(&format!(", cgroup cpu quota {:.2}", quota));
+        }
+        if let Some(limit) = self.cgroup.memory_limit_bytes {
+            line.push_str(&format!(", cgroup memory limit {} MiB", limit / (1024 * 1024)));
+        }
+        if let Some(simd) = self.simd.summary() {
+            line.push_str(&format!(", {}", simd));
+        }
+        if self.build.unoptimized() {
+            line.push_str(&format!(", UNOPTIMIZED {} build", self.build.profile));
+        }
+        line
+    }
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// commit of the checkout being benchmarked, None outside a git tree
+fn git_commit() -> Option<String> {
+    let output = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
@@ -0,0 +1,88 @@
+// the no_std kernels of speed/micro timed on this host, so their numbers can be set
+// against the cycle counts an embedded target reports for the same kernels
+use std::time::Instant;
+
+use ::micro::{CycleCounter, TESTS};
+use bench_core::scaled;
+
+use crate::suites::{SelfCheck, SubTest};
+
+// iterations per scale step, enough to lift every kernel well above the timer resolution
+const ITERATIONS: usize = 2000;
+
+// hosted targets have no portable cycle counter, so this one counts nanoseconds
+struct NanoCounter(Instant);
+
+impl CycleCounter for NanoCounter {
+    fn cycles(&mut self) -> u64 {
+        self.0.elapsed().as_nanos() as u64
+    }
+
+    fn frequency_hz(&self) -> Option<u64> {
+        Some(1_000_000_000)
+    }
+}
+
+fn iterations(scale: f64) -> usize {
+    scaled(ITERATIONS, scale)
+}
+
+fn params(index: usize, scale: f64) -> String {
+    format!("{} iterations={}", TESTS[index].params, iterations(scale))
+}
+
+fn run(index: usize, iterations: usize) -> Result<f64, String> {
+    let test = &TESTS[index];
+    let mut counter = NanoCounter(Instant::now());
+    let measurement = (test.run)(&mut counter, iterations as u32);
+    if measurement.checksum != test.expected_checksum {
+        return Err(format!(
+            "checksum {} differs from the expected {}",
+            measurement.checksum, test.expected_checksum
+        ));
+    }
+    Ok(measurement.cycles as f64 / 1_000_000.0)
+}
+
+pub const SUBTESTS: &[SubTest] = &[
+    SubTest {
+        name: TESTS[0].name,
+        params: |scale| params(0, scale),
+        run: |scale| run(0, iterations(scale)),
+    },
+    SubTest {
+        name: TESTS[1].name,
+        params: |scale| params(1, scale),
+        run: |scale| run(1, iterations(scale)),
+    },
+    SubTest {
+        name: TESTS[2].name,
+        params: |scale| params(2, scale),
+        run: |scale| run(2, iterations(scale)),
+    },
+    SubTest {
+        name: TESTS[3].name,
+        params: |scale| params(3, scale),
+        run: |scale| run(3, iterations(scale)),
+    },
+];
+
+// one iteration is enough to compare the checksum
+pub const SELF_CHECKS: &[SelfCheck] = &[
+    SelfCheck {
+        name: TESTS[0].name,
+        check: || run(0, 1).map(|_| ()),
+    },
+    SelfCheck {
+        name: TESTS[1].name,
+        check: || run(1, 1).map(|_| ()),
+    },
+    SelfCheck {
+        name: TESTS[2].name,
+        check: || run(2, 1).map(|_| ()),
+    },
+    SelfCheck {
+        name: TESTS[3].name,
+        check: || run(3, 1).map(|_| ()),
+    },
+];
@@ -0,0 +1,101 @@
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+
+use bench_core::RngKind;
+use clap::Args;
+
+use crate::suites;
+use crate::Result;
+
+#[derive(Args)]
+pub struct WorkerArgs {
+    suite: String,
+    test: String,
+    #[arg(long, default_value_t = 1.0)]
+    scale: f64,
+    #[arg(long, value_parser = RngKind::parse, default_value = "native")]
+    rng: RngKind,
+}
+
+// one copy of a sub-test for `run --processes`, started from inside the suite directory;
+// it waits for a line on stdin so all copies start timing together, then prints the ms
+pub fn worker(args: WorkerArgs) -> Result<()> {
+    let all = suites::all();
+    let test = all
+        .iter()
+        .filter(|s| s.name == args.suite)
+        .flat_map(|s| &s.subtests)
+        .find(|t| t.name == args.test)
+        .ok_or_else(|| format!("unknown sub-test {}.{}", args.suite, args.test))?;
+
+    bench_core::select_rng(args.rng);
+    let mut go = String::new();
+    std::io::stdin().read_line(&mut go)?;
+    let ms = (test.run)(args.scale)?;
+    println!("{}", ms);
+    Ok(())
+}
+
+fn spawn_copy(suite: &str, test: &str, scale: f64) -> std::result::Result<Child, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("cannot find the harness binary: {}", e))?;
+    Command::new(exe)
+        .args(["worker", suite, test, "--scale", &scale.to_string()])
+        .args(["--rng", bench_core::selected_rng().name()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot start a copy: {}", e))
+}
+
+fn collect(child: Child) -> std::result::Result<f64, String> {
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("a copy exited with {}: {}", output.status, stderr.trim()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("a copy printed no timing: {:?}", stdout.trim()))
+}
+
+// run `copies` processes of one sub-test at the same time and report the slowest, which
+// is how long the whole machine took to get through all of them
+pub fn run_copies(suite: &str, test: &str, scale: f64, copies: usize) -> std::result::Result<f64, String> {
+    let mut children = Vec::with_capacity(copies);
+    for _ in 0..copies {
+        match spawn_copy(suite, test, scale) {
+            Ok(child) => children.push(child),
+            Err(err) => {
+                for mut child in children {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                return Err(err);
+            }
+        }
+    }
+    // the copies are all loaded and waiting, release them together
+    for child in &mut children {
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = writeln!(stdin, "go");
+        }
+    }
+
+    let mut slowest: f64 = 0.0;
+    let mut first_error = None;
+    for child in children {
+        match collect(child) {
+            Ok(ms) => slowest = slowest.max(ms),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    match first_error {
+        Some(err) => Err(err),
+        None => Ok(slowest),
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "webhooks")]
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::run::RunReport;
+use crate::Result;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    Slack,
+    Discord,
+    #[default]
+    Json,
+}
+
+// where regression alerts go, from the "webhook" section of the run config
+#[derive(Deserialize, Clone, Debug)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Regression {
+    pub suite: String,
+    pub name: String,
+    pub baseline_ms: f64,
+    pub ms: f64,
+    pub change_pct: f64,
+}
+
+// sub-tests that got slower than the baseline by more than threshold_pct, worst first;
+// the ones that ran at another scale than in the baseline aren't comparable and are skipped
+pub fn find_regressions(report: &RunReport, baseline: &RunReport, threshold_pct: f64) -> Vec<Regression> {
+    let baseline_ms: BTreeMap<(&str, &str), f64> = baseline
+        .results
+        .iter()
+        .filter_map(|r| r.ms.map(|ms| ((r.suite.as_str(), r.name.as_str()), ms)))
+        .collect();
+
+    let mut regressions: Vec<Regression> = report
+        .results
+        .iter()
+        .filter_map(|result| {
+            let ms = result.ms?;
+            if report.scale_of(&result.suite, &result.name) != baseline.scale_of(&result.suite, &result.name) {
+                return None;
+            }
+            let &before = baseline_ms.get(&(result.suite.as_str(), result.name.as_str()))?;
+            if before <= 0.0 {
+                return None;
+            }
+            let change_pct = (ms - before) / before * 100.0;
+            (change_pct > threshold_pct).then(|| Regression {
+                suite: result.suite.clone(),
+                name: result.name.clone(),
+                baseline_ms: before,
+                ms,
+                change_pct,
+            })
+        })
+        .collect();
+    regressions.sort_by(|a, b| b.change_pct.total_cmp(&a.change_pct));
+    regressions
+}
+
+pub fn summary(report: &RunReport, baseline_name: &str, regressions: &[Regression]) -> String {
+    let metadata = &report.metadata;
+    let mut text = format!(
+        "{} regression(s) on {} (commit {}, scale {}) against {}:",
+        regressions.len(),
+        metadata.hostname,
+        metadata.commit.as_deref().unwrap_or("unknown"),
+        report.scale,
+        baseline_name
+    );
+    for r in regressions {
+        text.push_str(&format!(
+            "\n  {}.{}: {:.3} ms -> {:.3} ms ({:+.1}%)",
+            r.suite, r.name, r.baseline_ms, r.ms, r.change_pct
+        ));
+    }
+    text
+}
+
+pub fn post(
+    webhook: &Webhook,
+    report: &RunReport,
+    baseline_name: &str,
+    threshold_pct: f64,
+    regressions: &[Regression],
+) -> Result<()> {
+    let text = summary(report, baseline_name, regressions);
+    let payload = match webhook.format {
+        WebhookFormat::Slack => serde_json::json!({ "text": format!("```{}```", text) }),
+        // discord rejects messages over 2000 characters
+        WebhookFormat::Discord => {
+            let body: String = text.chars().take(1990).collect();
+            serde_json::json!({ "content": format!("```{}```", body) })
+        }
+        WebhookFormat::Json => serde_json::json!({
+            "host": report.metadata.hostname,
+            "commit": report.metadata.commit,
+            "scale": report.scale,
+            "baseline": baseline_name,
+            "threshold_pct": threshold_pct,
+            "regressions": regressions,
+            "summary": text,
+        }),
+    };
+
+    send(&webhook.url, &payload)
+}
+
+#[cfg(feature = "webhooks")]
+fn send(url: &str, payload: &serde_json::Value) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()?;
+    let response = client.post(url).json(payload).send()?;
+    if !response.status().is_success() {
+        return Err(format!("webhook answered {}", response.status()).into());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "webhooks"))]
+fn send(_url: &str, _payload: &serde_json::Value) -> Result<()> {
+    Err("this build has no `webhooks` feature, regression alerts can't be posted".into())
+}
@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::influx;
+use crate::metadata::Metadata;
+use crate::Result;
+
+// default language table, mirrors the command lines in the suite scripts
+const DEFAULT_CONFIG: &str = include_str!("../orchestrate.json");
+
+#[derive(Args)]
+pub struct OrchestrateArgs {
+    /// Language command table (defaults to the built-in orchestrate.json)
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Directory holding the suite folders, with every implementation already compiled
+    #[arg(long, default_value = ".")]
+    root: PathBuf,
+    /// Scale factor passed to every implementation
+    #[arg(long, default_value_t = 3)]
+    scale: usize,
+    /// Runs per implementation, the median is reported
+    #[arg(long, default_value_t = 3)]
+    runs: usize,
+    /// Only run these suites (comma separated)
+    #[arg(long, value_delimiter = ',')]
+    suites: Vec<String>,
+    /// Only run these languages (comma separated)
+    #[arg(long, value_delimiter = ',')]
+    languages: Vec<String>,
+    /// Also write the combined report as JSON to this file
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Push the results as line-protocol points to this InfluxDB write URL
+    #[arg(long)]
+    influx_url: Option<String>,
+    /// Also write the line-protocol points to this file
+    #[arg(long)]
+    line_protocol: Option<PathBuf>,
+}
+
+// command lines per language, with optional per-suite replacements
+#[derive(Deserialize)]
+pub(crate) struct OrchestrateConfig {
+    reference: String,
+    pub(crate) suites: Vec<String>,
+    languages: BTreeMap<String, String>,
+    #[serde(default)]
+    overrides: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct LanguageResult {
+    pub(crate) language: String,
+    pub(crate) command: String,
+    pub(crate) samples_ms: Vec<f64>,
+    pub(crate) median_ms: Option<f64>,
+    pub(crate) relative_to_reference: Option<f64>,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct SuiteComparison {
+    pub(crate) suite: String,
+    pub(crate) reference_ms: Option<f64>,
+    pub(crate) results: Vec<LanguageResult>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct OrchestrateReport {
+    pub(crate) metadata: Metadata,
+    pub(crate) scale: usize,
+    pub(crate) runs: usize,
+    pub(crate) reference: String,
+    pub(crate) suites: Vec<SuiteComparison>,
+}
+
+// fill in the {suite}, {scale}, {exe}, {python} and {cp_sep} placeholders
+fn expand_command(template: &str, suite: &str, scale: usize) -> String {
+    let (python, cp_sep) = if cfg!(windows) { ("python", ";") } else { ("python3", ":") };
+    template
+        .replace("{suite}", suite)
+        .replace("{scale}", &scale.to_string())
+        .replace("{exe}", std::env::consts::EXE_SUFFIX)
+        .replace("{python}", python)
+        .replace("{cp_sep}", cp_sep)
+}
+
+// run one implementation once and read the total milliseconds it prints last
+pub(crate) fn run_once(command: &str, dir: &Path) -> Result<f64> {
+    let mut parts = command.split_whitespace();
+    let program = parts.next().ok_or("empty command line")?;
+    let output = Command::new(program)
+        .args(parts)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("failed to start `{}`: {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status).into());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let last_line = stdout
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .ok_or("printed no timing")?;
+    // "NaN" and "inf" parse as floats but aren't timings, and would break the medians
+    match last_line.trim().parse::<f64>() {
+        Ok(ms) if ms.is_finite() => Ok(ms),
+        _ => Err(format!("last output line is not a timing: {:?}", last_line.trim()).into()),
+    }
+}
+
+pub(crate) fn median(samples: &[f64]) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+fn run_language(language: &str, command: String, dir: &Path, runs: usize) -> LanguageResult {
+    let mut samples_ms = Vec::with_capacity(runs);
+    let mut error = None;
+    for _ in 0..runs {
+        match run_once(&command, dir) {
+            Ok(ms) => samples_ms.push(ms),
+            Err(err) => {
+                error = Some(err.to_string());
+                break;
+            }
+        }
+    }
+
+    LanguageResult {
+        language: language.to_string(),
+        command,
+        median_ms: if error.is_none() { median(&samples_ms) } else { None },
+        samples_ms,
+        relative_to_reference: None,
+        error,
+    }
+}
+
+fn print_suite(comparison: &SuiteComparison, reference: &str) {
+    println!("{}", comparison.suite);
+    for result in &comparison.results {
+        match (result.median_ms, result.relative_to_reference) {
+            (Some(ms), Some(ratio)) => {
+                println!("  {:<8} {:>12.3} ms  {:>8.2}x {}", result.language, ms, ratio, reference)
+            }
+            (Some(ms), None) => println!("  {:<8} {:>12.3} ms", result.language, ms),
+            _ => println!(
+                "  {:<8} failed: {}",
+                result.language,
+                result.error.as_deref().unwrap_or("no result")
+            ),
+        }
+    }
+    println!();
+}
+
+// read the language table from a file, or fall back to the built-in one
+pub(crate) fn load_config(path: Option<&Path>) -> Result<OrchestrateConfig> {
+    let config_text = match path {
+        Some(path) => fs::read_to_string(path)?,
+        None => DEFAULT_CONFIG.to_string(),
+    };
+    Ok(serde_json::from_str(&config_text)?)
+}
+
+// run the selected suites for the selected languages, empty selections mean everything
+pub(crate) fn compare(
+    config: &OrchestrateConfig,
+    root: &Path,
+    scale: usize,
+    runs: usize,
+    only_suites: &[String],
+    only_languages: &[String],
+) -> OrchestrateReport {
+    let suites: Vec<&String> = config
+        .suites
+        .iter()
+        .filter(|s| only_suites.is_empty() || only_suites.contains(s))
+        .collect();
+    let languages: Vec<(&String, &String)> = config
+        .languages
+        .iter()
+        .filter(|(lang, _)| only_languages.is_empty() || only_languages.contains(lang) || **lang == config.reference)
+        .collect();
+
+    let mut report = OrchestrateReport {
+        metadata: Metadata::collect(),
+        scale,
+        runs,
+        reference: config.reference.clone(),
+        suites: Vec::new(),
+    };
+
+    for suite in suites {
+        let dir = root.join(suite);
+        let mut results = Vec::new();
+        for (language, template) in &languages {
+            let template = config
+                .overrides
+                .get(suite)
+                .and_then(|o| o.get(*language))
+                .unwrap_or(template);
+            let command = expand_command(template, suite, scale);
+            eprintln!("running {} {}: {}", suite, language, command);
+            results.push(run_language(language, command, &dir, runs));
+        }
+
+        // normalize against the reference language, slowest last and failures at the end
+        let reference_ms = results
+            .iter()
+            .find(|r| r.language == config.reference)
+            .and_then(|r| r.median_ms);
+        for result in &mut results {
+            result.relative_to_reference = match (result.median_ms, reference_ms) {
+                (Some(ms), Some(reference)) if reference > 0.0 => Some(ms / reference),
+                _ => None,
+            };
+        }
+        results.sort_by(|a, b| match (a.median_ms, b.median_ms) {
+            (Some(x), Some(y)) => x.total_cmp(&y),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.language.cmp(&b.language),
+        });
+
+        report.suites.push(SuiteComparison {
+            suite: suite.clone(),
+            reference_ms,
+            results,
+        });
+    }
+
+    report
+}
+
+pub fn run(args: OrchestrateArgs) -> Result<()> {
+    let config = load_config(args.config.as_deref())?;
+    let report = compare(&config, &args.root, args.scale, args.runs, &args.suites, &args.languages);
+
+    println!(
+        "Cross-language comparison (scale {}, median of {} runs, relative to {})",
+        report.scale, report.runs, report.reference
+    );
+    println!("Host: {}", report.metadata.summary());
+    println!();
+    for comparison in &report.suites {
+        print_suite(comparison, &report.reference);
+    }
+
+    if let Some(path) = &args.output {
+        fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        println!("Report written to {}", path.display());
+    }
+
+    if args.influx_url.is_some() || args.line_protocol.is_some() {
+        let points = influx::line_protocol(&report);
+        if let Some(path) = &args.line_protocol {
+            fs::write(path, &points)?;
+            println!("Line protocol written to {}", path.display());
+        }
+        if let Some(url) = &args.influx_url {
+            influx::push(url, &points)?;
+            println!("Pushed {} points to {}", points.lines().count(), url);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use crate::suites::{self, SubTest, Suite};
+use crate::Result;
+
+// the scales a size can be searched over, far beyond anything that fits in memory
+const MIN_SCALE: f64 = 1e-6;
+const MAX_SCALE: f64 = 1e6;
+
+// a sub-test whose problem size `--set` pinned, run at the scale that produces it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Override {
+    pub suite: String,
+    pub name: String,
+    // as it was given, e.g. signal_processing.size=8192
+    pub setting: String,
+    pub scale: f64,
+    pub params: String,
+}
+
+pub fn scale_of(overrides: &[Override], suite: &str, name: &str) -> Option<f64> {
+    overrides.iter().find(|o| o.suite == suite && o.name == name).map(|o| o.scale)
+}
+
+// the value of `key` in a sub-test's parameters, which read like "size=256 passes=8"
+fn param(params: &str, key: &str) -> Option<f64> {
+    params
+        .split_whitespace()
+        .filter_map(|p| p.split_once('='))
+        .find(|(k, _)| *k == key)
+        .and_then(|(_, v)| v.parse().ok())
+}
+
+// the scale at which `key` reaches `target`: the sizes are mostly a base size times the
+// scale, so the proportional guess usually hits it, and a bisection over the scale finds
+// the smallest one reaching it otherwise
+fn scale_for(test: &SubTest, key: &str, target: f64) -> std::result::Result<f64, String> {
+    let at = |scale: f64| param(&(test.params)(scale), key).unwrap_or(f64::NAN);
+    let base = at(1.0);
+    let guess = target / base;
+    if guess.is_finite() && guess > 0.0 && at(guess) == target {
+        return Ok(guess);
+    }
+    let (mut low, mut high) = (MIN_SCALE, MAX_SCALE);
+    if at(low) == at(high) {
+        return Err(format!("its {} stays {} at every scale", key, base));
+    }
+    if target > at(high) {
+        return Err(format!("its {} can't grow beyond {}", key, at(high)));
+    }
+    if target <= at(low) {
+        return Ok(low);
+    }
+    // geometric halving, the scale spans twelve orders of magnitude
+    for _ in 0..100 {
+        let middle = (low * high).sqrt();
+        if at(middle) < target {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Ok(high)
+}
+
+// turns TARGET.PARAM=VALUE settings into per sub-test scales, where TARGET is a sub-test
+// (bare or as suite.name) or a whole suite and PARAM one of its parameters, or `scale`
+// itself; a later setting for the same sub-test replaces an earlier one
+pub fn resolve(suites: &[&Suite], settings: &[String]) -> Result<Vec<Override>> {
+    let mut overrides: Vec<Override> = Vec::new();
+    for setting in settings {
+        let parsed = setting
+            .split_once('=')
+            .and_then(|(path, value)| Some((path.rsplit_once('.')?, value.parse::<f64>().ok()?)));
+        let Some(((target, key), value)) = parsed else {
+            return Err(format!("`{}` isn't TARGET.PARAM=VALUE, e.g. signal_processing.size=8192", setting).into());
+        };
+        if !value.is_finite() || value <= 0.0 {
+            return Err(format!("`{}` needs a positive value", setting).into());
+        }
+        let whole_suite = suites.iter().any(|suite| suite.name == target);
+        let tests: Vec<(&Suite, &SubTest)> = suites
+            .iter()
+            .flat_map(|suite| suite.subtests.iter().map(move |test| (*suite, test)))
+            .filter(|(suite, test)| suite.name == target || suites::names_test(target, suite.name, test.name))
+            .collect();
+        if tests.is_empty() {
+            return Err(format!("`{}` names none of the selected suites or sub-tests", target).into());
+        }
+
+        let mut applied = 0;
+        for (suite, test) in tests {
+            let scale = if key == "scale" {
+                value
+            } else if param(&(test.params)(1.0), key).is_none() {
+                // a suite-wide setting only touches the sub-tests that have the parameter
+                if whole_suite {
+                    continue;
+                }
+                return Err(format!(
+                    "{}.{} has no `{}` parameter, its parameters are: {}",
+                    suite.name,
+                    test.name,
+                    key,
+                    (test.params)(1.0)
+                )
+                .into());
+            } else {
+                scale_for(test, key, value).map_err(|e| format!("cannot set {}.{}: {}", suite.name, test.name, e))?
+            };
+            let params = (test.params)(scale);
+            if key != "scale" && param(&params, key) != Some(value) {
+                eprintln!(
+                    "warning: {}.{} can't run at {}={} exactly, it runs at {}",
+                    suite.name, test.name, key, value, params
+                );
+            }
+            overrides.retain(|o| !(o.suite == suite.name && o.name == test.name));
+            overrides.push(Override {
+                suite: suite.name.to_string(),
+                name: test.name.to_string(),
+                setting: setting.clone(),
+                scale,
+                params,
+            });
+            applied += 1;
+        }
+        if applied == 0 {
+            return Err(format!("no sub-test of {} has a `{}` parameter", target, key).into());
+        }
+    }
+    Ok(overrides)
+}
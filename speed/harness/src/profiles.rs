@@ -0,0 +1,55 @@
+use std::fs;
+use std::path::Path;
+
+use crate::run::RunReport;
+use crate::store;
+use crate::Result;
+
+pub struct Profile {
+    pub name: &'static str,
+    pub description: &'static str,
+    data: &'static str,
+}
+
+// calibration runs of known machines, shipped so results from different hosts can be
+// expressed against the same yardstick; each is a regular `run --output` file of the
+// current sub-tests, recorded at a known commit on a machine its description names.
+// rerecord one when sub-tests are added or resized, a stale one scores against sub-tests
+// and parameters that no longer exist
+pub const PROFILES: &[Profile] = &[Profile {
+    name: "xeon-1cpu-ref",
+    description: "virtualized Intel Xeon (family 6 model 207), 1 vCPU, Linux, release build of f2f47cd, scale 1, median of 3 interleaved rounds",
+    data: include_str!("../profiles/xeon-1cpu-ref.json"),
+}];
+
+// a shipped profile by name, any earlier results file by path, or one run of a result
+// history as `history.jsonl@N` (see store::pick)
+pub fn load(name_or_path: &str) -> Result<RunReport> {
+    if let Some(profile) = PROFILES.iter().find(|p| p.name == name_or_path) {
+        return Ok(serde_json::from_str(profile.data)?);
+    }
+    if let Some((path, index)) = name_or_path.rsplit_once('@') {
+        if let (true, Ok(index)) = (Path::new(path).exists(), index.parse::<isize>()) {
+            return store::pick(Path::new(path), index);
+        }
+    }
+    if Path::new(name_or_path).exists() {
+        let text = fs::read_to_string(name_or_path)?;
+        return Ok(serde_json::from_str(&text)
+            .map_err(|e| format!("invalid profile {}: {}", name_or_path, e))?);
+    }
+    let names: Vec<&str> = PROFILES.iter().map(|p| p.name).collect();
+    Err(format!(
+        "no profile or file named `{}` (shipped profiles: {})",
+        name_or_path,
+        names.join(", ")
+    )
+    .into())
+}
+
+pub fn list() -> Result<()> {
+    for profile in PROFILES {
+        println!("{:<16} {}", profile.name, profile.description);
+    }
+    Ok(())
+}
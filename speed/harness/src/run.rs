@@ -0,0 +1,792 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bench_core::{RngKind, SKIPPED};
+use clap::Args;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::calibrate::{self, Calibration};
+use crate::counters::{self, Counter, Ratio};
+use crate::doctor;
+use crate::dryrun;
+use crate::influx;
+use crate::interrupt;
+use crate::memusage::{self, MemoryUse};
+use crate::metadata::{BuildInfo, Metadata};
+use crate::multiproc;
+use crate::notify::{self, Webhook};
+use crate::orchestrate;
+use crate::overrides::{self, Override};
+use crate::profiles;
+use crate::scaling::{self, ThreadCounts};
+use crate::score::{self, Score, Weights};
+use crate::selftest;
+use crate::store;
+use crate::suites::{self, SubTest, Suite};
+use crate::watch;
+use crate::Result;
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Only run these suites (comma separated, defaults to all of them)
+    #[arg(long = "suite", value_delimiter = ',')]
+    suites: Vec<String>,
+    /// Only run these sub-tests (comma separated, by name or as suite.name)
+    #[arg(long, value_delimiter = ',')]
+    only: Vec<String>,
+    /// Directory holding the suite folders, sub-tests run from inside their suite's folder
+    #[arg(long, default_value = ".")]
+    root: PathBuf,
+    /// Scale factor passed to every sub-test
+    #[arg(long, default_value_t = 1.0, value_parser = parse_scale)]
+    scale: f64,
+    /// Pin one sub-test's (or a whole suite's) problem size regardless of --scale, e.g.
+    /// signal_processing.size=8192 or mathematical.matrix_operations.scale=3; repeatable
+    #[arg(long = "set", value_name = "TARGET.PARAM=VALUE", conflicts_with = "budget")]
+    settings: Vec<String>,
+    /// Draw every kernel's inputs from this generator (lcg, xorshift, pcg64, chacha20)
+    /// instead of its native one, the one its other-language versions use
+    #[arg(long, value_name = "NAME", value_parser = RngKind::parse, default_value = "native")]
+    rng: RngKind,
+    /// Run config (JSON) with score weights, drift and regression thresholds and a webhook
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Score the results against a shipped profile (see `benchmark profiles`) or an earlier results file
+    #[arg(long = "normalize-to", alias = "reference", value_name = "PROFILE")]
+    reference: Option<String>,
+    /// Write the results as JSON to this file
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Append the results to this history file (JSON lines) and warn about drift against it
+    #[arg(long, alias = "record")]
+    store: Option<PathBuf>,
+    /// Push every sub-test's result as a line-protocol point to this InfluxDB write URL
+    #[arg(long)]
+    influx_url: Option<String>,
+    /// Also write the line-protocol points to this file
+    #[arg(long)]
+    line_protocol: Option<PathBuf>,
+    /// Time the sub-tests even though this harness was built without optimizations
+    #[arg(long)]
+    allow_debug: bool,
+    /// Start timing even when the preflight checks (see `benchmark doctor`) fail
+    #[arg(long)]
+    skip_preflight: bool,
+    /// Stop at the first failed sub-test and report the ones after it as skipped
+    #[arg(long, conflicts_with_all = ["dry_run", "self_test"])]
+    strict: bool,
+    /// Write the input files of the selected suites at --scale before running (the io
+    /// suite's data/), replacing any that are already there
+    #[arg(long, conflicts_with_all = ["dry_run", "self_test"])]
+    generate_data: bool,
+    /// Only print the parameters and estimated time, memory and disk use of each sub-test
+    #[arg(long, conflicts_with = "watch")]
+    dry_run: bool,
+    /// Only check that every sub-test kernel still computes the right thing, at tiny sizes
+    #[arg(long, conflicts_with_all = ["dry_run", "watch"])]
+    self_test: bool,
+    /// Only rerun the kernels at --scale and check their outputs against known-good values
+    /// (prime counts, fft round trips, sorted outputs) instead of timing them
+    #[arg(long, conflicts_with_all = ["dry_run", "self_test", "watch", "budget"])]
+    verify: bool,
+    /// Run the sub-tests in a random order, reshuffled every round
+    #[arg(long)]
+    shuffle: bool,
+    /// Run every sub-test N times as N rounds of all of them (A B C A B C) and report the medians
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    interleave: usize,
+    /// Run N copies of each sub-test at once as separate processes and report the slowest,
+    /// to measure whole-machine throughput under process-per-core parallelism
+    #[arg(long, value_name = "N", default_value_t = 1, conflicts_with_all = ["dry_run", "self_test"])]
+    processes: usize,
+    /// Count these hardware events around every sub-test (comma separated); the ones this
+    /// platform can't supply are reported as gaps
+    #[arg(long, value_enum, value_delimiter = ',', conflicts_with_all = ["dry_run", "self_test", "processes"])]
+    counters: Vec<Counter>,
+    /// Record every sub-test's peak resident set, peak heap, bytes allocated and
+    /// allocation count next to its time
+    #[arg(long, conflicts_with_all = ["dry_run", "self_test", "processes"])]
+    memory: bool,
+    /// Pick every sub-test's scale so the whole run takes about this long (e.g. 90s, 30m),
+    /// after a short calibration; the chosen scales are stored with the results
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = watch::parse_interval,
+        conflicts_with_all = ["scale", "reference", "processes", "dry_run", "self_test"]
+    )]
+    budget: Option<Duration>,
+    /// Keep rerunning the selected suites at this interval (e.g. 90s, 30m, 1h)
+    #[arg(long, value_name = "INTERVAL", value_parser = watch::parse_interval)]
+    watch: Option<Duration>,
+    /// Only run the sub-tests with a thread count (the concurrency suite's parallel ones)
+    /// once per count, `auto` for powers of two up to the usable cpus or e.g. 1,2,4,8, and
+    /// print a scaling table
+    #[arg(
+        long,
+        value_name = "auto|N,...",
+        value_parser = scaling::parse_threads,
+        conflicts_with_all = [
+            "dry_run", "self_test", "verify", "budget", "watch", "processes", "counters", "memory",
+            "interleave", "shuffle", "output", "store", "reference", "influx_url", "line_protocol"
+        ]
+    )]
+    threads: Option<ThreadCounts>,
+}
+
+// settings read from --config, see config.example.json
+#[derive(Deserialize)]
+pub struct RunConfig {
+    #[serde(default)]
+    pub weights: Weights,
+    // how far (in percent) a sub-test may move from its recent history before a warning
+    #[serde(default = "default_threshold")]
+    pub drift_threshold_pct: f64,
+    // how much slower (in percent) than the --normalize-to baseline counts as a regression
+    #[serde(default = "default_threshold")]
+    pub regression_threshold_pct: f64,
+    // optional alert target for regressions
+    #[serde(default)]
+    pub webhook: Option<Webhook>,
+    // problem sizes like `--set` takes them, which the command line overrides
+    #[serde(default)]
+    pub set: BTreeMap<String, f64>,
+}
+
+fn default_threshold() -> f64 {
+    10.0
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        RunConfig {
+            weights: Weights::default(),
+            drift_threshold_pct: default_threshold(),
+            regression_threshold_pct: default_threshold(),
+            webhook: None,
+            set: BTreeMap::new(),
+        }
+    }
+}
+
+impl RunConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let config: RunConfig = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| format!("invalid config {}: {}", path.display(), e))?;
+        config.weights.validate()?;
+        Ok(config)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SubTestResult {
+    pub suite: String,
+    pub name: String,
+    pub ms: Option<f64>,
+    pub error: Option<String>,
+    // every round's timing when the run was interleaved, ms is their median
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rounds_ms: Vec<f64>,
+    // hardware counts from --counters, the median over rounds when interleaved
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub counters: BTreeMap<Counter, u64>,
+    // from --memory, the highest peaks and median counts over rounds when interleaved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<MemoryUse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunReport {
+    pub metadata: Metadata,
+    pub scale: f64,
+    pub results: Vec<SubTestResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<Score>,
+    // copies of every sub-test that ran at once, each ms is the slowest copy
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub processes: usize,
+    // where the --counters values came from, and the requested ones it couldn't supply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counter_backend: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub counter_gaps: BTreeMap<Counter, String>,
+    // the per sub-test scales --budget picked, they replace `scale`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<Calibration>,
+    // the sub-tests --set pinned, their scales replace `scale` and the calibrated ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<Override>,
+    // the generator --rng put in place of every kernel's native one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rng: Option<String>,
+    // set when the run was interrupted before every selected sub-test ran
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub incomplete: bool,
+}
+
+// a positive, finite scale factor; fractions run smaller than scale 1
+pub fn parse_scale(text: &str) -> std::result::Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(scale) if scale.is_finite() && scale > 0.0 => Ok(scale),
+        _ => Err(format!("`{}` is not a positive number", text)),
+    }
+}
+
+impl RunReport {
+    // the scale a sub-test actually ran at
+    pub fn scale_of(&self, suite: &str, name: &str) -> f64 {
+        overrides::scale_of(&self.overrides, suite, name)
+            .or_else(|| self.calibration.as_ref().and_then(|c| c.scale_of(suite, name)))
+            .unwrap_or(self.scale)
+    }
+}
+
+fn one() -> usize {
+    1
+}
+
+fn is_one(value: &usize) -> bool {
+    *value == 1
+}
+
+// how many sub-test runs per second the machine got through with every copy going at once
+fn print_throughput(results: &[SubTestResult], processes: usize) {
+    println!("Throughput with {} processes", processes);
+    for result in results {
+        if let Some(ms) = result.ms.filter(|ms| *ms > 0.0) {
+            println!(
+                "  {:<36} {:>10.1} runs/s",
+                format!("{}.{}", result.suite, result.name),
+                processes as f64 * 1000.0 / ms
+            );
+        }
+    }
+    println!();
+}
+
+// per counter, the median of the rounds that supplied it
+fn median_counts(rounds: &[BTreeMap<Counter, u64>]) -> BTreeMap<Counter, u64> {
+    let mut values: BTreeMap<Counter, Vec<f64>> = BTreeMap::new();
+    for round in rounds {
+        for (counter, value) in round {
+            values.entry(*counter).or_default().push(*value as f64);
+        }
+    }
+    values
+        .into_iter()
+        .filter_map(|(counter, values)| Some((counter, orchestrate::median(&values)? as u64)))
+        .collect()
+}
+
+fn print_counters(report: &RunReport, requested: &[Counter], backend: &str) {
+    let supplied: Vec<Counter> = requested.iter().copied().filter(|c| !report.counter_gaps.contains_key(c)).collect();
+    println!("Hardware counters ({})", backend);
+    if !supplied.is_empty() {
+        let ratios: Vec<&Ratio> = counters::RATIOS.iter().filter(|r| r.available(&supplied)).collect();
+        print!("  {:<36}", "");
+        for counter in &supplied {
+            print!(" {:>16}", counter.name());
+        }
+        for ratio in &ratios {
+            print!(" {:>12}", ratio.name);
+        }
+        println!();
+        for result in &report.results {
+            print!("  {:<36}", format!("{}.{}", result.suite, result.name));
+            for counter in &supplied {
+                match result.counters.get(counter) {
+                    Some(value) => print!(" {:>16}", value),
+                    None => print!(" {:>16}", "-"),
+                }
+            }
+            for ratio in &ratios {
+                match ratio.of(&result.counters) {
+                    Some(value) => print!(" {:>12.2}", value),
+                    None => print!(" {:>12}", "-"),
+                }
+            }
+            println!();
+        }
+    }
+    for (counter, reason) in &report.counter_gaps {
+        println!("  {} unavailable: {}", counter.name(), reason);
+    }
+    println!();
+}
+
+fn print_memory(results: &[SubTestResult]) {
+    println!("Memory use");
+    println!(
+        "  {:<36} {:>12} {:>12} {:>12} {:>12}",
+        "", "peak rss", "peak heap", "allocated", "allocations"
+    );
+    for result in results {
+        let Some(memory) = &result.memory else { continue };
+        println!(
+            "  {:<36} {:>12} {:>12} {:>12} {:>12}",
+            format!("{}.{}", result.suite, result.name),
+            dryrun::human_bytes(memory.peak_rss_bytes),
+            dryrun::human_bytes(Some(memory.peak_heap_bytes)),
+            dryrun::human_bytes(Some(memory.allocated_bytes)),
+            memory.allocations
+        );
+    }
+    println!();
+}
+
+fn print_results(results: &[SubTestResult]) {
+    let mut current_suite = "";
+    let mut suite_total = 0.0;
+    for result in results {
+        if result.suite != current_suite {
+            if !current_suite.is_empty() {
+                println!("  {:<24} {:>12.3} ms", "total", suite_total);
+                println!();
+            }
+            println!("{}", result.suite);
+            current_suite = &result.suite;
+            suite_total = 0.0;
+        }
+        match (result.ms, &result.error) {
+            (Some(ms), _) => {
+                suite_total += ms;
+                println!("  {:<24} {:>12.3} ms", result.name, ms);
+            }
+            (None, Some(error)) if error == SKIPPED => println!("  {:<24} {}", result.name, error),
+            (None, error) => println!(
+                "  {:<24} failed: {}",
+                result.name,
+                error.as_deref().unwrap_or("no result")
+            ),
+        }
+    }
+    if !current_suite.is_empty() {
+        println!("  {:<24} {:>12.3} ms", "total", suite_total);
+        println!();
+    }
+}
+
+// run from inside the suite directory, which is where the scratch paths are relative to
+pub fn remove_scratch_files(suite: &Suite) {
+    for file in suite.scratch_files {
+        if let Err(err) = fs::remove_file(file) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("warning: could not remove {}/{}: {}", suite.name, file, err);
+            }
+        }
+    }
+}
+
+// write next to the target and rename over it, so an interrupted write never leaves
+// a truncated results file behind
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let mut temp_name = path.file_name().ok_or("output path has no file name")?.to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+// every suite's generator runs inside its own directory, like its sub-tests
+fn generate_data(suites: &[&Suite], root: &Path, scale: f64) -> Result<()> {
+    let start_dir = env::current_dir()?;
+    for suite in suites {
+        let Some(generate) = suite.generate else { continue };
+        let dir = start_dir.join(root).join(suite.name);
+        env::set_current_dir(&dir).map_err(|e| format!("cannot enter suite directory {}: {}", dir.display(), e))?;
+        eprintln!("generating {} data at scale {}", suite.name, scale);
+        let generated = generate(scale);
+        env::set_current_dir(&start_dir)?;
+        generated.map_err(|e| format!("generating {} data failed: {}", suite.name, e))?;
+    }
+    Ok(())
+}
+
+pub fn selected<'a>(all: &'a [Suite], names: &[String]) -> Vec<&'a Suite> {
+    all.iter()
+        .filter(|suite| names.is_empty() || names.iter().any(|s| s == suite.name))
+        .collect()
+}
+
+fn run_once(
+    args: &RunArgs,
+    all: &[Suite],
+    calibration: Option<&Calibration>,
+    overrides: &[Override],
+    config: &RunConfig,
+    reference: Option<&RunReport>,
+) -> Result<RunReport> {
+    let start_dir = env::current_dir()?;
+    let root = start_dir.join(&args.root);
+    let mut report = RunReport {
+        metadata: Metadata::collect(),
+        scale: args.scale,
+        results: Vec::new(),
+        score: None,
+        processes: args.processes,
+        counter_backend: None,
+        counter_gaps: BTreeMap::new(),
+        calibration: calibration.cloned(),
+        overrides: overrides.to_vec(),
+        rng: (args.rng != RngKind::Native).then(|| args.rng.name().to_string()),
+        incomplete: false,
+    };
+    let mut counters = (!args.counters.is_empty()).then(|| counters::open(&args.counters));
+    if let Some(counters) = &counters {
+        report.counter_backend = Some(counters.backend().to_string());
+        report.counter_gaps = counters.gaps().clone();
+    }
+
+    let suites = selected(all, &args.suites);
+    // every selected sub-test in registry order, which is also the order they're reported in
+    let tests: Vec<(&Suite, &SubTest)> = suites
+        .iter()
+        .flat_map(|suite| suite.subtests.iter().map(move |test| (*suite, test)))
+        .collect();
+    let mut outcomes: Vec<Vec<std::result::Result<f64, String>>> = vec![Vec::new(); tests.len()];
+    let mut counts: Vec<Vec<BTreeMap<Counter, u64>>> = vec![Vec::new(); tests.len()];
+    let mut memory: Vec<Vec<MemoryUse>> = vec![Vec::new(); tests.len()];
+    let mut order: Vec<usize> = (0..tests.len()).collect();
+    let mut rng = rand::thread_rng();
+    let mut stopped = false;
+
+    // one round runs every sub-test once, so repetitions of a test are spread out over
+    // the whole run instead of sharing the same cache and thermal state back to back
+    'rounds: for round in 1..=args.interleave {
+        if args.shuffle {
+            order.shuffle(&mut rng);
+        }
+        for &i in &order {
+            if interrupt::requested() {
+                break 'rounds;
+            }
+            let (suite, test) = tests[i];
+            let dir = root.join(suite.name);
+            env::set_current_dir(&dir)
+                .map_err(|e| format!("cannot enter suite directory {}: {}", dir.display(), e))?;
+            if args.interleave > 1 {
+                eprintln!("running {} {} (round {}/{})", suite.name, test.name, round, args.interleave);
+            } else {
+                eprintln!("running {} {}", suite.name, test.name);
+            }
+            let scale = report.scale_of(suite.name, test.name);
+            outcomes[i].push(if args.processes > 1 {
+                multiproc::run_copies(suite.name, test.name, scale, args.processes)
+            } else {
+                let tracker = args.memory.then(memusage::Tracker::start);
+                if let Some(counters) = &mut counters {
+                    counters.start();
+                }
+                let outcome = (test.run)(scale);
+                if let Some(counters) = &mut counters {
+                    counts[i].push(counters.stop());
+                }
+                if let Some(tracker) = tracker {
+                    memory[i].push(tracker.stop());
+                }
+                outcome
+            });
+            if let (true, Some(Err(err))) = (args.strict, outcomes[i].last()) {
+                eprintln!("{} {} failed, stopping because of --strict: {}", suite.name, test.name, err);
+                stopped = true;
+                break 'rounds;
+            }
+        }
+    }
+    for suite in &suites {
+        if env::set_current_dir(root.join(suite.name)).is_ok() {
+            remove_scratch_files(suite);
+        }
+    }
+    env::set_current_dir(&start_dir)?;
+    let complete = outcomes.iter().filter(|o| o.len() == args.interleave).count();
+    report.incomplete = complete < tests.len();
+
+    for ((((suite, test), outcomes), counts), memory) in tests.iter().zip(outcomes).zip(counts).zip(memory) {
+        if outcomes.is_empty() {
+            if stopped {
+                report.results.push(SubTestResult {
+                    suite: suite.name.to_string(),
+                    name: test.name.to_string(),
+                    ms: None,
+                    error: Some(SKIPPED.to_string()),
+                    rounds_ms: Vec::new(),
+                    counters: BTreeMap::new(),
+                    memory: None,
+                });
+            }
+            continue;
+        }
+        let timings: Vec<f64> = outcomes.iter().filter_map(|o| o.as_ref().ok().copied()).collect();
+        report.results.push(SubTestResult {
+            suite: suite.name.to_string(),
+            name: test.name.to_string(),
+            ms: orchestrate::median(&timings),
+            // a sub-test only counts as failed when none of its rounds produced a timing
+            error: if timings.is_empty() { outcomes.into_iter().find_map(|o| o.err()) } else { None },
+            rounds_ms: if args.interleave > 1 { timings } else { Vec::new() },
+            counters: median_counts(&counts),
+            memory: MemoryUse::combine(&memory),
+        });
+    }
+
+    let scale = match &report.calibration {
+        Some(c) => format!("scales calibrated to a {} budget", dryrun::human_time(Some(c.budget_s * 1000.0))),
+        None => format!("scale {}", report.scale),
+    };
+    match (args.interleave, args.shuffle) {
+        (1, false) => println!("Rust sub-tests ({})", scale),
+        (1, true) => println!("Rust sub-tests ({}, shuffled)", scale),
+        (n, false) => println!("Rust sub-tests ({}, median of {} interleaved rounds)", scale, n),
+        (n, true) => println!("Rust sub-tests ({}, median of {} shuffled rounds)", scale, n),
+    }
+    println!("Host: {}", report.metadata.summary());
+    if let Some(rng) = &report.rng {
+        println!("Inputs from the {} generator instead of each kernel's own", rng);
+    }
+    for o in &report.overrides {
+        println!("Set by {}: {}.{} runs at {} (scale {:.4})", o.setting, o.suite, o.name, o.params, o.scale);
+    }
+    if report.processes > 1 {
+        println!("{} copies of each sub-test ran at once, each time is the slowest copy", report.processes);
+    }
+    println!();
+    print_results(&report.results);
+    if report.processes > 1 {
+        print_throughput(&report.results, report.processes);
+    }
+    if let Some(backend) = &report.counter_backend {
+        print_counters(&report, &args.counters, backend);
+    }
+    if args.memory {
+        print_memory(&report.results);
+    }
+
+    if let (Some(reference), Some(name)) = (reference, &args.reference) {
+        if reference.scale != report.scale {
+            eprintln!(
+                "warning: reference profile was recorded at scale {}, this run used scale {}, so its sub-tests are left out of the score",
+                reference.scale, report.scale
+            );
+        }
+        let score = score::score(&report, reference, name, &config.weights);
+        score::print_score(&score);
+        report.score = Some(score);
+    }
+
+    if report.incomplete {
+        println!(
+            "{}: {} of {} sub-tests ran every round, the results are marked incomplete",
+            if stopped { "Stopped at the first failure" } else { "Interrupted" },
+            complete,
+            tests.len()
+        );
+    }
+
+    if let Some(path) = &args.output {
+        write_atomically(path, &serde_json::to_string_pretty(&report)?)?;
+        println!("Results written to {}", path.display());
+    }
+
+    Ok(report)
+}
+
+pub fn run(args: RunArgs) -> Result<()> {
+    let mut all = suites::all();
+    suites::check_selection(&all, &args.suites)?;
+    suites::retain_only(&mut all, &args.suites, &args.only)?;
+    bench_core::select_rng(args.rng);
+
+    // read the config and reference first so a bad path fails before the long part
+    let config = match &args.config {
+        Some(path) => RunConfig::load(path)?,
+        None => RunConfig::default(),
+    };
+    let reference = match &args.reference {
+        Some(name) => Some(profiles::load(name)?),
+        None => None,
+    };
+
+    // the config's sizes first, so the command line's replace them
+    let settings: Vec<String> = config
+        .set
+        .iter()
+        .map(|(path, value)| format!("{}={}", path, value))
+        .chain(args.settings.iter().cloned())
+        .collect();
+    let overrides = overrides::resolve(&selected(&all, &args.suites), &settings)?;
+
+    let mut history = match &args.store {
+        Some(path) => store::load(path)?,
+        None => Vec::new(),
+    };
+
+    interrupt::install();
+
+    if args.processes == 0 {
+        return Err("--processes needs at least one process".into());
+    }
+    if args.interleave == 0 {
+        return Err("--interleave needs at least one round".into());
+    }
+
+    if args.self_test {
+        return selftest::self_test(&selected(&all, &args.suites));
+    }
+    if args.verify {
+        return selftest::verify(&selected(&all, &args.suites), args.scale);
+    }
+
+    let build = BuildInfo::current();
+    if build.unoptimized() {
+        let what = format!("this harness was built without optimizations (opt-level {})", build.opt_level);
+        if !args.allow_debug {
+            return Err(format!(
+                "{}, its timings say nothing about rust performance; rebuild it with `cargo build --release` or pass --allow-debug",
+                what
+            )
+            .into());
+        }
+        eprintln!("warning: {}, the results are marked as unoptimized", what);
+    }
+
+    if args.dry_run {
+        let hostname = Metadata::collect().hostname;
+        return dryrun::dry_run(&selected(&all, &args.suites), &args.root, args.scale, &overrides, &history, &hostname);
+    }
+
+    if args.generate_data {
+        generate_data(&selected(&all, &args.suites), &args.root, args.scale)?;
+    }
+
+    if !args.skip_preflight {
+        let checks = doctor::preflight(&selected(&all, &args.suites), &args.root, args.scale);
+        let failures = doctor::failed(&checks);
+        if failures > 0 {
+            println!("Preflight checks");
+            doctor::print_checks(&checks, true);
+            println!();
+            return Err(format!("{} preflight check(s) failed, fix them or pass --skip-preflight", failures).into());
+        }
+    }
+
+    if let Some(threads) = &args.threads {
+        return scaling::sweep(&selected(&all, &args.suites), &args.root, &threads.0, args.scale, &overrides);
+    }
+
+    // calibrated once, so every run of a watch session uses the same scales
+    let calibration = match args.budget {
+        Some(budget) => Some(calibrate::calibrate(&selected(&all, &args.suites), &args.root, budget, args.interleave)?),
+        None => None,
+    };
+
+    // the failed sub-tests of the latest run, which decide the exit status
+    let mut failed: Vec<String>;
+    loop {
+        let report = run_once(&args, &all, calibration.as_ref(), &overrides, &config, reference.as_ref())?;
+        failed = report
+            .results
+            .iter()
+            .filter(|r| r.ms.is_none())
+            .map(|r| format!("{}.{}", r.suite, r.name))
+            .collect();
+
+        for warning in watch::drift_warnings(&history, &report, config.drift_threshold_pct) {
+            eprintln!("warning: {}", warning);
+        }
+        if let Some(path) = &args.store {
+            store::append(path, &report)?;
+        }
+        if args.influx_url.is_some() || args.line_protocol.is_some() {
+            let points = influx::run_line_protocol(&report);
+            if let Some(path) = &args.line_protocol {
+                fs::write(path, &points)?;
+                println!("Line protocol written to {}", path.display());
+            }
+            if let Some(url) = &args.influx_url {
+                // like the webhook, an unreachable influx shouldn't end a watch session
+                match influx::push(url, &points) {
+                    Ok(()) => println!("Pushed {} points to {}", points.lines().count(), url),
+                    Err(err) => eprintln!("warning: influx push failed: {}", err),
+                }
+            }
+        }
+
+        if let (Some(baseline), Some(name)) = (&reference, &args.reference) {
+            let regressions = notify::find_regressions(&report, baseline, config.regression_threshold_pct);
+            if !regressions.is_empty() {
+                println!("{}", notify::summary(&report, name, &regressions));
+                if let Some(webhook) = &config.webhook {
+                    // a flaky webhook shouldn't end a watch session
+                    match notify::post(webhook, &report, name, config.regression_threshold_pct, &regressions) {
+                        Ok(()) => println!("Regression alert posted to the webhook"),
+                        Err(err) => eprintln!("warning: webhook failed: {}", err),
+                    }
+                }
+            }
+        }
+
+        let Some(interval) = args.watch else { break };
+        if interrupt::requested() {
+            break;
+        }
+        history.push(report);
+        println!("Next run in {}s", interval.as_secs());
+        println!();
+
+        // sleep in short steps so ctrl-c doesn't have to wait out the interval
+        let wake = Instant::now() + interval;
+        while Instant::now() < wake && !interrupt::requested() {
+            thread::sleep(Duration::from_millis(200));
+        }
+        if interrupt::requested() {
+            break;
+        }
+    }
+
+    // failed sub-tests count as zero in the totals, so a run with any of them must not
+    // look like a successful one to scripts
+    if !failed.is_empty() {
+        return Err(format!("{} sub-test(s) failed or were skipped: {}", failed.len(), failed.join(", ")).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::{profiles, suites, Cli, Command};
+
+    // `run --normalize-to NAME` loads every shipped profile, and each one only names
+    // sub-tests the suites built in here still have
+    #[test]
+    fn normalize_to_finds_shipped_profiles() {
+        assert!(!profiles::PROFILES.is_empty(), "no reference profile is shipped");
+        let all = suites::all();
+        for profile in profiles::PROFILES {
+            let Command::Run(args) = Cli::try_parse_from(["benchmark", "run", "--normalize-to", profile.name]).unwrap().command else {
+                unreachable!("parsed a run command");
+            };
+            let report = profiles::load(args.reference.as_deref().unwrap()).unwrap();
+            assert!(!report.results.is_empty(), "{} has no results", profile.name);
+            for result in &report.results {
+                if let Some(suite) = all.iter().find(|suite| suite.name == result.suite) {
+                    assert!(
+                        suite.subtests.iter().any(|test| test.name == result.name),
+                        "{} times {}.{}, which no longer exists",
+                        profile.name,
+                        result.suite,
+                        result.name
+                    );
+                }
+            }
+        }
+    }
+}
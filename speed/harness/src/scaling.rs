@@ -0,0 +1,159 @@
+use std::env;
+use std::path::Path;
+
+use bench_core::available_cpus;
+
+use crate::interrupt;
+use crate::metadata::Metadata;
+use crate::overrides::{self, Override};
+use crate::run::remove_scratch_files;
+use crate::suites::{SubTest, Suite};
+use crate::Result;
+
+// the variable the thread-scaled sub-tests take their thread count from
+const THREADS_VAR: &str = "RUST_BENCH_THREADS";
+
+// the thread counts `run --threads` sweeps, smallest first
+#[derive(Clone, Debug)]
+pub struct ThreadCounts(pub Vec<usize>);
+
+// `auto` for the powers of two below the usable cpus and then the cpus themselves, or a
+// comma separated list of counts
+pub fn parse_threads(text: &str) -> std::result::Result<ThreadCounts, String> {
+    if text == "auto" {
+        let cpus = available_cpus();
+        let mut counts: Vec<usize> = std::iter::successors(Some(1usize), |n| Some(n * 2)).take_while(|&n| n < cpus).collect();
+        counts.push(cpus);
+        return Ok(ThreadCounts(counts));
+    }
+    let counts: Option<Vec<usize>> = text
+        .split(',')
+        .map(|count| count.trim().parse::<usize>().ok().filter(|&count| count > 0))
+        .collect();
+    match counts {
+        Some(mut counts) if !counts.is_empty() => {
+            counts.sort_unstable();
+            counts.dedup();
+            Ok(ThreadCounts(counts))
+        }
+        _ => Err(format!("`{}` is neither auto nor a list of thread counts", text)),
+    }
+}
+
+fn threads_label(threads: usize) -> String {
+    if threads == 1 {
+        "1 thread".to_string()
+    } else {
+        format!("{} threads", threads)
+    }
+}
+
+// one sub-test's outcome at every thread count of the sweep
+struct ScalingRow {
+    name: String,
+    outcomes: Vec<std::result::Result<f64, String>>,
+}
+
+fn print_table(rows: &[ScalingRow], counts: &[usize]) {
+    print!("  {:<28}", "");
+    for &threads in counts {
+        print!(" {:>23}", threads_label(threads));
+    }
+    println!();
+    for row in rows {
+        print!("  {:<28}", row.name);
+        let base = row.outcomes.first().and_then(|o| o.as_ref().ok().copied());
+        for outcome in &row.outcomes {
+            match (outcome, base) {
+                (Ok(ms), Some(base)) if *ms > 0.0 => print!(" {:>12.3} ms {:>6.2}x", ms, base / ms),
+                (Ok(ms), _) => print!(" {:>12.3} ms {:>7}", ms, "-"),
+                (Err(_), _) => print!(" {:>23}", "failed"),
+            }
+        }
+        println!();
+    }
+    println!();
+}
+
+// runs every selected thread-scaled sub-test once per thread count, from inside its suite
+// directory, and prints each one's times with the speedup over the smallest count
+pub fn sweep(suites: &[&Suite], root: &Path, counts: &[usize], scale: f64, overrides: &[Override]) -> Result<()> {
+    let tests: Vec<(&Suite, &SubTest)> = suites
+        .iter()
+        .flat_map(|suite| {
+            suite
+                .subtests
+                .iter()
+                .filter(|test| suite.thread_scaled.contains(&test.name))
+                .map(move |test| (*suite, test))
+        })
+        .collect();
+    if tests.is_empty() {
+        let scaled: Vec<String> = suites
+            .iter()
+            .flat_map(|suite| suite.thread_scaled.iter().map(move |name| format!("{}.{}", suite.name, name)))
+            .collect();
+        if scaled.is_empty() {
+            return Err("none of the selected suites has sub-tests with a thread count to sweep".into());
+        }
+        return Err(format!("none of the selected sub-tests has a thread count to sweep, these do: {}", scaled.join(", ")).into());
+    }
+
+    let start_dir = env::current_dir()?;
+    let previous = env::var_os(THREADS_VAR);
+    let mut rows: Vec<ScalingRow> = Vec::new();
+    'tests: for (suite, test) in &tests {
+        let dir = start_dir.join(root).join(suite.name);
+        env::set_current_dir(&dir).map_err(|e| format!("cannot enter suite directory {}: {}", dir.display(), e))?;
+        let scale = overrides::scale_of(overrides, suite.name, test.name).unwrap_or(scale);
+        let mut outcomes = Vec::with_capacity(counts.len());
+        for &threads in counts {
+            if interrupt::requested() {
+                break 'tests;
+            }
+            eprintln!("running {} {} on {}", suite.name, test.name, threads_label(threads));
+            env::set_var(THREADS_VAR, threads.to_string());
+            outcomes.push((test.run)(scale));
+        }
+        rows.push(ScalingRow {
+            name: format!("{}.{}", suite.name, test.name),
+            outcomes,
+        });
+    }
+    match previous {
+        Some(value) => env::set_var(THREADS_VAR, value),
+        None => env::remove_var(THREADS_VAR),
+    }
+    for suite in suites {
+        if env::set_current_dir(start_dir.join(root).join(suite.name)).is_ok() {
+            remove_scratch_files(suite);
+        }
+    }
+    env::set_current_dir(&start_dir)?;
+
+    println!("Thread scaling (scale {}, speedup over {})", scale, threads_label(counts[0]));
+    println!("Host: {}", Metadata::collect().summary());
+    for o in overrides {
+        println!("Set by {}: {}.{} runs at {} (scale {:.4})", o.setting, o.suite, o.name, o.params, o.scale);
+    }
+    println!("The thread pools run twice as many threads as their column says");
+    println!();
+    print_table(&rows, counts);
+
+    let mut failed = 0;
+    for row in &rows {
+        for (threads, outcome) in counts.iter().zip(&row.outcomes) {
+            if let Err(err) = outcome {
+                println!("  {} on {} failed: {}", row.name, threads_label(*threads), err);
+                failed += 1;
+            }
+        }
+    }
+    if rows.len() < tests.len() {
+        println!("Interrupted: {} of {} sub-tests ran at every thread count", rows.len(), tests.len());
+    }
+    if failed > 0 {
+        return Err(format!("{} sweep run(s) failed", failed).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::run::RunReport;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SuiteScore {
+    pub suite: String,
+    pub score: f64,
+    pub tests: usize,
+    pub weight: f64,
+}
+
+// composite score against a reference profile, 1.0 means as fast as the reference
+// and 2.0 twice as fast
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Score {
+    pub reference: String,
+    pub overall: Option<f64>,
+    pub suites: Vec<SuiteScore>,
+    // sub-tests without a timing on either side, left out of the score
+    pub missing: Vec<String>,
+    // sub-tests that ran at another scale than in the reference (--set, --budget or a
+    // different --scale), left out of the score too
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rescaled: Vec<String>,
+}
+
+// per-test and per-suite weights from the run config, keyed "io" or "io.csv_read";
+// anything not listed weighs 1 and a weight of 0 leaves it out of the score
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Weights(pub BTreeMap<String, f64>);
+
+impl Weights {
+    pub fn suite(&self, suite: &str) -> f64 {
+        self.0.get(suite).copied().unwrap_or(1.0)
+    }
+
+    pub fn subtest(&self, suite: &str, name: &str) -> f64 {
+        self.0.get(&format!("{}.{}", suite, name)).copied().unwrap_or(1.0)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self.0.iter().find(|(_, w)| !w.is_finite() || **w < 0.0) {
+            Some((key, weight)) => Err(format!("weight for `{}` must be a non-negative number, got {}", key, weight)),
+            None => Ok(()),
+        }
+    }
+}
+
+// weighted geometric mean of (value, weight) pairs, zero-weight pairs are ignored
+pub fn geometric_mean(values: &[(f64, f64)]) -> Option<f64> {
+    let weighted: Vec<&(f64, f64)> = values.iter().filter(|(_, w)| *w > 0.0).collect();
+    if weighted.is_empty() || weighted.iter().any(|(v, _)| *v <= 0.0) {
+        return None;
+    }
+    let total_weight: f64 = weighted.iter().map(|(_, w)| w).sum();
+    let log_sum: f64 = weighted.iter().map(|(v, w)| w * v.ln()).sum();
+    Some((log_sum / total_weight).exp())
+}
+
+// geometric mean of the per-test speedups within each suite, and of the suite scores
+// overall, so no single long-running test or suite dominates the number
+pub fn score(report: &RunReport, reference: &RunReport, reference_name: &str, weights: &Weights) -> Score {
+    let results = &report.results;
+    let reference_ms: BTreeMap<(&str, &str), f64> = reference
+        .results
+        .iter()
+        .filter_map(|r| r.ms.map(|ms| ((r.suite.as_str(), r.name.as_str()), ms)))
+        .collect();
+
+    let mut ratios: BTreeMap<&str, Vec<(f64, f64)>> = BTreeMap::new();
+    let mut missing = Vec::new();
+    let mut rescaled = Vec::new();
+    for result in results {
+        if report.scale_of(&result.suite, &result.name) != reference.scale_of(&result.suite, &result.name) {
+            rescaled.push(format!("{}.{}", result.suite, result.name));
+            continue;
+        }
+        let pair = (result.ms, reference_ms.get(&(result.suite.as_str(), result.name.as_str())));
+        match pair {
+            (Some(ms), Some(&reference)) if ms > 0.0 && reference > 0.0 => {
+                let weight = weights.subtest(&result.suite, &result.name);
+                ratios.entry(result.suite.as_str()).or_default().push((reference / ms, weight));
+            }
+            _ => missing.push(format!("{}.{}", result.suite, result.name)),
+        }
+    }
+
+    // keep the suites in the order they ran
+    let mut suites = Vec::new();
+    for result in results {
+        if suites.iter().any(|s: &SuiteScore| s.suite == result.suite) {
+            continue;
+        }
+        if let Some(values) = ratios.get(result.suite.as_str()) {
+            if let Some(score) = geometric_mean(values) {
+                suites.push(SuiteScore {
+                    suite: result.suite.clone(),
+                    score,
+                    tests: values.iter().filter(|(_, w)| *w > 0.0).count(),
+                    weight: weights.suite(&result.suite),
+                });
+            }
+        }
+    }
+
+    let suite_scores: Vec<(f64, f64)> = suites.iter().map(|s| (s.score, s.weight)).collect();
+    Score {
+        reference: reference_name.to_string(),
+        overall: geometric_mean(&suite_scores),
+        suites,
+        missing,
+        rescaled,
+    }
+}
+
+pub fn print_score(score: &Score) {
+    println!("Score relative to {} (geometric mean, higher is faster)", score.reference);
+    for suite in &score.suites {
+        let weight = if suite.weight != 1.0 { format!(", weight {}", suite.weight) } else { String::new() };
+        println!("  {:<14} {:>8.3}  ({} tests{})", suite.suite, suite.score, suite.tests, weight);
+    }
+    match score.overall {
+        Some(overall) => println!("  {:<14} {:>8.3}", "overall", overall),
+        None => println!("  {:<14} {:>8}", "overall", "n/a"),
+    }
+    if !score.missing.is_empty() {
+        println!("  not scored: {}", score.missing.join(", "));
+    }
+    if !score.rescaled.is_empty() {
+        println!("  not scored, at another scale than the reference: {}", score.rescaled.join(", "));
+    }
+}
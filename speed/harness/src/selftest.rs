@@ -0,0 +1,67 @@
+use std::time::Instant;
+
+use crate::interrupt;
+use crate::suites::Suite;
+use crate::Result;
+
+// a named check and the closure running it
+type Check<'a> = (String, Box<dyn Fn() -> std::result::Result<(), String> + 'a>);
+
+// run every suite's correctness checks; they use their own tiny inputs, so unlike a
+// real run they don't need the generated data
+pub fn self_test(suites: &[&Suite]) -> Result<()> {
+    let checks = suites
+        .iter()
+        .flat_map(|suite| suite.self_checks.iter().map(move |check| (suite.name, check)))
+        .map(|(suite, check)| -> Check { (format!("{}.{}", suite, check.name), Box::new(check.check)) })
+        .collect();
+    run_checks(checks, "self-test")
+}
+
+// rerun the kernels that have verifications at `scale` and check what they computed;
+// unlike the self test this takes about as long as the sub-tests themselves
+pub fn verify(suites: &[&Suite], scale: f64) -> Result<()> {
+    for suite in suites.iter().filter(|suite| suite.verifications.is_empty()) {
+        println!("  --    {}: nothing to verify beyond the self-test", suite.name);
+    }
+    let checks = suites
+        .iter()
+        .flat_map(|suite| suite.verifications.iter().map(move |verification| (suite.name, verification)))
+        .map(|(suite, verification)| -> Check {
+            (format!("{}.{}", suite, verification.name), Box::new(move || (verification.check)(scale)))
+        })
+        .collect();
+    run_checks(checks, "verification")
+}
+
+fn run_checks(checks: Vec<Check>, what: &str) -> Result<()> {
+    let start = Instant::now();
+    let (mut passed, mut failed) = (0, Vec::new());
+    for (name, check) in checks {
+        if interrupt::requested() {
+            break;
+        }
+        match check() {
+            Ok(()) => {
+                println!("  ok    {}", name);
+                passed += 1;
+            }
+            Err(err) => {
+                println!("  FAIL  {}: {}", name, err);
+                failed.push(name);
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} passed, {} failed in {:.1} s",
+        passed,
+        failed.len(),
+        start.elapsed().as_secs_f64()
+    );
+    if !failed.is_empty() {
+        return Err(format!("{} failed: {}", what, failed.join(", ")).into());
+    }
+    Ok(())
+}
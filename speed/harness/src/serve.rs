@@ -0,0 +1,148 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::orchestrate::{self, OrchestrateConfig};
+use crate::Result;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8900")]
+    listen: String,
+    /// Language command table (defaults to the built-in orchestrate.json)
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Directory holding the suite folders, with every implementation already compiled
+    #[arg(long, default_value = ".")]
+    root: PathBuf,
+    /// Scale factor used when a request does not pass one
+    #[arg(long, default_value_t = 1)]
+    scale: usize,
+    /// Runs per implementation used when a request does not pass one
+    #[arg(long, default_value_t = 3)]
+    runs: usize,
+}
+
+struct Response {
+    status: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(body: String) -> Self {
+        Response { status: "200 OK", body }
+    }
+
+    fn error(status: &'static str, message: &str) -> Self {
+        Response {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+// split "/run?suite=io&scale=2" into the path and its key/value pairs
+fn parse_target(target: &str) -> (&str, Vec<(&str, &str)>) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .collect();
+    (path, params)
+}
+
+fn handle_run(params: &[(&str, &str)], args: &ServeArgs, config: &OrchestrateConfig) -> Response {
+    let mut suites = Vec::new();
+    let mut languages = Vec::new();
+    let mut scale = args.scale;
+    let mut runs = args.runs;
+
+    for (key, value) in params {
+        match *key {
+            "suite" => suites.extend(value.split(',').map(str::to_string)),
+            "language" | "languages" => languages.extend(value.split(',').map(str::to_string)),
+            "scale" => match value.parse() {
+                Ok(v) if v > 0 => scale = v,
+                _ => return Response::error("400 Bad Request", "scale must be a positive integer"),
+            },
+            "runs" => match value.parse() {
+                Ok(v) if v > 0 => runs = v,
+                _ => return Response::error("400 Bad Request", "runs must be a positive integer"),
+            },
+            _ => return Response::error("400 Bad Request", &format!("unknown parameter `{}`", key)),
+        }
+    }
+
+    if suites.is_empty() {
+        return Response::error("400 Bad Request", "missing `suite` parameter");
+    }
+    if let Some(unknown) = suites.iter().find(|s| !config.suites.contains(s)) {
+        return Response::error("404 Not Found", &format!("unknown suite `{}`", unknown));
+    }
+
+    let report = orchestrate::compare(config, &args.root, scale, runs, &suites, &languages);
+    match serde_json::to_string_pretty(&report) {
+        Ok(body) => Response::json(body),
+        Err(err) => Response::error("500 Internal Server Error", &err.to_string()),
+    }
+}
+
+fn handle(stream: &mut TcpStream, args: &ServeArgs, config: &OrchestrateConfig) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // drain the headers, the api takes everything from the query string
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim() != "" {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let (path, params) = parse_target(parts.next().unwrap_or(""));
+    eprintln!("{} {}", method, request_line.split_whitespace().nth(1).unwrap_or(""));
+
+    let response = match (method, path) {
+        ("POST", "/run") => handle_run(&params, args, config),
+        ("GET", "/suites") => Response::json(serde_json::to_string(&config.suites)?),
+        (_, "/run") | (_, "/suites") => Response::error("405 Method Not Allowed", "method not allowed"),
+        _ => Response::error("404 Not Found", "not found"),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+pub fn run(args: ServeArgs) -> Result<()> {
+    let config = orchestrate::load_config(args.config.as_deref())?;
+    let listener = TcpListener::bind(&args.listen)?;
+    println!("Listening on http://{} (POST /run?suite=<name>&scale=<n>)", listener.local_addr()?);
+
+    // requests are served one at a time so two benchmark runs never share the machine
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("connection failed: {}", err);
+                continue;
+            }
+        };
+        if let Err(err) = handle(&mut stream, &args, &config) {
+            eprintln!("request failed: {}", err);
+        }
+    }
+
+    Ok(())
+}
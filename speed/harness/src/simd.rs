@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+// which vector instruction sets the binary was compiled for and which the cpu offers;
+// the kernels are plain loops the compiler auto-vectorizes, with no runtime dispatch, so
+// they use exactly the compiled set and anything only detected is left on the table
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SimdInfo {
+    pub compiled: Vec<String>,
+    pub detected: Vec<String>,
+    pub used: Vec<String>,
+}
+
+// sets that each mean a wider or new kind of vector unit, the rest are extensions of them
+const HEADLINE: &[&str] = &["sse2", "sse4.2", "avx", "avx2", "avx512f", "neon", "sve", "sve2"];
+
+// the instruction sets worth naming, oldest first
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+macro_rules! simd_features {
+    ($check:ident) => {
+        [
+            ("sse2", $check!("sse2")),
+            ("sse4.1", $check!("sse4.1")),
+            ("sse4.2", $check!("sse4.2")),
+            ("avx", $check!("avx")),
+            ("avx2", $check!("avx2")),
+            ("fma", $check!("fma")),
+            ("avx512f", $check!("avx512f")),
+            ("avx512bw", $check!("avx512bw")),
+            ("avx512vl", $check!("avx512vl")),
+        ]
+    };
+}
+
+#[cfg(target_arch = "aarch64")]
+macro_rules! simd_features {
+    ($check:ident) => {
+        [("neon", $check!("neon")), ("sve", $check!("sve")), ("sve2", $check!("sve2"))]
+    };
+}
+
+macro_rules! compiled_for {
+    ($feature:tt) => {
+        cfg!(target_feature = $feature)
+    };
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+macro_rules! detected {
+    ($feature:tt) => {
+        std::arch::is_x86_feature_detected!($feature)
+    };
+}
+
+#[cfg(target_arch = "aarch64")]
+macro_rules! detected {
+    ($feature:tt) => {
+        std::arch::is_aarch64_feature_detected!($feature)
+    };
+}
+
+fn names(features: &[(&str, bool)]) -> Vec<String> {
+    features.iter().filter(|(_, on)| *on).map(|(name, _)| name.to_string()).collect()
+}
+
+impl SimdInfo {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+    pub fn detect() -> Self {
+        let compiled = names(&simd_features!(compiled_for));
+        let detected = names(&simd_features!(detected));
+        let used = compiled.iter().filter(|f| detected.contains(f)).cloned().collect();
+        SimdInfo { compiled, detected, used }
+    }
+
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    pub fn detect() -> Self {
+        SimdInfo::default()
+    }
+
+    // the widest set in use and the widest one the cpu would have, for the host line
+    pub fn summary(&self) -> Option<String> {
+        let widest = |sets: &[String]| sets.iter().rev().find(|f| HEADLINE.contains(&f.as_str())).cloned();
+        let used = widest(&self.used)?;
+        match widest(&self.detected) {
+            Some(best) if best != used => Some(format!("simd {} ({} available)", used, best)),
+            _ => Some(format!("simd {}", used)),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::run::RunReport;
+use crate::Result;
+
+// append-only result history, one run report per line
+pub fn append(path: &Path, report: &RunReport) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(report)?)?;
+    Ok(())
+}
+
+// every stored run, oldest first; a missing store is just an empty history
+pub fn load(path: &Path) -> Result<Vec<RunReport>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(path)?;
+    let mut reports = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let report = serde_json::from_str(line)
+            .map_err(|e| format!("{} line {}: {}", path.display(), index + 1, e))?;
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+// one stored run, counted from the oldest for 0 and up and from the latest for -1 and down
+pub fn pick(path: &Path, index: isize) -> Result<RunReport> {
+    let mut reports = load(path)?;
+    let count = reports.len();
+    let position = if index < 0 { count.checked_sub(index.unsigned_abs()) } else { Some(index as usize) };
+    match position.filter(|&p| p < count) {
+        Some(p) => Ok(reports.swap_remove(p)),
+        None => Err(format!("{} holds {} runs, there is no run {}", path.display(), count, index).into()),
+    }
+}
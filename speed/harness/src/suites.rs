@@ -0,0 +1,201 @@
+// the rust suite sources, compiled in so their sub-tests can run in-process; the
+// suite scripts still build each file standalone with their own main, which leaves
+// code the harness never calls. the lints allowed on top of that are for code that
+// predates the harness (the `%` divisibility tests in mathematical, the `3.14` sample
+// value and a borrowed csv record in io), anything newer is held to clippy
+#[cfg(feature = "mathematical")]
+#[allow(dead_code, clippy::manual_is_multiple_of)]
+#[path = "../../mathematical/mathematical.rs"]
+mod mathematical;
+#[cfg(feature = "io")]
+#[allow(dead_code, clippy::approx_constant, clippy::needless_borrows_for_generic_args)]
+#[path = "../../io/io.rs"]
+mod io;
+#[cfg(feature = "memory")]
+#[allow(dead_code)]
+#[path = "../../memory/memory.rs"]
+mod memory;
+#[cfg(feature = "concurrency")]
+#[allow(dead_code)]
+#[path = "../../concurrency/concurrency.rs"]
+mod concurrency;
+#[cfg(feature = "crypto")]
+#[allow(dead_code)]
+#[path = "../../crypto/crypto.rs"]
+mod crypto;
+#[cfg(feature = "compression")]
+#[allow(dead_code)]
+#[path = "../../compression/compression.rs"]
+mod compression;
+#[cfg(feature = "strings")]
+#[allow(dead_code)]
+#[path = "../../strings/strings.rs"]
+mod strings;
+#[cfg(feature = "regex")]
+#[allow(dead_code)]
+#[path = "../../regex/regex.rs"]
+mod regex;
+#[cfg(feature = "graph")]
+#[allow(dead_code)]
+#[path = "../../graph/graph.rs"]
+mod graph;
+#[cfg(feature = "sorting")]
+#[allow(dead_code)]
+#[path = "../../sorting/sorting.rs"]
+mod sorting;
+// the no_std kernels in speed/micro, wrapped into the same registry shape
+#[cfg(feature = "micro")]
+#[path = "micro.rs"]
+mod micro;
+
+pub use bench_core::{SelfCheck, SubTest, Verification};
+#[cfg(feature = "concurrency")]
+pub use concurrency::SPAWN_CHILD_ARG;
+
+#[derive(Default)]
+pub struct Suite {
+    pub name: &'static str,
+    pub subtests: Vec<SubTest>,
+    // tiny-size correctness checks of the sub-test kernels
+    pub self_checks: Vec<SelfCheck>,
+    // full-size checks of what the sub-tests compute, for the suites that have them
+    pub verifications: Vec<Verification>,
+    // paths below are relative to the suite directory
+    // inputs that have to exist before the sub-tests can run
+    pub data_files: &'static [&'static str],
+    // files the sub-tests write and don't clean up
+    pub scratch_files: &'static [&'static str],
+    // writes data_files for a scale, run from inside the suite directory
+    pub generate: Option<fn(f64) -> Result<(), String>>,
+    // sub-tests that take their thread count from RUST_BENCH_THREADS, the ones
+    // `run --threads` sweeps
+    pub thread_scaled: &'static [&'static str],
+}
+
+// only the suite features use it, a build without any of them has nothing to expand
+#[cfg(any(
+    feature = "mathematical",
+    feature = "io",
+    feature = "memory",
+    feature = "concurrency",
+    feature = "crypto",
+    feature = "compression",
+    feature = "strings",
+    feature = "regex",
+    feature = "graph",
+    feature = "sorting",
+    feature = "micro"
+))]
+macro_rules! suite {
+    ($module:ident $(, $field:ident: $value:expr)* $(,)?) => {
+        Suite {
+            name: stringify!($module),
+            subtests: $module::SUBTESTS.to_vec(),
+            self_checks: $module::SELF_CHECKS.to_vec(),
+            $($field: $value,)*
+            ..Default::default()
+        }
+    };
+}
+
+// every suite the scripts know about, whether or not its feature is enabled here
+pub const NAMES: &[&str] = &["mathematical", "io", "memory", "concurrency", "crypto", "compression", "strings", "regex", "graph", "sorting", "micro"];
+
+// every suite compiled into this build, in the order the scripts run them
+pub fn all() -> Vec<Suite> {
+    vec![
+        #[cfg(feature = "mathematical")]
+        {
+            // the rust-only sub-tests, which the standalone binary leaves out of its total
+            let mut suite = suite!(mathematical, verifications: mathematical::VERIFICATIONS.to_vec());
+            suite.subtests.extend_from_slice(mathematical::EXTENDED_SUBTESTS);
+            suite
+        },
+        #[cfg(feature = "io")]
+        {
+            let mut suite = suite!(
+                io,
+                data_files: io::DATA_FILES,
+                scratch_files: io::SCRATCH_FILES,
+                generate: Some(|scale| io::generate_data(scale).map_err(|e| e.to_string())),
+            );
+            suite.subtests.extend_from_slice(io::EXTENDED_SUBTESTS);
+            suite
+        },
+        #[cfg(feature = "memory")]
+        suite!(memory),
+        #[cfg(feature = "concurrency")]
+        {
+            let mut suite = suite!(concurrency, thread_scaled: concurrency::THREAD_SCALED);
+            suite.subtests.extend_from_slice(concurrency::EXTENDED_SUBTESTS);
+            suite
+        },
+        #[cfg(feature = "crypto")]
+        suite!(crypto),
+        #[cfg(feature = "compression")]
+        suite!(compression),
+        #[cfg(feature = "strings")]
+        suite!(strings),
+        #[cfg(feature = "regex")]
+        suite!(regex),
+        #[cfg(feature = "graph")]
+        suite!(graph),
+        #[cfg(feature = "sorting")]
+        suite!(sorting, verifications: sorting::VERIFICATIONS.to_vec()),
+        #[cfg(feature = "micro")]
+        suite!(micro),
+    ]
+}
+
+// fails on the first name that isn't a suite of this build
+pub fn check_selection(all: &[Suite], names: &[String]) -> crate::Result<()> {
+    for name in names {
+        if all.iter().any(|suite| suite.name == name.as_str()) {
+            continue;
+        }
+        if NAMES.contains(&name.as_str()) {
+            return Err(format!("suite `{}` is not built in, enable the `{}` cargo feature", name, name).into());
+        }
+        return Err(format!("unknown suite `{}`", name).into());
+    }
+    Ok(())
+}
+
+// whether `pattern` names this sub-test, bare or as suite.test
+pub fn names_test(pattern: &str, suite: &str, test: &str) -> bool {
+    match pattern.split_once('.') {
+        Some((s, t)) => s == suite && t == test,
+        None => pattern == test,
+    }
+}
+
+// keeps only the sub-tests (and their self checks) named in `only`, dropping suites left
+// empty; fails on a name none of the selected suites has
+pub fn retain_only(all: &mut Vec<Suite>, suites: &[String], only: &[String]) -> crate::Result<()> {
+    if only.is_empty() {
+        return Ok(());
+    }
+    let selected = |suite: &Suite| suites.is_empty() || suites.iter().any(|s| s == suite.name);
+    for pattern in only {
+        let known = all
+            .iter()
+            .filter(|suite| selected(suite))
+            .any(|suite| suite.subtests.iter().any(|t| names_test(pattern, suite.name, t.name)));
+        if !known {
+            let available: Vec<String> = all
+                .iter()
+                .filter(|suite| selected(suite))
+                .flat_map(|suite| suite.subtests.iter().map(move |t| format!("{}.{}", suite.name, t.name)))
+                .collect();
+            return Err(format!("no sub-test `{}` in the selected suites, they have: {}", pattern, available.join(", ")).into());
+        }
+    }
+    for suite in all.iter_mut() {
+        let name = suite.name;
+        suite.subtests.retain(|t| only.iter().any(|p| names_test(p, name, t.name)));
+        suite.self_checks.retain(|c| only.iter().any(|p| names_test(p, name, c.name)));
+        suite.verifications.retain(|v| only.iter().any(|p| names_test(p, name, v.name)));
+    }
+    all.retain(|suite| !suite.subtests.is_empty());
+    Ok(())
+}
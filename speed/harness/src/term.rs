@@ -0,0 +1,64 @@
+use std::io::IsTerminal;
+
+use clap::ValueEnum;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    // auto means color only on a terminal and only when NO_COLOR isn't set
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        }
+    }
+}
+
+// one block character per value, scaled between the smallest and largest value
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max - min <= f64::EPSILON {
+                SPARKS[3]
+            } else {
+                let level = ((v - min) / (max - min) * (SPARKS.len() - 1) as f64).round() as usize;
+                SPARKS[level.min(SPARKS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+// a timing change in percent, red when slower and green when faster than the threshold
+pub fn delta(change_pct: f64, threshold_pct: f64, color: bool) -> String {
+    let text = format!("{:+7.1}%", change_pct);
+    if !color {
+        return text;
+    }
+    if change_pct > threshold_pct {
+        format!("\x1b[31m{}\x1b[0m", text)
+    } else if change_pct < -threshold_pct {
+        format!("\x1b[32m{}\x1b[0m", text)
+    } else {
+        text
+    }
+}
+
+pub fn bold(text: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[1m{}\x1b[0m", text)
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use crate::run::RunReport;
+
+// how many earlier runs make up the baseline a new run is compared with
+const DRIFT_WINDOW: usize = 5;
+
+// "90s", "30m", "1h", "2d" or plain seconds
+pub fn parse_interval(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let (number, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => text.split_at(i),
+        None => (text, "s"),
+    };
+    let value: u64 = number.parse().map_err(|_| format!("invalid interval `{}`", text))?;
+    let seconds = match unit {
+        "s" => value,
+        "m" => value * 60,
+        "h" => value * 3600,
+        "d" => value * 86400,
+        _ => return Err(format!("invalid interval unit in `{}`, use s, m, h or d", text)),
+    };
+    if seconds == 0 {
+        return Err("interval must be longer than zero".to_string());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+// sub-tests that moved more than threshold_pct away from the median of the last few
+// comparable runs (same host, and the sub-test at the same scale) in the history
+pub fn drift_warnings(history: &[RunReport], report: &RunReport, threshold_pct: f64) -> Vec<String> {
+    let comparable: Vec<&RunReport> = history
+        .iter()
+        .rev()
+        .filter(|r| r.metadata.hostname == report.metadata.hostname)
+        .collect();
+
+    let mut warnings = Vec::new();
+    for result in &report.results {
+        let Some(ms) = result.ms else { continue };
+        let scale = report.scale_of(&result.suite, &result.name);
+        let mut previous: Vec<f64> = comparable
+            .iter()
+            .filter(|r| r.scale_of(&result.suite, &result.name) == scale)
+            .take(DRIFT_WINDOW)
+            .filter_map(|r| r.results.iter().find(|p| p.suite == result.suite && p.name == result.name))
+            .filter_map(|p| p.ms)
+            .collect();
+        if previous.is_empty() {
+            continue;
+        }
+        let count = previous.len();
+        let baseline = median(&mut previous);
+        if baseline <= 0.0 {
+            continue;
+        }
+        let change_pct = (ms - baseline) / baseline * 100.0;
+        if change_pct.abs() > threshold_pct {
+            warnings.push(format!(
+                "{}.{} drifted {:+.1}% ({:.3} ms vs median {:.3} ms of the last {} runs)",
+                result.suite, result.name, change_pct, ms, baseline, count
+            ));
+        }
+    }
+    warnings
+}